@@ -9,7 +9,7 @@ use rpocket::{
 
 #[tokio::main]
 async fn main() {
-    let mut pocket_base = PocketBase::new("https://pocketbase.io", "en");
+    let pocket_base = PocketBase::new("https://pocketbase.io", "en");
 
     let config = AdminAuthWithPasswordConfig::<HashMap<String, String>> {
         identity: "test@example.com".to_string(),
@@ -0,0 +1,869 @@
+mod output;
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use rpocket::{
+    error::RPocketError,
+    model::{Log, LogRequest},
+    rpocket::{
+        ApiVersion, PocketBaseBuilder, PocketBaseClient, PocketBaseService, TOKEN_KEY,
+        USER_OR_ADMIN_KEY,
+    },
+    service::{
+        admin::{AdminAuthResponse, AdminAuthWithPasswordConfig},
+        collection::{CollectionExportConfig, CollectionImportConfig},
+        crud::{CRUDDeleteConfig, CRUDGetListConfig, CRUDGetOneConfig, CRUDMutateConfig},
+        health::HealthCheckConfig,
+        log::{LogGetRequestsStatsConfig, LogRequestFilter},
+        oauth2::RecordAuthWithOAuth2FlowConfig,
+        record::{RecordAuthResponse, RecordAuthWithPasswordConfig},
+        setting::{SettingGetAllConfig, SettingUpdateConfig},
+    },
+    store::FileStorage,
+    PocketBase,
+};
+use serde_json::{Map, Value};
+
+use output::Format;
+
+type Client = PocketBase<PocketBaseService>;
+
+/// command-line tool for PocketBase administration.
+#[derive(Debug, Parser)]
+#[command(name = "rpocket", version)]
+struct Cli {
+    /// base url of the PocketBase server.
+    #[arg(
+        long,
+        global = true,
+        env = "POCKETBASE_URL",
+        default_value = "http://127.0.0.1:8090"
+    )]
+    url: String,
+    /// language of the error messages.
+    #[arg(long, global = true, default_value = "en")]
+    lang: String,
+    /// version of the PocketBase server, eg. 0.22 or 0.23, asked to the server if missing.
+    #[arg(long, global = true, env = "POCKETBASE_API_VERSION", value_parser = parse_api_version)]
+    api_version: Option<ApiVersion>,
+    /// file persisting the auth token, defaults to ~/.config/rpocket/auth.json.
+    #[arg(long, global = true, env = "RPOCKET_TOKEN_FILE")]
+    token_file: Option<PathBuf>,
+    /// output format.
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Json)]
+    output: Format,
+    /// comma separated columns of the table output.
+    #[arg(long, global = true, value_delimiter = ',')]
+    columns: Vec<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// authenticate as an admin, or as a record with --collection, and persist the token.
+    Login {
+        /// email or username.
+        identity: String,
+        /// password, read from stdin if missing.
+        #[arg(long, env = "RPOCKET_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+        /// auth collection of the record, admins are used if missing.
+        #[arg(long)]
+        collection: Option<String>,
+    },
+    /// authenticate a record with an oauth2 provider in the browser and persist the token.
+    LoginOauth2 {
+        /// name of the provider, eg. github.
+        provider: String,
+        /// auth collection of the record.
+        #[arg(long, default_value = "users")]
+        collection: String,
+        /// port of the local redirect listener, a free port is picked if 0.
+        #[arg(long, default_value_t = 0)]
+        port: u16,
+    },
+    /// forget the persisted token.
+    Logout,
+    /// manage collections.
+    #[command(subcommand)]
+    Collections(CollectionsCommand),
+    /// manage records.
+    #[command(subcommand)]
+    Records(RecordsCommand),
+    /// manage settings.
+    #[command(subcommand)]
+    Settings(SettingsCommand),
+    /// read request logs.
+    #[command(subcommand)]
+    Logs(LogsCommand),
+    /// check the health of the server.
+    Health,
+}
+
+#[derive(Debug, Subcommand)]
+enum CollectionsCommand {
+    /// list all collections.
+    List,
+    /// export all collections into a file.
+    Export { path: PathBuf },
+    /// import collections from an exported file.
+    Import {
+        path: PathBuf,
+        /// delete the collections missing from the file.
+        #[arg(long)]
+        delete_missing: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RecordsCommand {
+    /// list the records of a collection.
+    List {
+        collection: String,
+        #[arg(long)]
+        filter: Option<String>,
+        #[arg(long)]
+        sort: Option<String>,
+        #[arg(long)]
+        expand: Option<String>,
+        #[arg(long, default_value_t = 1)]
+        page: i64,
+        #[arg(long, default_value_t = 30)]
+        per_page: i64,
+    },
+    /// get a record.
+    Get {
+        collection: String,
+        id: String,
+        #[arg(long)]
+        expand: Option<String>,
+    },
+    /// create a record from json, `@file` or `-` for stdin.
+    Create { collection: String, data: String },
+    /// update a record from json, `@file` or `-` for stdin.
+    Update {
+        collection: String,
+        id: String,
+        data: String,
+    },
+    /// delete a record.
+    Delete { collection: String, id: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum SettingsCommand {
+    /// get all settings.
+    Get,
+    /// update settings from json, `@file` or `-` for stdin.
+    Set { data: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum LogsCommand {
+    /// print the latest request logs.
+    Tail {
+        /// number of logs printed first.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: i64,
+        /// keep polling for new logs.
+        #[arg(short, long)]
+        follow: bool,
+        /// polling interval in seconds.
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// print the hourly requests stats.
+    Stats {
+        #[arg(long)]
+        filter: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout().lock();
+
+    if let Err(error) = run(cli, &mut stdout).await {
+        match error {
+            RPocketError::APIError(error) if !is_empty(&error.data) => {
+                eprintln!("error: {} {}", error.message, error.data)
+            }
+            error => eprintln!("error: {}", error),
+        }
+        std::process::exit(1);
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+// parses a server version, eg. `0.22.8` or `v0.23`, into its API version.
+fn parse_api_version(version: &str) -> Result<ApiVersion, String> {
+    let mut parts = version.trim_start_matches('v').split('.');
+    let (Some(major), Some(minor)) = (parts.next(), parts.next()) else {
+        return Err(format!("invalid version {}, eg. 0.23", version));
+    };
+
+    match (major.parse::<u64>(), minor.parse::<u64>()) {
+        (Ok(0), Ok(minor)) if minor < 23 => Ok(ApiVersion::V0_14),
+        (Ok(_), Ok(_)) => Ok(ApiVersion::V0_23),
+        _ => Err(format!("invalid version {}, eg. 0.23", version)),
+    }
+}
+
+// tokens are stored per server url so switching servers keeps the sessions.
+// the API version is asked to the server once unless given.
+async fn client(cli: &Cli) -> Result<Client, RPocketError> {
+    let token_file = match cli.token_file {
+        Some(ref token_file) => token_file.clone(),
+        None => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".config")
+            .join("rpocket")
+            .join("auth.json"),
+    };
+
+    let base = PocketBaseBuilder::new()
+        .base_url(&cli.url)
+        .lang(&cli.lang)
+        .token_key(format!("{}@{}", TOKEN_KEY, cli.url))
+        .user_or_admin_key(format!("{}@{}", USER_OR_ADMIN_KEY, cli.url))
+        .storage(Arc::new(FileStorage::new(token_file)))
+        .api_version(cli.api_version.unwrap_or_default())
+        .build()?;
+
+    // logging out does not need the server.
+    if cli.api_version.is_none() && !matches!(cli.command, Command::Logout) {
+        base.detect_api_version().await?;
+    }
+
+    Ok(base)
+}
+
+async fn run(cli: Cli, out: &mut dyn Write) -> Result<(), RPocketError> {
+    let base = client(&cli).await?;
+    let print =
+        |out: &mut dyn Write, value: &Value| output::print(out, cli.output, &cli.columns, value);
+
+    match cli.command {
+        Command::Login {
+            ref identity,
+            ref password,
+            ref collection,
+        } => {
+            let password = match password {
+                Some(password) => password.clone(),
+                None => read_password()?,
+            };
+
+            let payload = match collection {
+                None => {
+                    let response = base
+                        .admin()
+                        .auth_with_password::<AdminAuthResponse, HashMap<String, String>>(
+                            &AdminAuthWithPasswordConfig {
+                                identity: identity.clone(),
+                                password,
+                                ..Default::default()
+                            },
+                        )
+                        .await?;
+                    serde_json::to_value(response.admin)?
+                }
+                Some(collection) => {
+                    let response = base
+                        .record(collection)
+                        .auth_with_password::<RecordAuthResponse<Value>, HashMap<String, String>>(
+                            &RecordAuthWithPasswordConfig {
+                                identity: identity.clone(),
+                                password,
+                                ..Default::default()
+                            },
+                        )
+                        .await?;
+                    response.record
+                }
+            };
+
+            print(out, &payload)?;
+        }
+        Command::LoginOauth2 {
+            ref provider,
+            ref collection,
+            port,
+        } => {
+            let response = base
+                .record(collection)
+                .auth_with_oauth2_flow::<RecordAuthResponse<Value>, HashMap<String, String>, _>(
+                    &RecordAuthWithOAuth2FlowConfig {
+                        provider: provider.clone(),
+                        redirect_port: port,
+                        ..Default::default()
+                    },
+                    |auth_url| {
+                        eprintln!("open this url in a browser to log in:\n{}", auth_url);
+                        Ok(())
+                    },
+                )
+                .await?;
+
+            print(out, &response.record)?;
+        }
+        Command::Logout => base.auth_state().clear().await?,
+        Command::Collections(ref command) => match command {
+            CollectionsCommand::List => {
+                let collections = base.collection().get_full_list(&[]).await?;
+                print(out, &serde_json::to_value(collections)?)?;
+            }
+            CollectionsCommand::Export { path } => {
+                let collections = base
+                    .collection()
+                    .export(&CollectionExportConfig {
+                        path: path.clone(),
+                        ..Default::default()
+                    })
+                    .await?;
+                writeln!(
+                    out,
+                    "exported {} collections to {}",
+                    collections.len(),
+                    path.display()
+                )?;
+            }
+            CollectionsCommand::Import {
+                path,
+                delete_missing,
+            } => {
+                let mut config =
+                    CollectionImportConfig::<HashMap<String, String>>::from_file(path)?;
+                config.delete_missing = *delete_missing;
+                let count = config.collections.len();
+
+                base.collection().import(&config).await?;
+                writeln!(
+                    out,
+                    "imported {} collections from {}",
+                    count,
+                    path.display()
+                )?;
+            }
+        },
+        Command::Records(ref command) => match command {
+            RecordsCommand::List {
+                collection,
+                filter,
+                sort,
+                expand,
+                page,
+                per_page,
+            } => {
+                let query_params = [("filter", filter), ("sort", sort), ("expand", expand)]
+                    .into_iter()
+                    .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+                    .collect();
+
+                let list = base
+                    .record(collection)
+                    .crud()
+                    .get_list::<Value>(&CRUDGetListConfig {
+                        page: *page,
+                        per_page: *per_page,
+                        query_params,
+                        ..Default::default()
+                    })
+                    .await?;
+                print(out, &serde_json::to_value(list)?)?;
+            }
+            RecordsCommand::Get {
+                collection,
+                id,
+                expand,
+            } => {
+                let record = base
+                    .record(collection)
+                    .crud()
+                    .get_one::<Value>(&CRUDGetOneConfig {
+                        id: id.clone(),
+                        query_params: expand
+                            .iter()
+                            .map(|expand| ("expand".to_string(), expand.clone()))
+                            .collect(),
+                        ..Default::default()
+                    })
+                    .await?;
+                print(out, &record)?;
+            }
+            RecordsCommand::Create { collection, data } => {
+                let record = base
+                    .record(collection)
+                    .crud()
+                    .mutate::<Value, _>(&CRUDMutateConfig {
+                        body: read_data(data)?,
+                        ..Default::default()
+                    })
+                    .await?;
+                print(out, &record)?;
+            }
+            RecordsCommand::Update {
+                collection,
+                id,
+                data,
+            } => {
+                let record = base
+                    .record(collection)
+                    .crud()
+                    .mutate::<Value, _>(&CRUDMutateConfig {
+                        id: Some(id.clone()),
+                        body: read_data(data)?,
+                        ..Default::default()
+                    })
+                    .await?;
+                print(out, &record)?;
+            }
+            RecordsCommand::Delete { collection, id } => {
+                base.record(collection)
+                    .crud()
+                    .delete(&CRUDDeleteConfig {
+                        id: id.clone(),
+                        ..Default::default()
+                    })
+                    .await?;
+                writeln!(out, "deleted {}/{}", collection, id)?;
+            }
+        },
+        Command::Settings(ref command) => {
+            let settings = match command {
+                SettingsCommand::Get => {
+                    base.setting()
+                        .get_all::<Value>(&SettingGetAllConfig::default())
+                        .await?
+                }
+                SettingsCommand::Set { data } => {
+                    base.setting()
+                        .update::<Value, _>(&SettingUpdateConfig {
+                            body: read_data(data)?,
+                            ..Default::default()
+                        })
+                        .await?
+                }
+            };
+            print(out, &settings)?;
+        }
+        Command::Logs(LogsCommand::Stats { ref filter }) => {
+            let stats = base
+                .log()
+                .get_requests_stats::<Value>(&LogGetRequestsStatsConfig {
+                    query_params: filter
+                        .iter()
+                        .map(|filter| ("filter".to_string(), filter.clone()))
+                        .collect(),
+                    ..Default::default()
+                })
+                .await?;
+            print(out, &Value::Array(stats))?;
+        }
+        Command::Logs(LogsCommand::Tail {
+            lines,
+            follow,
+            interval,
+            ref filter,
+        }) => {
+            tail_logs(
+                &base,
+                cli.output,
+                out,
+                lines,
+                follow,
+                interval,
+                filter.as_deref(),
+            )
+            .await?
+        }
+        Command::Health => {
+            let health = base
+                .health()
+                .check::<Value>(&HealthCheckConfig::default())
+                .await?;
+            print(out, &health)?;
+        }
+    }
+
+    Ok(())
+}
+
+// prints the latest logs oldest first, then polls the logs created since the last one.
+async fn tail_logs(
+    base: &Client,
+    format: Format,
+    out: &mut dyn Write,
+    lines: i64,
+    follow: bool,
+    interval: u64,
+    filter: Option<&str>,
+) -> Result<(), RPocketError> {
+    let mut since = LogRequestFilter::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut per_page = lines.max(1);
+    let mut first = true;
+
+    loop {
+        let mut expressions: Vec<String> = filter
+            .iter()
+            .map(|filter| format!("({})", filter))
+            .collect();
+        let created = since.build_for(base.api_version());
+        if !created.is_empty() {
+            expressions.push(created);
+        }
+
+        // the first fetch prints the latest lines only.
+        let logs = fetch_logs(base, &expressions.join(" && "), per_page, &seen, !first).await?;
+
+        for log in logs.iter().rev() {
+            if !seen.insert(log.base.id.clone()) {
+                continue;
+            }
+
+            match format {
+                Format::Json => writeln!(out, "{}", serde_json::to_string(log)?)?,
+                Format::Table => writeln!(
+                    out,
+                    "{} {} {} {} {}ms",
+                    log.base.created,
+                    log.method.to_uppercase(),
+                    log.status,
+                    log.url,
+                    log.exec_time().unwrap_or_default()
+                )?,
+            }
+        }
+        out.flush()?;
+
+        if !follow {
+            return Ok(());
+        }
+
+        // the created filter is inclusive, `seen` skips the logs of the same instant.
+        if let Some(last) = logs.first() {
+            if let Ok(created) = rpocket::model::parse_date(&last.base.created) {
+                since = LogRequestFilter::new().created(created..);
+                seen.retain(|id| logs.iter().any(|log| &log.base.id == id));
+            }
+        }
+        per_page = 200;
+        first = false;
+
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+    }
+}
+
+// fetches the logs newest first, with `page_back` the pages are fetched until one
+// reaches the seen logs so a burst of requests between two polls is not skipped.
+async fn fetch_logs(
+    base: &Client,
+    filter: &str,
+    per_page: i64,
+    seen: &HashSet<String>,
+    page_back: bool,
+) -> Result<Vec<LogRequest>, RPocketError> {
+    let mut logs = Vec::new();
+
+    for page in 1.. {
+        let list = base
+            .log()
+            .crud()
+            .get_list::<Value>(&CRUDGetListConfig {
+                page,
+                per_page,
+                query_params: vec![
+                    ("sort".to_string(), "-created".to_string()),
+                    ("filter".to_string(), filter.to_string()),
+                ],
+                ..Default::default()
+            })
+            .await?;
+        let full = !list.items.is_empty() && list.items.len() as i64 >= list.per_page;
+
+        let page_logs = list
+            .items
+            .into_iter()
+            .map(|log| log_request(base.api_version(), log))
+            .collect::<Result<Vec<_>, _>>()?;
+        let overlaps = page_logs.iter().any(|log| seen.contains(&log.base.id));
+        logs.extend(page_logs);
+
+        if !page_back || overlaps || !full {
+            break;
+        }
+    }
+
+    Ok(logs)
+}
+
+// reads a listed log, the logs of `v0.23` are converted to request logs.
+fn log_request(api_version: ApiVersion, log: Value) -> Result<LogRequest, RPocketError> {
+    Ok(match api_version {
+        ApiVersion::V0_14 => serde_json::from_value(log)?,
+        ApiVersion::V0_23 => LogRequest::from(serde_json::from_value::<Log>(log)?),
+    })
+}
+
+// prompts for the password on the terminal without echoing it.
+fn read_password() -> Result<String, RPocketError> {
+    Ok(rpassword::prompt_password("password: ")?)
+}
+
+// reads a json object given inline, as `@path` or as `-` for stdin.
+fn read_data(data: &str) -> Result<Map<String, Value>, RPocketError> {
+    let data = match data {
+        "-" => std::io::read_to_string(std::io::stdin())?,
+        data => match data.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => data.to_string(),
+        },
+    };
+
+    match serde_json::from_str(&data)? {
+        Value::Object(map) => Ok(map),
+        _ => Err(RPocketError::ConfigError(
+            "data must be a json object".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rpocket::testing::FakePocketBase;
+
+    async fn rpocket(fake: &FakePocketBase, token_file: &std::path::Path, args: &[&str]) -> String {
+        let cli = Cli::try_parse_from(
+            [
+                "rpocket",
+                "--url",
+                fake.url(),
+                "--token-file",
+                token_file.to_str().unwrap(),
+            ]
+            .iter()
+            .chain(args),
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        run(cli, &mut out).await.unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cli() {
+        let fake = FakePocketBase::start().await.unwrap();
+        fake.create_admin("admin@example.com", "password123")
+            .unwrap();
+        let token_file = std::env::temp_dir()
+            .join(format!("rpocket-cli-{}", std::process::id()))
+            .join("auth.json");
+
+        let health = rpocket(&fake, &token_file, &["health"]).await;
+        assert!(health.contains("API is healthy."));
+
+        let admin = rpocket(
+            &fake,
+            &token_file,
+            &["login", "admin@example.com", "--password", "password123"],
+        )
+        .await;
+        assert!(admin.contains("admin@example.com"));
+
+        // the token is read back from the file by the next commands.
+        fake.create_collection(serde_json::json!({"name": "posts"}))
+            .unwrap();
+
+        let created: Value = serde_json::from_str(
+            &rpocket(
+                &fake,
+                &token_file,
+                &["records", "create", "posts", r#"{"title": "hello"}"#],
+            )
+            .await,
+        )
+        .unwrap();
+        let id = created["id"].as_str().unwrap();
+
+        rpocket(
+            &fake,
+            &token_file,
+            &["records", "update", "posts", id, r#"{"title": "world"}"#],
+        )
+        .await;
+
+        let list = rpocket(
+            &fake,
+            &token_file,
+            &[
+                "records",
+                "list",
+                "posts",
+                "--filter",
+                "title = 'world'",
+                "-o",
+                "table",
+                "--columns",
+                "id,title",
+            ],
+        )
+        .await;
+        assert_eq!(
+            list,
+            format!(
+                "id{}  title\n{}  -----\n{}  world\n",
+                " ".repeat(13),
+                "-".repeat(15),
+                id
+            )
+        );
+
+        let collections = rpocket(
+            &fake,
+            &token_file,
+            &["collections", "list", "-o", "table", "--columns", "name"],
+        )
+        .await;
+        assert_eq!(collections, "name\n-----\nposts\n");
+
+        let deleted = rpocket(&fake, &token_file, &["records", "delete", "posts", id]).await;
+        assert_eq!(deleted, format!("deleted posts/{}\n", id));
+
+        rpocket(&fake, &token_file, &["logout"]).await;
+        let cli = Cli::try_parse_from([
+            "rpocket",
+            "--url",
+            fake.url(),
+            "--token-file",
+            token_file.to_str().unwrap(),
+            "settings",
+            "get",
+        ])
+        .unwrap();
+        assert!(
+            matches!(run(cli, &mut Vec::new()).await, Err(RPocketError::APIError(error)) if error.code == 401)
+        );
+
+        std::fs::remove_dir_all(token_file.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_api_version() {
+        assert_eq!(parse_api_version("0.22.8"), Ok(ApiVersion::V0_14));
+        assert_eq!(parse_api_version("v0.23"), Ok(ApiVersion::V0_23));
+        assert_eq!(parse_api_version("1.0.0"), Ok(ApiVersion::V0_23));
+        assert!(parse_api_version("latest").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cli_detects_api_version() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let token_file = std::env::temp_dir()
+            .join(format!("rpocket-cli-version-{}", std::process::id()))
+            .join("auth.json");
+
+        // the _superusers collection only exists since v0.23.
+        let detect_mock = server
+            .mock("GET", "/api/collections/_superusers/auth-methods")
+            .with_status(200)
+            .with_body("{}")
+            .create_async()
+            .await;
+        let login_mock = server
+            .mock("POST", "/api/collections/_superusers/auth-with-password")
+            .with_status(200)
+            .with_body(r#"{"token":"t","record":{"id":"a","email":"admin@example.com"}}"#)
+            .create_async()
+            .await;
+
+        let cli = Cli::try_parse_from([
+            "rpocket",
+            "--url",
+            url.as_str(),
+            "--token-file",
+            token_file.to_str().unwrap(),
+            "login",
+            "admin@example.com",
+            "--password",
+            "password123",
+        ])
+        .unwrap();
+        let mut out = Vec::new();
+        run(cli, &mut out).await.unwrap();
+
+        detect_mock.assert_async().await;
+        login_mock.assert_async().await;
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("admin@example.com"));
+
+        std::fs::remove_dir_all(token_file.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_pages_back() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let log = |id: &str| {
+            let mut log = LogRequest::default();
+            log.base.id = id.to_string();
+            serde_json::to_string(&log).unwrap()
+        };
+
+        // more logs than a page arrived since the last poll, "b" was printed before.
+        let mut mocks = Vec::new();
+        for (page, ids) in [(1, ["e", "d"]), (2, ["c", "b"])] {
+            let items = ids.map(log).join(",");
+            mocks.push(
+                server
+                    .mock("GET", "/api/logs/requests")
+                    .match_query(mockito::Matcher::UrlEncoded(
+                        "page".into(),
+                        page.to_string(),
+                    ))
+                    .with_status(200)
+                    .with_body(format!(
+                        r#"{{"page":{},"perPage":2,"totalItems":-1,"totalPages":-1,"items":[{}]}}"#,
+                        page, items
+                    ))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let seen = HashSet::from(["b".to_string()]);
+        let logs = fetch_logs(&base, "", 2, &seen, true).await.unwrap();
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        assert_eq!(
+            logs.iter()
+                .map(|log| log.base.id.as_str())
+                .collect::<Vec<_>>(),
+            ["e", "d", "c", "b"]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::io::Write;
+
+use serde_json::Value;
+
+// cells longer than this are truncated in tables.
+const MAX_CELL_WIDTH: usize = 40;
+
+/// Format is the output format of the commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Json,
+    Table,
+}
+
+// writes the value as pretty json or as a table,
+// lists and list results become one row per item.
+pub fn print(
+    out: &mut dyn Write,
+    format: Format,
+    columns: &[String],
+    value: &Value,
+) -> std::io::Result<()> {
+    if format == Format::Json {
+        return writeln!(out, "{}", serde_json::to_string_pretty(value)?);
+    }
+
+    match value {
+        Value::Array(items) => table(out, items, columns),
+        Value::Object(map) => match map.get("items") {
+            Some(Value::Array(items)) => table(out, items, columns),
+            _ => {
+                let rows: Vec<Vec<String>> = map
+                    .iter()
+                    .filter(|(key, _)| columns.is_empty() || columns.contains(key))
+                    .map(|(key, value)| vec![key.clone(), cell(value)])
+                    .collect();
+                write_rows(out, &["field".to_string(), "value".to_string()], &rows)
+            }
+        },
+        value => writeln!(out, "{}", cell(value)),
+    }
+}
+
+fn table(out: &mut dyn Write, items: &[Value], columns: &[String]) -> std::io::Result<()> {
+    let columns = match columns.is_empty() {
+        true => {
+            let mut columns: Vec<String> = Vec::new();
+            for key in items
+                .iter()
+                .filter_map(Value::as_object)
+                .flat_map(|item| item.keys())
+            {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+            columns
+        }
+        false => columns.to_vec(),
+    };
+
+    let rows: Vec<Vec<String>> = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|column| cell(item.get(column).unwrap_or(&Value::Null)))
+                .collect()
+        })
+        .collect();
+
+    write_rows(out, &columns, &rows)
+}
+
+fn write_rows(out: &mut dyn Write, header: &[String], rows: &[Vec<String>]) -> std::io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|column| column.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    writeln!(out, "{}", line(header))?;
+    writeln!(
+        out,
+        "{}",
+        line(
+            &widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+        )
+    )?;
+    for row in rows {
+        writeln!(out, "{}", line(row))?;
+    }
+
+    Ok(())
+}
+
+fn cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+
+    match text.chars().count() > MAX_CELL_WIDTH {
+        true => format!(
+            "{}…",
+            text.chars().take(MAX_CELL_WIDTH - 1).collect::<String>()
+        ),
+        false => text,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_print_table() {
+        let value = serde_json::json!({
+            "page": 1,
+            "items": [
+                {"id": "a", "title": "hello", "tags": ["x"]},
+                {"id": "b", "views": 10, "title": "x".repeat(50)},
+            ],
+        });
+
+        let mut out = Vec::new();
+        print(&mut out, Format::Table, &[], &value).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "id  tags   title{}  views\n--  -----  {}  -----\na   [\"x\"]  hello\nb          {}…  10\n",
+                " ".repeat(35),
+                "-".repeat(40),
+                "x".repeat(39),
+            )
+        );
+
+        let mut out = Vec::new();
+        print(
+            &mut out,
+            Format::Table,
+            &["id".to_string()],
+            &value["items"][1],
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "field  value\n-----  -----\nid     b\n"
+        );
+    }
+}
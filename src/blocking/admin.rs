@@ -0,0 +1,200 @@
+use crate::{
+    error::RPocketError,
+    service::admin::{
+        AdminAuthRefreshConfig, AdminAuthResponse, AdminAuthWithPasswordConfig,
+        AdminConfirmPasswordResetConfig, AdminImpersonateConfig, AdminRequestPasswordResetConfig,
+        AdminRequests,
+    },
+};
+use serde::Serialize;
+
+/// AdminService is the blocking service for the admin.
+pub struct AdminService<'a> {
+    client: &'a super::PocketBase,
+    admin_base_path: &'static [&'static str],
+    requests: AdminRequests,
+}
+
+impl<'a> AdminService<'a> {
+    /// create a new AdminService.
+    pub fn new(client: &'a super::PocketBase) -> Self {
+        AdminService {
+            client,
+            admin_base_path: client.api_version().admin_base_path(),
+            requests: AdminRequests {
+                api_version: client.api_version(),
+            },
+        }
+    }
+
+    /// returns crud service.
+    pub fn crud(&self) -> super::crud::CRUDService<'_> {
+        self.client.crud(self.admin_base_path)
+    }
+
+    /// authenticate with password
+    pub fn auth_with_password<T, B>(
+        &self,
+        config: &AdminAuthWithPasswordConfig<B>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_with_password(config)?;
+        self.client
+            .http()
+            .execute_auth::<AdminAuthResponse, T>(&request, config.without_saving)
+    }
+
+    /// returns a non-refreshable token of an auth record, the admin session is kept.
+    /// the impersonate endpoint exists since `v0.23`.
+    pub fn impersonate<T, B>(&self, config: &AdminImpersonateConfig<B>) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.impersonate(config)?;
+        let response = self.client.http().execute(&request)?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// refreshes the current authenticated admin instance and
+    pub fn auth_refresh<T, B>(&self, config: &AdminAuthRefreshConfig<B>) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_refresh(config)?;
+        self.client
+            .http()
+            .execute_auth::<AdminAuthResponse, T>(&request, config.without_saving)
+    }
+
+    /// ends auth admin password reset request.
+    pub fn request_password_reset<B>(
+        &self,
+        config: &AdminRequestPasswordResetConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        let request = self.requests.request_password_reset(config)?;
+        self.client.http().execute(&request)?;
+
+        Ok(())
+    }
+
+    /// confirms auth admin password reset request.
+    pub fn confirm_password_reset<B>(
+        &self,
+        config: &AdminConfirmPasswordResetConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        let request = self.requests.confirm_password_reset(config)?;
+        self.client.http().execute(&request)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blocking::PocketBase;
+    use crate::rpocket::ApiVersion;
+    use crate::service::auth_state::AuthPayload;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_blocking_admin_auth_with_password() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("POST", "/api/admins/auth-with-password")
+            .with_status(200)
+            .match_body(r#"{"identity":"test","password":"12345678"}"#)
+            .with_body(
+                r#"{
+  "token": "token",
+  "admin": {
+    "id": "b6e4b08274f34e9",
+    "created": "2022-06-22 07:13:09.735Z",
+    "updated": "2022-06-22 07:13:09.735Z",
+    "email": "test@example.com",
+    "avatar": 0
+  }
+                }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = AdminAuthWithPasswordConfig::<HashMap<String, String>> {
+            identity: "test".to_string(),
+            password: "12345678".to_string(),
+            ..Default::default()
+        };
+
+        let response = base
+            .admin()
+            .auth_with_password::<AdminAuthResponse, HashMap<String, String>>(&config);
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(response.token == "token");
+        assert!(response.admin.email == "test@example.com");
+        assert!(base.auth_state().get_token().unwrap().unwrap() == "token");
+        match base.auth_state().get_user_or_admin().unwrap().unwrap() {
+            AuthPayload::Admin(admin) => assert!(admin.base.id == "b6e4b08274f34e9"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_blocking_admin_impersonate() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("POST", "/api/collections/users/impersonate/8171022dc95a4ed")
+            .with_status(200)
+            .with_body(
+                r#"{
+  "token": "user-token",
+  "record": {
+    "id": "8171022dc95a4ed",
+    "collectionId": "_pb_users_auth_",
+    "collectionName": "users",
+    "created": "2024-11-01 07:13:09.735Z",
+    "updated": "2024-11-01 07:13:09.735Z"
+  }
+}"#,
+            )
+            .create();
+
+        let base = crate::blocking::PocketBaseBuilder::new()
+            .base_url(url)
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let user = base
+            .impersonate(&AdminImpersonateConfig::<HashMap<String, String>> {
+                collection: "users".to_string(),
+                id: "8171022dc95a4ed".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(
+            user.auth_state().get_token().unwrap().unwrap(),
+            "user-token"
+        );
+        assert!(base.auth_state().get_token().unwrap().is_none());
+    }
+}
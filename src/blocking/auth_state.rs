@@ -0,0 +1,82 @@
+use crate::error::RPocketError;
+use crate::service::auth_state::AuthPayload;
+
+/// AuthStateService is the blocking service for the auth state.
+pub struct AuthStateService<'a> {
+    client: &'a super::PocketBase,
+    token_key: &'a str,
+    user_or_admin_key: &'a str,
+}
+
+impl<'a> AuthStateService<'a> {
+    /// create a new AuthStateService with custom keys.
+    pub fn new(
+        client: &'a super::PocketBase,
+        token_key: &'a str,
+        user_or_admin_key: &'a str,
+    ) -> Self {
+        AuthStateService {
+            client,
+            token_key,
+            user_or_admin_key,
+        }
+    }
+
+    /// get the token.
+    pub fn get_token(&self) -> Result<Option<String>, RPocketError> {
+        self.client.storage().get(self.token_key)
+    }
+
+    // get the user or admin record.
+    pub fn get_user_or_admin(&self) -> Result<Option<AuthPayload>, RPocketError> {
+        let storage = self.client.storage();
+        let data = storage.get(self.user_or_admin_key)?;
+        match data {
+            Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// clear the storage.
+    pub fn clear(&self) -> Result<(), RPocketError> {
+        let storage = self.client.storage();
+        storage.delete(self.token_key)?;
+        storage.delete(self.user_or_admin_key)
+    }
+
+    // save the token and the user or admin record.
+    pub fn save(&self, token: &str, record: &AuthPayload) -> Result<(), RPocketError> {
+        let storage = self.client.storage();
+        let record = serde_json::to_string(record)?;
+        storage.set(self.token_key, token)?;
+        storage.set(self.user_or_admin_key, &record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{blocking::PocketBase, model::Admin};
+
+    #[test]
+    fn test_blocking_auth_storage() {
+        let base = PocketBase::new("http://hello.world", "en");
+        let auth_service = AuthStateService::new(&base, "foo", "bar");
+
+        assert!(auth_service.get_token().unwrap().is_none());
+
+        let user_or_admin = AuthPayload::Admin(Admin {
+            ..Default::default()
+        });
+        assert!(auth_service.save("token", &user_or_admin).is_ok());
+        assert_eq!(auth_service.get_token().unwrap().unwrap(), "token");
+        assert_eq!(
+            auth_service.get_user_or_admin().unwrap().unwrap(),
+            user_or_admin
+        );
+
+        assert!(auth_service.clear().is_ok());
+        assert!(auth_service.get_token().unwrap().is_none());
+        assert!(auth_service.get_user_or_admin().unwrap().is_none());
+    }
+}
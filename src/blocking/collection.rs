@@ -0,0 +1,115 @@
+use crate::{
+    error::RPocketError,
+    model::Collection,
+    service::{
+        collection::{
+            import_request, write_collections_file, CollectionExportConfig, CollectionImportConfig,
+            CollectionPager,
+        },
+        http::RequestOptions,
+    },
+};
+
+/// CollectionService is the blocking service for collections.
+pub struct CollectionService<'a> {
+    client: &'a super::PocketBase,
+    collection_base_path: [&'static str; 2],
+}
+
+impl<'a> CollectionService<'a> {
+    /// create a new CollectionService.
+    pub fn new(client: &'a super::PocketBase) -> Self {
+        CollectionService {
+            client,
+            collection_base_path: ["api", "collections"],
+        }
+    }
+
+    /// returns crud service.
+    pub fn crud(&self) -> super::crud::CRUDService<'_> {
+        self.client.crud(&self.collection_base_path)
+    }
+
+    /// imports the provided collections.
+    pub fn import<B>(&self, config: &CollectionImportConfig<B>) -> Result<(), RPocketError>
+    where
+        B: serde::Serialize,
+    {
+        let request = import_request(self.client.api_version(), config)?;
+        self.client.http().execute(&request)?;
+        Ok(())
+    }
+
+    /// returns all collections, fetching every page.
+    pub fn get_full_list(
+        &self,
+        query_params: &[(String, String)],
+    ) -> Result<Vec<Collection>, RPocketError> {
+        self.get_full_list_with_options(query_params, &RequestOptions::default())
+    }
+
+    /// returns all collections with the given request options, fetching every page.
+    pub fn get_full_list_with_options(
+        &self,
+        query_params: &[(String, String)],
+        options: &RequestOptions,
+    ) -> Result<Vec<Collection>, RPocketError> {
+        let mut pager = CollectionPager::new(query_params, options);
+
+        loop {
+            let result = self.crud().get_list::<Collection>(&pager.config)?;
+            if !pager.push(result) {
+                return Ok(pager.collections);
+            }
+        }
+    }
+
+    /// exports all collections into the file at config.path.
+    pub fn export(&self, config: &CollectionExportConfig) -> Result<Vec<Collection>, RPocketError> {
+        let collections = self.get_full_list_with_options(&config.query_params, &config.options)?;
+
+        write_collections_file(&config.path, &collections)?;
+
+        Ok(collections)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{blocking::PocketBase, model::BaseModel};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_blocking_collection_import() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("PUT", "/api/collections/import")
+            .with_status(204)
+            .match_header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"collections":[{"id":"1","name":"test"}],"deleteMissing":true}"#.to_string(),
+            ))
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = CollectionImportConfig::<HashMap<String, String>> {
+            collections: vec![Collection {
+                base: BaseModel {
+                    id: "1".to_string(),
+                    ..Default::default()
+                },
+                name: "test".to_string(),
+                ..Default::default()
+            }],
+            delete_missing: true,
+            ..Default::default()
+        };
+
+        let response = base.collection().import(&config);
+        mock.assert();
+        response.unwrap();
+    }
+}
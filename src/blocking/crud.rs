@@ -0,0 +1,243 @@
+use crate::{
+    error::RPocketError,
+    model::ListResult,
+    service::crud::{
+        CRUDDeleteConfig, CRUDGetListConfig, CRUDGetOneConfig, CRUDMutateConfig, CRUDRequests,
+    },
+};
+#[cfg(feature = "multipart")]
+use reqwest::blocking::multipart;
+use serde::Serialize;
+
+/// CRUDService is the blocking service for CRUD operations.
+pub struct CRUDService<'a> {
+    client: &'a super::PocketBase,
+    requests: CRUDRequests<'a>,
+}
+
+impl<'a> CRUDService<'a> {
+    /// create a new CRUDService.
+    pub fn new(client: &'a super::PocketBase, base_path: &'a [&'a str]) -> Self {
+        CRUDService {
+            client,
+            requests: CRUDRequests { base_path },
+        }
+    }
+
+    /// get a list of records.
+    pub fn get_list<T>(&self, config: &CRUDGetListConfig) -> Result<ListResult<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self
+            .client
+            .http()
+            .execute(&self.requests.get_list(config))?;
+
+        Ok(response.json::<ListResult<T>>()?)
+    }
+
+    /// get a record.
+    pub fn get_one<T>(&self, config: &CRUDGetOneConfig) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.client.http().execute(&self.requests.get_one(config))?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// mutate a record
+    /// function will create a new record if config.id is None.
+    /// function will update a record if config.id is Some.
+    pub fn mutate<T, B>(&self, config: &CRUDMutateConfig<B>) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let response = self.client.http().execute(&self.requests.mutate(config)?)?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// multipart mutate a record
+    /// function will create a new record if config.id is None.
+    /// function will update a record if config.id is Some.
+    #[cfg(feature = "multipart")]
+    pub fn multipart_mutate<T>(
+        &self,
+        config: CRUDMutateConfig<multipart::Form>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (method, path) = self.requests.mutate_target(config.id.as_deref());
+        let url = self.client.url(&path)?;
+
+        let request_builder = self
+            .client
+            .request_builder(method, url.as_str())
+            .multipart(config.body)
+            .query(&config.query_params);
+
+        let response = self
+            .client
+            .http()
+            .send_with_options(request_builder, &config.options)?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// delete a record
+    pub fn delete(&self, config: &CRUDDeleteConfig) -> Result<(), RPocketError> {
+        self.client.http().execute(&self.requests.delete(config))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::PocketBase;
+    use crate::model::Record;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_blocking_record_get_list() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/collections/test/records?perPage=10&page=1")
+            .with_status(200)
+            .match_header(reqwest::header::ACCEPT_LANGUAGE.as_str(), "en")
+            .with_body(
+                r#"{ "items": [
+                    {
+                      "id": "d08dfc4f4d84419",
+                      "collectionId": "a98f514eb05f454",
+                      "collectionName": "posts",
+                      "updated": "2022-06-25 11:03:45.876",
+                      "created": "2022-06-25 11:03:45.876",
+                      "title": "test2"
+                    }
+                ],
+                "totalItems": 1,
+                "page": 1,
+                "perPage": 10
+            }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = CRUDGetListConfig {
+            per_page: 10,
+            page: 1,
+            ..Default::default()
+        };
+
+        let response = base
+            .crud(&["api", "collections", "test", "records"])
+            .get_list::<Record>(&config);
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(response.items.len() == 1);
+        assert!(response.items[0].base.id == "d08dfc4f4d84419");
+        assert!(response.items[0].data["title"] == "test2");
+    }
+
+    #[test]
+    fn test_blocking_record_get_one() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/collections/test/records/1")
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "d08dfc4f4d84419",
+                "collectionId": "a98f514eb05f454",
+                "collectionName": "posts",
+                "updated": "2022-06-25 11:03:45.876",
+                "created": "2022-06-25 11:03:45.876",
+                "title": "test2"
+            }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = CRUDGetOneConfig {
+            id: "1".to_string(),
+            ..Default::default()
+        };
+
+        let response = base
+            .crud(&["api", "collections", "test", "records"])
+            .get_one::<Record>(&config);
+        mock.assert();
+        assert!(response.unwrap().base.id == "d08dfc4f4d84419");
+    }
+
+    #[test]
+    fn test_blocking_record_mutate_update() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("PATCH", "/api/collections/test/records/d08dfc4f4d84419")
+            .with_status(200)
+            .match_header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
+            .match_body(r#"{"title":"test2"}"#)
+            .with_body(
+                r#"{
+                "id": "d08dfc4f4d84419",
+                "collectionId": "a98f514eb05f454",
+                "collectionName": "posts",
+                "updated": "2022-06-25 11:03:45.876",
+                "created": "2022-06-25 11:03:45.876",
+                "title": "test2"
+            }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = CRUDMutateConfig {
+            id: Some("d08dfc4f4d84419".to_string()),
+            body: HashMap::from([("title".to_string(), "test2".to_string())]),
+            query_params: Vec::new(),
+            options: Default::default(),
+        };
+
+        let response = base
+            .crud(&["api", "collections", "test", "records"])
+            .mutate::<Record, HashMap<String, String>>(&config);
+        mock.assert();
+        assert!(response.unwrap().data["title"] == "test2");
+    }
+
+    #[test]
+    fn test_blocking_record_delete() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("DELETE", "/api/collections/test/records/d08dfc4f4d84419")
+            .with_status(204)
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = CRUDDeleteConfig {
+            id: "d08dfc4f4d84419".to_string(),
+            query_params: Vec::new(),
+            options: Default::default(),
+        };
+
+        let response = base
+            .crud(&["api", "collections", "test", "records"])
+            .delete(&config);
+        mock.assert();
+        response.unwrap();
+    }
+}
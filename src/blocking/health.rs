@@ -0,0 +1,60 @@
+use crate::{
+    error::RPocketError,
+    service::health::{check_request, HealthCheckConfig},
+};
+
+/// HealthService is the blocking service for health.
+pub struct HealthService<'a> {
+    client: &'a super::PocketBase,
+}
+
+impl<'a> HealthService<'a> {
+    /// create a new HealthService.
+    pub fn new(client: &'a super::PocketBase) -> Self {
+        HealthService { client }
+    }
+
+    /// get the health status of the server.
+    pub fn check<T>(&self, config: &HealthCheckConfig) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.client.http().execute(&check_request(config))?;
+
+        Ok(response.json::<T>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blocking::PocketBase, service::health::HealthCheckResponse};
+
+    #[test]
+    fn test_blocking_health_check() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/health")
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "status": 200,
+                    "message": "API is healthy."
+                }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let response = base
+            .health()
+            .check::<HealthCheckResponse>(&HealthCheckConfig::default());
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.message, "API is healthy.");
+    }
+}
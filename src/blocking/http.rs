@@ -0,0 +1,136 @@
+use crate::error::{APIError, RPocketError};
+use crate::service::auth_state::SavedAuthResponse;
+use crate::service::http::{RequestAuth, RequestOptions};
+use crate::service::request::ApiRequest;
+
+/// HTTPService is the blocking service for sending requests.
+pub struct HTTPService<'a> {
+    client: &'a super::PocketBase,
+}
+
+impl<'a> HTTPService<'a> {
+    /// create a new HTTPService.
+    pub fn new(client: &'a super::PocketBase) -> Self {
+        HTTPService { client }
+    }
+
+    /// send a request.
+    pub fn send(
+        &self,
+        request_builder: reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response, RPocketError> {
+        self.send_with_options(request_builder, &RequestOptions::default())
+    }
+
+    /// send a request with the given options.
+    pub fn send_with_options(
+        &self,
+        mut request_builder: reqwest::blocking::RequestBuilder,
+        options: &RequestOptions,
+    ) -> Result<reqwest::blocking::Response, RPocketError> {
+        let stored_token = match options.auth {
+            RequestAuth::Stored => self.client.auth_state().get_token()?,
+            _ => None,
+        };
+
+        request_builder =
+            request_builder.headers(options.build_headers(self.client.lang(), stored_token)?);
+
+        if let Some(timeout) = options.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder.send()?;
+
+        if !response.status().is_success() {
+            return Err(RPocketError::APIError(response.json::<APIError>()?));
+        }
+
+        Ok(response)
+    }
+
+    // sends a request built by a service.
+    pub(crate) fn execute(
+        &self,
+        request: &ApiRequest<'_>,
+    ) -> Result<reqwest::blocking::Response, RPocketError> {
+        let url = request.url(self.client.base_url())?;
+        let mut request_builder = self
+            .client
+            .request_builder(request.method.clone(), url.as_str())
+            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
+            .query(&request.query);
+
+        if let Some(ref body) = request.body {
+            request_builder = request_builder.body(body.clone());
+        }
+
+        self.send_with_options(request_builder, request.options)
+    }
+
+    // sends an auth request, the token and the record of the response are saved
+    // unless `without_saving` is set.
+    pub(crate) fn execute_auth<A, T>(
+        &self,
+        request: &ApiRequest<'_>,
+        without_saving: bool,
+    ) -> Result<T, RPocketError>
+    where
+        A: SavedAuthResponse,
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.execute(request)?;
+        if without_saving {
+            return Ok(response.json::<T>()?);
+        }
+
+        let auth_response = response.json::<A>()?;
+        let (token, payload) = auth_response.auth_payload();
+        self.client.auth_state().save(token, &payload)?;
+
+        auth_response.into_response()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{blocking::PocketBase, rpocket::TOKEN_KEY};
+
+    #[test]
+    fn test_blocking_http_send() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/")
+            .with_status(400)
+            .match_header(reqwest::header::ACCEPT_LANGUAGE.as_str(), "en")
+            .match_header(reqwest::header::AUTHORIZATION.as_str(), "test")
+            .with_body(
+                r#"{
+                "code": 400,
+                "message": "Bad Request",
+                "data": {}
+            }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        base.storage().set(TOKEN_KEY, "test").unwrap();
+
+        let request_builder = base.request_builder(reqwest::Method::GET, url.as_str());
+        let response = HTTPService::new(&base).send(request_builder).unwrap_err();
+        mock.assert();
+
+        match response {
+            RPocketError::APIError(APIError { code, message, .. }) => {
+                assert!(code == 400);
+                assert!(message == "Bad Request");
+            }
+            _ => {
+                panic!("unexpected error");
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    error::RPocketError,
+    model::{ListResult, LogRequest},
+    service::{
+        crud::CRUDGetListConfig,
+        log::{LogGetRequestsStatsConfig, LogRequestFilter, LogRequests},
+    },
+};
+
+/// LogService is the blocking service for logs.
+pub struct LogService<'a> {
+    client: &'a super::PocketBase,
+    request_base_path: &'static [&'static str],
+    requests: LogRequests,
+}
+
+impl<'a> LogService<'a> {
+    /// create a new LogService.
+    pub fn new(client: &'a super::PocketBase) -> Self {
+        LogService {
+            client,
+            request_base_path: client.api_version().log_base_path(),
+            requests: LogRequests {
+                api_version: client.api_version(),
+            },
+        }
+    }
+
+    /// returns crud service.
+    pub fn crud(&self) -> super::crud::CRUDService<'_> {
+        self.client.crud(self.request_base_path)
+    }
+
+    /// get the requests stats of the server.
+    pub fn get_requests_stats<T>(
+        &self,
+        config: &LogGetRequestsStatsConfig,
+    ) -> Result<Vec<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.client.http().execute(&self.requests.stats(config))?;
+
+        Ok(response.json::<Vec<T>>()?)
+    }
+
+    /// get a list of request logs matching the filter.
+    pub fn get_requests_list(
+        &self,
+        filter: &LogRequestFilter,
+        config: &CRUDGetListConfig,
+    ) -> Result<ListResult<LogRequest>, RPocketError> {
+        let config = self.requests.requests_list_config(filter, config);
+        let logs = self.crud().get_list(&config)?;
+
+        self.requests.requests_list(logs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{blocking::PocketBase, service::log::LogGetRequestsStatsResponse};
+
+    #[test]
+    fn test_blocking_log_get_requests_stats() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/logs/requests/stats")
+            .with_status(200)
+            .with_body(r#"[{"total": 4, "date": "2022-06-01 19:00:00.000"}]"#)
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let response = base
+            .log()
+            .get_requests_stats::<LogGetRequestsStatsResponse>(&Default::default());
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(response.len() == 1);
+        assert!(response[0].total == 4);
+    }
+}
@@ -0,0 +1,356 @@
+//! blocking is a synchronous PocketBase client backed by `reqwest::blocking`.
+//! it shares the configs and models with the async client.
+//!
+//! like `reqwest::blocking`, it must not be used from within an async runtime.
+
+pub mod admin;
+pub mod auth_state;
+pub mod collection;
+pub mod crud;
+pub mod health;
+pub mod http;
+pub mod log;
+pub mod record;
+pub mod setting;
+pub mod storage;
+
+use crate::{
+    error::RPocketError,
+    model::Record,
+    rpocket::{
+        build_default_headers, normalize_base_url, validate_keys, ApiVersion, TOKEN_KEY,
+        USER_OR_ADMIN_KEY,
+    },
+    service::{admin::AdminImpersonateConfig, auth_state::AuthPayload, record::RecordAuthResponse},
+    store,
+};
+use std::sync::Arc;
+
+/// PocketBaseBuilder is the builder for the blocking PocketBase.
+pub struct PocketBaseBuilder {
+    lang: String,
+    token_key: String,
+    user_or_admin_key: String,
+    base_url: String,
+    headers: Vec<(String, String)>,
+    timeout: Option<std::time::Duration>,
+    user_agent: Option<String>,
+    storage: Arc<dyn store::Storage + Sync + Send>,
+    http_client: reqwest::blocking::Client,
+    api_version: ApiVersion,
+}
+
+impl PocketBaseBuilder {
+    /// create a new PocketBaseBuilder.
+    pub fn new() -> Self {
+        PocketBaseBuilder {
+            lang: "en".to_string(),
+            token_key: TOKEN_KEY.to_string(),
+            user_or_admin_key: USER_OR_ADMIN_KEY.to_string(),
+            base_url: "https://pocketbase.io".to_string(),
+            headers: Vec::new(),
+            timeout: None,
+            user_agent: None,
+            storage: Arc::new(store::MemoryStorage::new()),
+            http_client: reqwest::blocking::Client::new(),
+            api_version: ApiVersion::default(),
+        }
+    }
+
+    /// set the language.
+    pub fn lang<T: Into<String>>(mut self, lang: T) -> Self {
+        self.lang = lang.into();
+        self
+    }
+
+    /// set the base URL.
+    /// the URL is validated by `build`, a path prefix like `/pb` is kept.
+    pub fn base_url<T: Into<String>>(mut self, base_url: T) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// set token key
+    pub fn token_key<T: Into<String>>(mut self, token_key: T) -> Self {
+        self.token_key = token_key.into();
+        self
+    }
+
+    /// set user or admin key
+    pub fn user_or_admin_key<T: Into<String>>(mut self, user_or_admin_key: T) -> Self {
+        self.user_or_admin_key = user_or_admin_key.into();
+        self
+    }
+
+    /// add a header sent with every request.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// set the timeout of every request.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// set the user agent.
+    pub fn user_agent<T: Into<String>>(mut self, user_agent: T) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// set the storage.
+    pub fn storage(mut self, storage: Arc<dyn store::Storage + Send + Sync>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// set the http client.
+    pub fn http_client(mut self, http_client: reqwest::blocking::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// set the API version of the server.
+    pub fn api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
+    /// build the PocketBase.
+    /// returns an error if the base url, the headers or the keys are invalid.
+    pub fn build(self) -> Result<PocketBase, RPocketError> {
+        validate_keys(&self.lang, &self.token_key, &self.user_or_admin_key)?;
+
+        Ok(PocketBase {
+            inner: Arc::new(PocketBaseRef {
+                base_url: normalize_base_url(&self.base_url)?,
+                default_headers: build_default_headers(&self.headers, self.user_agent.as_deref())?,
+                timeout: self.timeout,
+                lang: self.lang,
+                token_key: self.token_key,
+                user_or_admin_key: self.user_or_admin_key,
+                storage: self.storage,
+                http_client: self.http_client,
+                api_version: self.api_version,
+            }),
+        })
+    }
+}
+
+impl Default for PocketBaseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// PocketBaseRef is the reference of the blocking PocketBase state.
+struct PocketBaseRef {
+    lang: String,
+    token_key: String,
+    user_or_admin_key: String,
+    base_url: url::Url,
+    default_headers: reqwest::header::HeaderMap,
+    timeout: Option<std::time::Duration>,
+    storage: Arc<dyn store::Storage + Sync + Send>,
+    http_client: reqwest::blocking::Client,
+    api_version: ApiVersion,
+}
+
+/// PocketBase is the blocking client.
+/// it is cheap to clone and clones share the same state.
+#[derive(Clone)]
+pub struct PocketBase {
+    inner: Arc<PocketBaseRef>,
+}
+
+impl PocketBase {
+    /// create a new PocketBase.
+    ///
+    /// # Panics
+    ///
+    /// panics if the base url is invalid, use `PocketBaseBuilder` to handle the error.
+    pub fn new(base_url: &str, lang: &str) -> Self {
+        PocketBaseBuilder::new()
+            .base_url(base_url)
+            .lang(lang)
+            .build()
+            .expect("invalid PocketBase config")
+    }
+
+    /// returns the default language.
+    pub fn lang(&self) -> &str {
+        &self.inner.lang
+    }
+
+    /// returns the base url.
+    pub fn base_url(&self) -> &url::Url {
+        &self.inner.base_url
+    }
+
+    /// returns the url of an endpoint built from its path segments,
+    /// see [`crate::rpocket::build_url`].
+    pub fn url(&self, segments: &[&str]) -> Result<url::Url, RPocketError> {
+        crate::rpocket::build_url(self.base_url(), segments)
+    }
+
+    /// returns the request builder.
+    pub fn request_builder(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> reqwest::blocking::RequestBuilder {
+        let mut request_builder = self
+            .inner
+            .http_client
+            .request(method, url)
+            .headers(self.inner.default_headers.clone());
+
+        if let Some(timeout) = self.inner.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        request_builder
+    }
+
+    /// returns the API version of the server.
+    pub fn api_version(&self) -> ApiVersion {
+        self.inner.api_version
+    }
+
+    /// returns the storage.
+    pub fn storage(&self) -> storage::SyncStorage {
+        storage::SyncStorage::new(self.inner.storage.clone())
+    }
+
+    /// returns a client for the same server with another storage, so another session.
+    pub fn with_storage(&self, storage: Arc<dyn store::Storage + Sync + Send>) -> Self {
+        PocketBase {
+            inner: Arc::new(PocketBaseRef {
+                lang: self.inner.lang.clone(),
+                token_key: self.inner.token_key.clone(),
+                user_or_admin_key: self.inner.user_or_admin_key.clone(),
+                base_url: self.inner.base_url.clone(),
+                default_headers: self.inner.default_headers.clone(),
+                timeout: self.inner.timeout,
+                storage,
+                http_client: self.inner.http_client.clone(),
+                api_version: self.inner.api_version,
+            }),
+        }
+    }
+
+    /// returns a client authenticated as an auth record, with the token of the
+    /// impersonate endpoint in its own `MemoryStorage`, the session of this client is kept.
+    /// the token can't be refreshed, impersonate again once it expired.
+    pub fn impersonate<B>(&self, config: &AdminImpersonateConfig<B>) -> Result<Self, RPocketError>
+    where
+        B: serde::Serialize,
+    {
+        let response = self
+            .admin()
+            .impersonate::<RecordAuthResponse<Record>, B>(config)?;
+
+        let client = self.with_storage(Arc::new(store::MemoryStorage::new()));
+        client
+            .auth_state()
+            .save(&response.token, &AuthPayload::User(response.record))?;
+
+        Ok(client)
+    }
+
+    /// returns auth state service.
+    pub fn auth_state(&self) -> auth_state::AuthStateService<'_> {
+        auth_state::AuthStateService::new(
+            self,
+            &self.inner.token_key,
+            &self.inner.user_or_admin_key,
+        )
+    }
+
+    /// returns http service.
+    pub fn http(&self) -> http::HTTPService<'_> {
+        http::HTTPService::new(self)
+    }
+
+    /// returns crud service.
+    pub fn crud<'a>(&'a self, base_path: &'a [&'a str]) -> crud::CRUDService<'a> {
+        crud::CRUDService::new(self, base_path)
+    }
+
+    /// returns record service.
+    pub fn record<'a>(&'a self, name: &'a str) -> record::RecordService<'a> {
+        record::RecordService::new(self, name)
+    }
+
+    /// returns admin service.
+    pub fn admin(&self) -> admin::AdminService<'_> {
+        admin::AdminService::new(self)
+    }
+
+    /// returns collection service.
+    pub fn collection(&self) -> collection::CollectionService<'_> {
+        collection::CollectionService::new(self)
+    }
+
+    /// returns log service.
+    pub fn log(&self) -> log::LogService<'_> {
+        log::LogService::new(self)
+    }
+
+    /// returns setting service.
+    pub fn setting(&self) -> setting::SettingService<'_> {
+        setting::SettingService::new(self)
+    }
+
+    /// returns health service.
+    pub fn health(&self) -> health::HealthService<'_> {
+        health::HealthService::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_blocking_pocket_base_new() {
+        let base = PocketBase::new("http://localhost:8080", "en");
+        assert_eq!(base.base_url().as_str(), "http://localhost:8080/");
+        assert_eq!(base.lang(), "en");
+    }
+
+    #[test]
+    fn test_blocking_pocket_base_request_builder() {
+        let base = PocketBase::new("http://localhost:8080", "en");
+        let request_builder = base.request_builder(reqwest::Method::GET, "http://localhost:8080");
+        let request = request_builder.build().unwrap();
+        assert_eq!(request.method(), reqwest::Method::GET);
+        assert_eq!(request.url().as_str(), "http://localhost:8080/");
+    }
+
+    #[test]
+    fn test_blocking_pocket_base_builder() {
+        assert!(PocketBaseBuilder::new()
+            .base_url("not a url")
+            .build()
+            .is_err());
+
+        let base = PocketBaseBuilder::new()
+            .base_url("http://localhost:8080/pb")
+            .user_agent("rpocket-test")
+            .build()
+            .unwrap();
+        assert_eq!(base.base_url().as_str(), "http://localhost:8080/pb/");
+
+        let request = base
+            .request_builder(reqwest::Method::GET, "http://localhost:8080")
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.headers()[reqwest::header::USER_AGENT],
+            "rpocket-test"
+        );
+    }
+}
@@ -0,0 +1,489 @@
+use crate::{
+    error::RPocketError,
+    model::Record,
+    service::{
+        self,
+        record::{
+            ListAuthMethod, RecordAuthOutcome, RecordAuthRefreshConfig, RecordAuthResponse,
+            RecordAuthWithOAuth2Config, RecordAuthWithOTPConfig, RecordAuthWithPasswordConfig,
+            RecordConfirmEmailChangeConfig, RecordConfirmPasswordResetConfig,
+            RecordConfirmVerificationConfig, RecordListAuthMethodsConfig,
+            RecordListExternalAuthsConfig, RecordRequestEmailChangeConfig, RecordRequestOTPConfig,
+            RecordRequestOTPResponse, RecordRequestPasswordResetConfig,
+            RecordRequestVerificationConfig, RecordRequests, RecordUnlinkExternalAuthConfig,
+        },
+    },
+};
+use serde::Serialize;
+
+/// RecordService is the blocking service for the record.
+pub struct RecordService<'a> {
+    client: &'a super::PocketBase,
+    record_base_path: [&'a str; 4],
+    requests: RecordRequests<'a>,
+}
+
+impl<'a> RecordService<'a> {
+    /// create a new RecordService.
+    pub fn new(client: &'a super::PocketBase, collection: &'a str) -> Self {
+        RecordService {
+            client,
+            record_base_path: ["api", "collections", collection, "records"],
+            requests: RecordRequests { collection },
+        }
+    }
+
+    /// returns crud service.
+    pub fn crud(&self) -> super::crud::CRUDService<'_> {
+        self.client.crud(&self.record_base_path)
+    }
+
+    /// list the auth methods of the collection, eg. the OAuth2 providers.
+    pub fn list_auth_methods(
+        &self,
+        config: &RecordListAuthMethodsConfig,
+    ) -> Result<ListAuthMethod, RPocketError> {
+        let response = self
+            .client
+            .http()
+            .execute(&self.requests.list_auth_methods(config))?;
+
+        Ok(response.json::<ListAuthMethod>()?)
+    }
+
+    /// authenticate with password
+    pub fn auth_with_password<T, B>(
+        &self,
+        config: &RecordAuthWithPasswordConfig<B>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_with_password(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+    }
+
+    /// authenticate with password, the MFA challenge of the first factor is
+    /// `RecordAuthOutcome::MfaRequired`, see `auth_with_otp_mfa`.
+    pub fn auth_with_password_mfa<T, B>(
+        &self,
+        config: &RecordAuthWithPasswordConfig<B>,
+    ) -> Result<RecordAuthOutcome<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        RecordAuthOutcome::from_result(self.auth_with_password(config))
+    }
+
+    /// sends a one-time password to the email of the auth record.
+    pub fn request_otp<B>(
+        &self,
+        config: &RecordRequestOTPConfig<B>,
+    ) -> Result<RecordRequestOTPResponse, RPocketError>
+    where
+        B: Serialize,
+    {
+        let response = self
+            .client
+            .http()
+            .execute(&self.requests.request_otp(config)?)?;
+
+        Ok(response.json::<RecordRequestOTPResponse>()?)
+    }
+
+    /// authenticate with a one-time password.
+    pub fn auth_with_otp<T, B>(
+        &self,
+        config: &RecordAuthWithOTPConfig<B>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_with_otp(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+    }
+
+    /// authenticate with a one-time password, the MFA challenge of the first factor is
+    /// `RecordAuthOutcome::MfaRequired`. the second factor sets `config.mfa_id` to the
+    /// `mfa_id` of the challenge.
+    pub fn auth_with_otp_mfa<T, B>(
+        &self,
+        config: &RecordAuthWithOTPConfig<B>,
+    ) -> Result<RecordAuthOutcome<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        RecordAuthOutcome::from_result(self.auth_with_otp(config))
+    }
+
+    /// authenticate with oauth2
+    pub fn auth_with_oauth2<T, B>(
+        &self,
+        config: &RecordAuthWithOAuth2Config<B>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_with_oauth2(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+    }
+
+    /// authenticate with oauth2, the MFA challenge of the first factor is
+    /// `RecordAuthOutcome::MfaRequired`, see `auth_with_otp_mfa`.
+    pub fn auth_with_oauth2_mfa<T, B>(
+        &self,
+        config: &RecordAuthWithOAuth2Config<B>,
+    ) -> Result<RecordAuthOutcome<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        RecordAuthOutcome::from_result(self.auth_with_oauth2(config))
+    }
+
+    /// authenticate with oauth2 in a desktop or command-line app, the redirect of the provider
+    /// is received by a listener on localhost.
+    pub fn auth_with_oauth2_flow<T, B, F>(
+        &self,
+        config: &service::oauth2::RecordAuthWithOAuth2FlowConfig<B>,
+        open_url: F,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+        F: FnOnce(&str) -> Result<(), RPocketError>,
+    {
+        let auth_methods = self.list_auth_methods(&RecordListAuthMethodsConfig {
+            options: config.options.clone(),
+            ..Default::default()
+        })?;
+        let provider = auth_methods.provider(&config.provider).ok_or_else(|| {
+            RPocketError::ConfigError(format!("unknown auth provider {}", config.provider))
+        })?;
+
+        let listener =
+            service::oauth2::RedirectListener::bind(config.redirect_port, &config.redirect_path)?;
+        let redirect_url = listener.redirect_url().to_string();
+
+        open_url(&provider.auth_url_with_redirect(&redirect_url))?;
+
+        let code = listener.receive(&provider.state, config.timeout)?;
+
+        self.auth_with_oauth2(&config.auth_config(provider, code, redirect_url))
+    }
+
+    /// refreshes the current authenticated record instance and
+    pub fn auth_refresh<T, B>(&self, config: &RecordAuthRefreshConfig<B>) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_refresh(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+    }
+
+    /// sends auth record password reset request.
+    pub fn request_password_reset<B>(
+        &self,
+        config: &RecordRequestPasswordResetConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        self.client
+            .http()
+            .execute(&self.requests.request_password_reset(config)?)?;
+
+        Ok(())
+    }
+
+    /// confirms auth record password reset request.
+    pub fn confirm_password_reset<B>(
+        &self,
+        config: &RecordConfirmPasswordResetConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        self.client
+            .http()
+            .execute(&self.requests.confirm_password_reset(config)?)?;
+
+        Ok(())
+    }
+
+    /// sends auth record verification email request.
+    pub fn request_verification<B>(
+        &self,
+        config: &RecordRequestVerificationConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        self.client
+            .http()
+            .execute(&self.requests.request_verification(config)?)?;
+
+        Ok(())
+    }
+
+    /// confirms auth record verification email request.
+    pub fn confirm_verification<B>(
+        &self,
+        config: &RecordConfirmVerificationConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        self.client
+            .http()
+            .execute(&self.requests.confirm_verification(config)?)?;
+
+        Ok(())
+    }
+
+    /// sends an email change request to the authenticated record model.
+    pub fn request_email_change<B>(
+        &self,
+        config: &RecordRequestEmailChangeConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        self.client
+            .http()
+            .execute(&self.requests.request_email_change(config)?)?;
+
+        Ok(())
+    }
+
+    /// confirms auth record's new email address.
+    pub fn confirm_email_change<B>(
+        &self,
+        config: &RecordConfirmEmailChangeConfig<B>,
+    ) -> Result<(), RPocketError>
+    where
+        B: Serialize,
+    {
+        self.client
+            .http()
+            .execute(&self.requests.confirm_email_change(config)?)?;
+
+        Ok(())
+    }
+
+    /// lists all linked external auth providers for the specified auth record.
+    pub fn list_external_auths<T>(
+        &self,
+        config: &RecordListExternalAuthsConfig,
+    ) -> Result<Vec<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self
+            .client
+            .http()
+            .execute(&self.requests.list_external_auths(config))?;
+
+        Ok(response.json::<Vec<T>>()?)
+    }
+
+    /// unlink a single external auth provider from the specified auth record.
+    pub fn unlink_external_auth(
+        &self,
+        config: &RecordUnlinkExternalAuthConfig,
+    ) -> Result<(), RPocketError> {
+        self.client
+            .http()
+            .execute(&self.requests.unlink_external_auth(config))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blocking::PocketBase;
+    use crate::service::auth_state::AuthPayload;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_blocking_record_list_auth_methods() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/collections/test/auth-methods")
+            .with_status(200)
+            .with_body(
+                r#"{
+  "usernamePassword": false,
+  "emailPassword": true,
+  "authProviders": []
+                }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let response = base
+            .record("test")
+            .list_auth_methods(&RecordListAuthMethodsConfig::default());
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(!response.username_password);
+        assert!(response.email_password);
+    }
+
+    #[test]
+    fn test_blocking_record_auth_with_password() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("POST", "/api/collections/test/auth-with-password")
+            .with_status(200)
+            .match_body(r#"{"identity":"test","password":"12345678"}"#)
+            .with_body(
+                r#"{
+  "token": "token",
+  "record": {
+    "id": "8171022dc95a4ed",
+    "collectionId": "d2972397d45614e",
+    "collectionName": "users",
+    "created": "2022-06-24 06:24:18.434Z",
+    "updated": "2022-06-24 06:24:18.889Z",
+    "username": "test@example.com"
+  }
+                }"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = RecordAuthWithPasswordConfig::<HashMap<String, String>> {
+            identity: "test".to_string(),
+            password: "12345678".to_string(),
+            ..Default::default()
+        };
+
+        let response = base
+            .record("test")
+            .auth_with_password::<RecordAuthResponse<Record>, HashMap<String, String>>(&config);
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(response.token == "token");
+        assert!(response.record.data["username"] == "test@example.com");
+        assert!(base.auth_state().get_token().unwrap().unwrap() == "token");
+        match base.auth_state().get_user_or_admin().unwrap().unwrap() {
+            AuthPayload::User(user) => assert!(user.base.id == "8171022dc95a4ed"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_blocking_record_auth_with_oauth2_mfa() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("POST", "/api/collections/users/auth-with-oauth2")
+            .with_status(401)
+            .with_body(
+                r#"{"code":401,"message":"Please complete the MFA authentication.","data":{"mfaId":"mfa000000000001"}}"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let outcome = base
+            .record("users")
+            .auth_with_oauth2_mfa::<RecordAuthResponse<Record>, HashMap<String, String>>(
+                &RecordAuthWithOAuth2Config {
+                    provider: "github".to_string(),
+                    code: "abc".to_string(),
+                    ..Default::default()
+                },
+            );
+
+        mock.assert();
+        assert!(matches!(
+            outcome.unwrap(),
+            RecordAuthOutcome::MfaRequired { mfa_id } if mfa_id == "mfa000000000001"
+        ));
+        assert!(base.auth_state().get_token().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_blocking_record_auth_with_otp_mfa() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("POST", "/api/collections/users/auth-with-otp")
+            .match_body(r#"{"otpId":"otp000000000001","password":"123456"}"#)
+            .with_status(401)
+            .with_body(
+                r#"{"code":401,"message":"Please complete the MFA authentication.","data":{"mfaId":"mfa000000000001"}}"#,
+            )
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let outcome = base
+            .record("users")
+            .auth_with_otp_mfa::<RecordAuthResponse<Record>, HashMap<String, String>>(
+                &RecordAuthWithOTPConfig {
+                    otp_id: "otp000000000001".to_string(),
+                    password: "123456".to_string(),
+                    ..Default::default()
+                },
+            );
+
+        mock.assert();
+        assert!(matches!(
+            outcome.unwrap(),
+            RecordAuthOutcome::MfaRequired { mfa_id } if mfa_id == "mfa000000000001"
+        ));
+        assert!(base.auth_state().get_token().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_blocking_record_unlink_external_auth() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock(
+                "DELETE",
+                "/api/collections/test/records/8171022dc95a4ed/external-auths/google",
+            )
+            .with_status(204)
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = RecordUnlinkExternalAuthConfig {
+            id: "8171022dc95a4ed".to_string(),
+            provider: "google".to_string(),
+            ..Default::default()
+        };
+
+        let response = base.record("test").unlink_external_auth(&config);
+        mock.assert();
+        response.unwrap();
+    }
+}
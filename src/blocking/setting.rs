@@ -0,0 +1,149 @@
+use crate::{
+    error::RPocketError,
+    service::setting::{
+        drift_diffs, drift_get_all_config, generate_apple_client_secret_request, get_all_request,
+        test_email_request, test_s3_request, update_request, SettingDiff, SettingDriftConfig,
+        SettingGenerateAppleClientSecretConfig, SettingGetAllConfig, SettingTestEmailConfig,
+        SettingTestS3Config, SettingUpdateConfig,
+    },
+};
+
+/// SettingService is the blocking service for setting.
+pub struct SettingService<'a> {
+    client: &'a super::PocketBase,
+}
+
+impl<'a> SettingService<'a> {
+    /// create a new SettingService.
+    pub fn new(client: &'a super::PocketBase) -> Self {
+        SettingService { client }
+    }
+
+    /// returns all settings.
+    pub fn get_all<T>(&self, config: &SettingGetAllConfig) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.client.http().execute(&get_all_request(config))?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// updates the provided settings.
+    pub fn update<T, B>(&self, config: &SettingUpdateConfig<B>) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+    {
+        let response = self.client.http().execute(&update_request(config)?)?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// tests the provided s3 settings.
+    pub fn test_s3<B>(&self, config: &SettingTestS3Config<B>) -> Result<(), RPocketError>
+    where
+        B: serde::Serialize,
+    {
+        self.client.http().execute(&test_s3_request(config)?)?;
+
+        Ok(())
+    }
+
+    /// tests the provided email settings.
+    pub fn test_email<B>(&self, config: &SettingTestEmailConfig<B>) -> Result<(), RPocketError>
+    where
+        B: serde::Serialize,
+    {
+        self.client.http().execute(&test_email_request(config)?)?;
+
+        Ok(())
+    }
+
+    /// generates an apple client secret.
+    pub fn generate_apple_client_secret<T, B>(
+        &self,
+        config: &SettingGenerateAppleClientSecretConfig<B>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+    {
+        let response = self
+            .client
+            .http()
+            .execute(&generate_apple_client_secret_request(config)?)?;
+
+        Ok(response.json::<T>()?)
+    }
+
+    /// compares the live settings against config.desired and returns the differences.
+    /// the differences are corrected on the server if config.apply is true.
+    pub fn drift<B>(&self, config: &SettingDriftConfig<B>) -> Result<Vec<SettingDiff>, RPocketError>
+    where
+        B: serde::Serialize,
+    {
+        let live = self.get_all::<serde_json::Value>(&drift_get_all_config(config))?;
+        let (diffs, update) = drift_diffs(config, &live)?;
+
+        if let Some(update) = update {
+            self.update::<serde_json::Value, serde_json::Value>(&update)?;
+        }
+
+        Ok(diffs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blocking::PocketBase, model::Settings};
+
+    #[test]
+    fn test_blocking_setting_get_all() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/settings")
+            .with_status(200)
+            .with_body(r#"{"meta":{"appName":"Acme"},"logs":{"maxDays":7}}"#)
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let response = base
+            .setting()
+            .get_all::<Settings>(&SettingGetAllConfig::default());
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(response.meta.app_name == "Acme");
+        assert!(response.logs.max_days == 7);
+    }
+
+    #[test]
+    fn test_blocking_setting_drift() {
+        let mut server = mockito::Server::new();
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/settings")
+            .with_status(200)
+            .with_body(r#"{"meta":{"appName":"Acme"},"logs":{"maxDays":7}}"#)
+            .create();
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let config = SettingDriftConfig {
+            desired: serde_json::json!({"logs": {"maxDays": 7}, "meta": {"appName": "Acme 2"}}),
+            ..Default::default()
+        };
+        let response = base.setting().drift(&config);
+
+        mock.assert();
+        let response = response.unwrap();
+
+        assert!(response.len() == 1);
+        assert!(response[0].path == "meta.appName");
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use crate::error::RPocketError;
+use crate::store::Storage;
+
+/// SyncStorage is the blocking adapter of a `Storage`, it shares the storage of the async client.
+#[derive(Clone)]
+pub struct SyncStorage {
+    storage: Arc<dyn Storage + Sync + Send>,
+}
+
+impl SyncStorage {
+    /// create a new SyncStorage.
+    pub fn new(storage: Arc<dyn Storage + Sync + Send>) -> Self {
+        SyncStorage { storage }
+    }
+
+    /// returns the wrapped storage.
+    pub fn inner(&self) -> Arc<dyn Storage + Sync + Send> {
+        self.storage.clone()
+    }
+
+    /// get the value of the key.
+    pub fn get(&self, key: &str) -> Result<Option<String>, RPocketError> {
+        futures::executor::block_on(self.storage.get(key))
+    }
+
+    /// set the value of the key.
+    pub fn set(&self, key: &str, value: &str) -> Result<(), RPocketError> {
+        futures::executor::block_on(self.storage.set(key, value))
+    }
+
+    /// delete the key.
+    pub fn delete(&self, key: &str) -> Result<(), RPocketError> {
+        futures::executor::block_on(self.storage.delete(key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::store::MemoryStorage;
+
+    #[test]
+    fn test_sync_storage() {
+        let storage = SyncStorage::new(Arc::new(MemoryStorage::new()));
+
+        assert!(storage.get("foo").unwrap().is_none());
+        storage.set("foo", "bar").unwrap();
+        assert_eq!(storage.get("foo").unwrap().unwrap(), "bar");
+
+        // the wrapped storage sees the same values.
+        let inner = storage.inner();
+        assert_eq!(
+            futures::executor::block_on(inner.get("foo"))
+                .unwrap()
+                .unwrap(),
+            "bar"
+        );
+
+        storage.delete("foo").unwrap();
+        assert!(storage.get("foo").unwrap().is_none());
+    }
+}
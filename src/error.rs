@@ -12,9 +12,13 @@ pub struct APIError {
 #[derive(Debug)]
 pub enum RPocketError {
     MutexError,
+    Cancelled,
+    ConfigError(String),
     SerdeError(serde_json::Error),
     RequestError(reqwest::Error),
     UrlError(url::ParseError),
+    PathError(String),
+    IoError(std::io::Error),
     APIError(APIError),
     Error(Box<dyn std::error::Error + Send + Sync>),
 }
@@ -37,6 +41,12 @@ impl From<url::ParseError> for RPocketError {
     }
 }
 
+impl From<std::io::Error> for RPocketError {
+    fn from(error: std::io::Error) -> Self {
+        RPocketError::IoError(error)
+    }
+}
+
 impl From<APIError> for RPocketError {
     fn from(error: APIError) -> Self {
         RPocketError::APIError(error)
@@ -53,9 +63,13 @@ impl std::fmt::Display for RPocketError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RPocketError::MutexError => write!(f, "mutex error"),
+            RPocketError::Cancelled => write!(f, "request cancelled"),
+            RPocketError::ConfigError(error) => write!(f, "config error: {}", error),
             RPocketError::SerdeError(error) => write!(f, "serde error: {}", error),
             RPocketError::RequestError(error) => write!(f, "request error: {}", error),
             RPocketError::UrlError(error) => write!(f, "url error: {}", error),
+            RPocketError::PathError(segment) => write!(f, "invalid path segment: {:?}", segment),
+            RPocketError::IoError(error) => write!(f, "io error: {}", error),
             RPocketError::APIError(error) => write!(f, "API error: {}", error.message),
             RPocketError::Error(error) => write!(f, "error: {}", error),
         }
@@ -66,9 +80,13 @@ impl std::error::Error for RPocketError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             RPocketError::MutexError => None,
+            RPocketError::Cancelled => None,
+            RPocketError::ConfigError(..) => None,
             RPocketError::SerdeError(error) => Some(error),
             RPocketError::RequestError(error) => Some(error),
             RPocketError::UrlError(error) => Some(error),
+            RPocketError::PathError(..) => None,
+            RPocketError::IoError(error) => Some(error),
             RPocketError::APIError(..) => None,
             RPocketError::Error(error) => Some(error.as_ref()),
         }
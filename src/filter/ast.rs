@@ -0,0 +1,104 @@
+/// Expr is a parsed filter or API rule expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Condition(Condition),
+}
+
+impl Expr {
+    /// returns the conditions of the expression, left to right.
+    pub fn conditions(&self) -> Vec<&Condition> {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                let mut conditions = left.conditions();
+                conditions.extend(right.conditions());
+                conditions
+            }
+            Expr::Condition(condition) => vec![condition],
+        }
+    }
+
+    /// returns the identifiers referenced by the expression, left to right.
+    pub fn identifiers(&self) -> Vec<&Identifier> {
+        self.conditions()
+            .into_iter()
+            .flat_map(|condition| [&condition.left, &condition.right])
+            .filter_map(|operand| match operand {
+                Operand::Identifier(identifier) => Some(identifier),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Condition compares two operands, eg. `status = "active"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub left: Operand,
+    pub operator: Operator,
+    /// true for the `?` operators, they match if any value matches.
+    pub any: bool,
+    pub right: Operand,
+    /// byte offset of the operator.
+    pub position: usize,
+}
+
+/// Operand is a side of a condition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Identifier(Identifier),
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+/// Identifier is a field or a macro like `@request.auth.id`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identifier {
+    pub name: String,
+    /// byte offset of the identifier.
+    pub position: usize,
+}
+
+impl Identifier {
+    /// returns the dot separated parts of the name.
+    pub fn path(&self) -> Vec<&str> {
+        self.name.split('.').collect()
+    }
+
+    /// returns true if the identifier is a macro, eg. `@request.auth.id` or `@now`.
+    pub fn is_macro(&self) -> bool {
+        self.name.starts_with('@')
+    }
+}
+
+/// Operator is a comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Like,
+    NotLike,
+}
+
+impl Operator {
+    /// returns the operator sign without the `?` prefix.
+    pub fn sign(&self) -> &'static str {
+        match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Like => "~",
+            Operator::NotLike => "!~",
+        }
+    }
+}
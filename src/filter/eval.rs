@@ -0,0 +1,374 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::ast::{Condition, Expr, Identifier, Operand, Operator};
+use super::FilterError;
+use crate::model::{self, Record};
+
+/// FilterContext is the `@request` context an expression is evaluated with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterContext {
+    /// the authenticated record of `@request.auth`, `None` for guests.
+    pub auth: Option<Record>,
+    /// the submitted body of `@request.data`.
+    pub data: HashMap<String, Value>,
+    /// the query parameters of `@request.query`.
+    pub query: HashMap<String, String>,
+    /// the HTTP method of `@request.method`.
+    pub method: String,
+}
+
+impl FilterContext {
+    /// set the authenticated record.
+    pub fn auth(mut self, auth: Record) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// set the submitted body.
+    pub fn data(mut self, data: HashMap<String, Value>) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// set the query parameters.
+    pub fn query(mut self, query: HashMap<String, String>) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// set the HTTP method.
+    pub fn method<T: Into<String>>(mut self, method: T) -> Self {
+        self.method = method.into();
+        self
+    }
+}
+
+impl Expr {
+    /// evaluates the expression against the record.
+    /// relation paths like `author.name` are resolved through `expand`.
+    pub fn evaluate(&self, record: &Record, context: &FilterContext) -> Result<bool, FilterError> {
+        let item =
+            serde_json::to_value(record).map_err(|error| FilterError::new(0, error.to_string()))?;
+
+        self.evaluate_value(&item, context)
+    }
+
+    /// evaluates the expression against a json object, eg. a serialized collection.
+    pub fn evaluate_value(
+        &self,
+        item: &Value,
+        context: &FilterContext,
+    ) -> Result<bool, FilterError> {
+        let evaluator = Evaluator {
+            item,
+            auth: match context.auth {
+                Some(ref auth) => serde_json::to_value(auth)
+                    .map_err(|error| FilterError::new(0, error.to_string()))?,
+                None => Value::Null,
+            },
+            data: Value::Object(context.data.clone().into_iter().collect()),
+            context,
+        };
+
+        evaluator.expr(self)
+    }
+}
+
+struct Evaluator<'a> {
+    item: &'a Value,
+    auth: Value,
+    data: Value,
+    context: &'a FilterContext,
+}
+
+impl Evaluator<'_> {
+    fn expr(&self, expr: &Expr) -> Result<bool, FilterError> {
+        match expr {
+            Expr::And(left, right) => Ok(self.expr(left)? && self.expr(right)?),
+            Expr::Or(left, right) => Ok(self.expr(left)? || self.expr(right)?),
+            Expr::Condition(condition) => self.condition(condition),
+        }
+    }
+
+    fn condition(&self, condition: &Condition) -> Result<bool, FilterError> {
+        let left = or_null(self.operand(&condition.left)?);
+        let right = or_null(self.operand(&condition.right)?);
+        let matches = |left: &Value, right: &Value| compare(left, condition.operator, right);
+
+        if condition.any {
+            Ok(left
+                .iter()
+                .any(|left| right.iter().any(|right| matches(left, right))))
+        } else {
+            Ok(left
+                .iter()
+                .all(|left| right.iter().all(|right| matches(left, right))))
+        }
+    }
+
+    fn operand(&self, operand: &Operand) -> Result<Vec<Value>, FilterError> {
+        Ok(match operand {
+            Operand::Identifier(identifier) => return self.identifier(identifier),
+            Operand::Text(text) => vec![Value::String(text.clone())],
+            Operand::Number(number) => vec![serde_json::Number::from_f64(*number)
+                .map(Value::Number)
+                .unwrap_or(Value::Null)],
+            Operand::Bool(value) => vec![Value::Bool(*value)],
+            Operand::Null => vec![Value::Null],
+        })
+    }
+
+    // returns every value the identifier resolves to, multi-value fields give many.
+    fn identifier(&self, identifier: &Identifier) -> Result<Vec<Value>, FilterError> {
+        let (name, modifier) = match identifier.name.split_once(':') {
+            Some((name, modifier)) => (name, Some(modifier)),
+            None => (identifier.name.as_str(), None),
+        };
+
+        let values = if let Some(path) = name.strip_prefix("@request.auth.") {
+            lookup(&self.auth, &path.split('.').collect::<Vec<_>>())
+        } else if let Some(path) = name.strip_prefix("@request.data.") {
+            lookup(&self.data, &path.split('.').collect::<Vec<_>>())
+        } else if let Some(key) = name.strip_prefix("@request.query.") {
+            vec![self
+                .context
+                .query
+                .get(key)
+                .map_or(Value::Null, |value| Value::String(value.clone()))]
+        } else if name == "@request.method" {
+            vec![Value::String(self.context.method.clone())]
+        } else if name == "@now" {
+            vec![Value::String(model::format_date(&chrono::Utc::now()))]
+        } else if name.starts_with('@') {
+            return Err(FilterError::new(
+                identifier.position,
+                format!("unsupported identifier {}", name),
+            ));
+        } else {
+            lookup(self.item, &name.split('.').collect::<Vec<_>>())
+        };
+
+        match modifier {
+            None => Ok(values),
+            Some("length") => Ok(vec![Value::from(
+                values.iter().filter(|value| !value.is_null()).count(),
+            )]),
+            Some(modifier) => Err(FilterError::new(
+                identifier.position,
+                format!("unsupported modifier :{}", modifier),
+            )),
+        }
+    }
+}
+
+// resolves a dotted path, arrays are flattened and relations are read from `expand`.
+fn lookup(value: &Value, path: &[&str]) -> Vec<Value> {
+    let (key, rest) = match path.split_first() {
+        Some(split) => split,
+        None => {
+            return match value {
+                Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            }
+        }
+    };
+
+    match value {
+        Value::Object(map) => match map.get(*key) {
+            Some(field) if rest.is_empty() || field.is_object() || is_object_list(field) => {
+                lookup(field, rest)
+            }
+            _ => match map.get("expand").and_then(|expand| expand.get(*key)) {
+                Some(expanded) if !rest.is_empty() => lookup(expanded, rest),
+                _ => vec![Value::Null],
+            },
+        },
+        Value::Array(values) => values
+            .iter()
+            .flat_map(|value| lookup(value, path))
+            .collect(),
+        _ => vec![Value::Null],
+    }
+}
+
+fn is_object_list(value: &Value) -> bool {
+    matches!(value, Value::Array(values) if values.iter().any(Value::is_object))
+}
+
+// empty multi-value fields compare like a missing value.
+fn or_null(values: Vec<Value>) -> Vec<Value> {
+    if values.is_empty() {
+        vec![Value::Null]
+    } else {
+        values
+    }
+}
+
+fn compare(left: &Value, operator: Operator, right: &Value) -> bool {
+    let ordering = order(left, right);
+
+    match operator {
+        Operator::Equal => equal(left, right),
+        Operator::NotEqual => !equal(left, right),
+        Operator::Like => like(left, right),
+        Operator::NotLike => !like(left, right),
+        Operator::Greater => ordering == Some(Ordering::Greater),
+        Operator::GreaterOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        Operator::Less => ordering == Some(Ordering::Less),
+        Operator::LessOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+// null and empty strings are equal, like in PocketBase.
+fn equal(left: &Value, right: &Value) -> bool {
+    let blank = |value: &Value| value.is_null() || value.as_str() == Some("");
+
+    (blank(left) && blank(right)) || order(left, right) == Some(Ordering::Equal) || left == right
+}
+
+fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::Number(left), Value::String(right)) => left
+            .as_f64()?
+            .partial_cmp(&right.trim().parse::<f64>().ok()?),
+        (Value::String(left), Value::Number(right)) => left
+            .trim()
+            .parse::<f64>()
+            .ok()?
+            .partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+// case-insensitive contains, or a LIKE pattern if the right side has a `%`.
+fn like(left: &Value, right: &Value) -> bool {
+    let value = text(left).to_lowercase();
+    let pattern = text(right).to_lowercase();
+
+    if !pattern.contains('%') {
+        return value.contains(&pattern);
+    }
+
+    let parts: Vec<&str> = pattern.split('%').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+
+    if !value.starts_with(first) || !value[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::filter::parse;
+    use crate::model::{BaseModel, ExpandValue};
+
+    fn record(id: &str, data: Value) -> Record {
+        Record {
+            base: BaseModel {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            collection_id: "posts_id".to_string(),
+            collection_name: "posts".to_string(),
+            data: serde_json::from_value(data).unwrap(),
+            expand: None,
+        }
+    }
+
+    fn evaluate(filter: &str, record: &Record, context: &FilterContext) -> bool {
+        parse(filter).unwrap().evaluate(record, context).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_fields() {
+        let post = record(
+            "p1",
+            serde_json::json!({"title": "Hello World", "views": 10, "draft": false, "tags": ["a", "b"], "summary": ""}),
+        );
+        let context = FilterContext::default();
+
+        assert!(evaluate(
+            "title = 'Hello World' && views > 5",
+            &post,
+            &context
+        ));
+        assert!(evaluate("views >= '10' && draft = false", &post, &context));
+        assert!(!evaluate("views < 10 || title !~ 'world'", &post, &context));
+        assert!(evaluate(
+            "title ~ 'hello%' && title ~ '%wor%d'",
+            &post,
+            &context
+        ));
+        assert!(evaluate("summary = null && missing = ''", &post, &context));
+        assert!(evaluate("tags ?= 'b' && tags != 'c'", &post, &context));
+        assert!(!evaluate("tags = 'b'", &post, &context));
+        assert!(evaluate("tags:length = 2", &post, &context));
+        assert!(evaluate(
+            "(id = 'x' || id = 'p1') && collectionName = 'posts'",
+            &post,
+            &context
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_request_context() {
+        let mut post = record(
+            "p1",
+            serde_json::json!({"author": "u1", "editors": ["u2", "u3"]}),
+        );
+        post.expand = Some(HashMap::from([(
+            "author".to_string(),
+            ExpandValue::Record(Box::new(record("u1", serde_json::json!({"name": "Ann"})))),
+        )]));
+
+        let guest = FilterContext::default();
+        assert!(!evaluate("@request.auth.id != ''", &post, &guest));
+        assert!(evaluate("author.name = 'Ann'", &post, &guest));
+
+        let editor = FilterContext::default()
+            .auth(record("u3", serde_json::json!({"role": "editor"})))
+            .data(HashMap::from([(
+                "title".to_string(),
+                serde_json::json!("New"),
+            )]))
+            .method("PATCH");
+        assert!(evaluate("editors ?= @request.auth.id", &post, &editor));
+        assert!(!evaluate("author = @request.auth.id", &post, &editor));
+        assert!(evaluate(
+            "@request.auth.role = 'editor' && @request.data.title != '' && @request.method = 'PATCH'",
+            &post,
+            &editor
+        ));
+
+        let error = parse("@collection.users.id = 'x'")
+            .unwrap()
+            .evaluate(&post, &editor)
+            .unwrap_err();
+        assert_eq!(error.position, 0);
+    }
+}
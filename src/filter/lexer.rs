@@ -0,0 +1,131 @@
+use super::ast::Operator;
+use super::FilterError;
+
+// operators sorted so the longest sign matches first.
+const OPERATORS: [(&str, Operator, bool); 16] = [
+    ("?!=", Operator::NotEqual, true),
+    ("?!~", Operator::NotLike, true),
+    ("?>=", Operator::GreaterOrEqual, true),
+    ("?<=", Operator::LessOrEqual, true),
+    ("?=", Operator::Equal, true),
+    ("?>", Operator::Greater, true),
+    ("?<", Operator::Less, true),
+    ("?~", Operator::Like, true),
+    ("!=", Operator::NotEqual, false),
+    ("!~", Operator::NotLike, false),
+    (">=", Operator::GreaterOrEqual, false),
+    ("<=", Operator::LessOrEqual, false),
+    ("=", Operator::Equal, false),
+    (">", Operator::Greater, false),
+    ("<", Operator::Less, false),
+    ("~", Operator::Like, false),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Identifier(String),
+    Text(String),
+    Number(f64),
+    Operator(Operator, bool),
+    And,
+    Or,
+    OpenParen,
+    CloseParen,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) position: usize,
+}
+
+// splits the expression into tokens, `//` comments are skipped.
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while let Some(c) = input[position..].chars().next() {
+        let rest = &input[position..];
+
+        if c.is_whitespace() {
+            position += c.len_utf8();
+            continue;
+        }
+
+        if rest.starts_with("//") {
+            position += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+
+        let (kind, length) = if rest.starts_with("&&") {
+            (TokenKind::And, 2)
+        } else if rest.starts_with("||") {
+            (TokenKind::Or, 2)
+        } else if c == '(' {
+            (TokenKind::OpenParen, 1)
+        } else if c == ')' {
+            (TokenKind::CloseParen, 1)
+        } else if c == '\'' || c == '"' {
+            read_text(rest, c, position)?
+        } else if c.is_ascii_digit() || (c == '-' && starts_with_digit(&rest[1..])) {
+            read_number(rest, position)?
+        } else if c.is_alphabetic() || c == '_' || c == '@' {
+            let length = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '@' | '.' | ':')))
+                .unwrap_or(rest.len());
+            (TokenKind::Identifier(rest[..length].to_string()), length)
+        } else if let Some((sign, operator, any)) =
+            OPERATORS.iter().find(|(sign, _, _)| rest.starts_with(sign))
+        {
+            (TokenKind::Operator(*operator, *any), sign.len())
+        } else {
+            return Err(FilterError::new(
+                position,
+                format!("unexpected character {:?}", c),
+            ));
+        };
+
+        tokens.push(Token { kind, position });
+        position += length;
+    }
+
+    Ok(tokens)
+}
+
+fn starts_with_digit(value: &str) -> bool {
+    value.chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+fn read_text(rest: &str, quote: char, position: usize) -> Result<(TokenKind, usize), FilterError> {
+    let mut text = String::new();
+    let mut chars = rest.char_indices().skip(1);
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            // like PocketBase, only the quote is unescaped, other backslashes are kept.
+            '\\' => match chars.next() {
+                Some((_, escaped)) if escaped == quote => text.push(escaped),
+                Some((_, escaped)) => {
+                    text.push('\\');
+                    text.push(escaped);
+                }
+                None => break,
+            },
+            c if c == quote => return Ok((TokenKind::Text(text), index + 1)),
+            c => text.push(c),
+        }
+    }
+
+    Err(FilterError::new(position, "unterminated string"))
+}
+
+fn read_number(rest: &str, position: usize) -> Result<(TokenKind, usize), FilterError> {
+    let length = rest[1..]
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map_or(rest.len(), |length| length + 1);
+
+    rest[..length]
+        .parse::<f64>()
+        .map(|number| (TokenKind::Number(number), length))
+        .map_err(|_| FilterError::new(position, format!("invalid number {:?}", &rest[..length])))
+}
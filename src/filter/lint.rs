@@ -0,0 +1,408 @@
+use super::ast::Identifier;
+use crate::model::{Collection, SchemaField};
+
+// fields every collection has.
+const BASE_FIELDS: [&str; 5] = ["id", "created", "updated", "collectionId", "collectionName"];
+// fields every auth collection has.
+const AUTH_FIELDS: [&str; 4] = ["username", "email", "emailVisibility", "verified"];
+// datetime macros, eg. `@now` or `@todayStart`.
+const DATE_MACROS: [&str; 14] = [
+    "@now",
+    "@second",
+    "@minute",
+    "@hour",
+    "@weekday",
+    "@day",
+    "@month",
+    "@year",
+    "@todayStart",
+    "@todayEnd",
+    "@monthStart",
+    "@monthEnd",
+    "@yearStart",
+    "@yearEnd",
+];
+
+/// Severity is the severity of a rule diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// the rule is invalid or references a missing field.
+    Error,
+    /// the rule is valid but probably not what was intended.
+    Warning,
+}
+
+/// Diagnostic is a problem found in a collection rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// name of the collection.
+    pub collection: String,
+    /// name of the rule, eg. `listRule`.
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// byte offset in the rule, `None` if the diagnostic is about the whole rule.
+    pub position: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        write!(f, "{}: {}.{}", severity, self.collection, self.rule)?;
+        if let Some(position) = self.position {
+            write!(f, " at position {}", position)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// lints the rules of every collection.
+pub fn lint_collections(collections: &[Collection]) -> Vec<Diagnostic> {
+    collections
+        .iter()
+        .flat_map(|collection| lint_collection(collection, collections))
+        .collect()
+}
+
+/// lints the rules of the collection, `collections` resolve relation fields,
+/// `@collection` references and `@request.auth` fields.
+pub fn lint_collection(collection: &Collection, collections: &[Collection]) -> Vec<Diagnostic> {
+    let rules = [
+        ("listRule", &collection.list_rule),
+        ("viewRule", &collection.view_rule),
+        ("createRule", &collection.create_rule),
+        ("updateRule", &collection.update_rule),
+        ("deleteRule", &collection.delete_rule),
+    ];
+
+    let mut diagnostics = Vec::new();
+    for (name, rule) in rules {
+        let linter = Linter {
+            collection,
+            collections,
+            rule: name,
+        };
+
+        if let Some(rule) = rule {
+            linter.lint(rule, &mut diagnostics);
+        }
+    }
+
+    diagnostics
+}
+
+struct Linter<'a> {
+    collection: &'a Collection,
+    collections: &'a [Collection],
+    rule: &'static str,
+}
+
+impl Linter<'_> {
+    fn diagnostic<T: Into<String>>(
+        &self,
+        severity: Severity,
+        position: Option<usize>,
+        message: T,
+    ) -> Diagnostic {
+        Diagnostic {
+            collection: self.collection.name.clone(),
+            rule: self.rule,
+            severity,
+            position,
+            message: message.into(),
+        }
+    }
+
+    fn lint(&self, rule: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let trimmed = rule.trim();
+
+        if trimmed.is_empty() {
+            if !rule.is_empty() {
+                diagnostics.push(self.diagnostic(
+                    Severity::Warning,
+                    None,
+                    "blank rule allows everyone, use \"\" for public access or null for admins only",
+                ));
+            }
+            if self.collection.collection_type == "auth" && self.rule != "createRule" {
+                diagnostics.push(self.diagnostic(
+                    Severity::Warning,
+                    None,
+                    "public rule on an auth collection, anyone can access its records",
+                ));
+            }
+            return;
+        }
+
+        if matches!(trimmed, "null" | "\"\"" | "''") {
+            diagnostics.push(self.diagnostic(
+                Severity::Warning,
+                None,
+                format!(
+                    "rule is the text {}, use null for admins only or \"\" for public access",
+                    trimmed
+                ),
+            ));
+            return;
+        }
+
+        let expr = match super::parse(rule) {
+            Ok(expr) => expr,
+            Err(error) => {
+                diagnostics.push(self.diagnostic(
+                    Severity::Error,
+                    Some(error.position),
+                    error.message,
+                ));
+                return;
+            }
+        };
+
+        for identifier in expr.identifiers() {
+            if let Some(diagnostic) = self.identifier(identifier) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    fn identifier(&self, identifier: &Identifier) -> Option<Diagnostic> {
+        let name = identifier
+            .name
+            .split_once(':')
+            .map_or(identifier.name.as_str(), |(name, _)| name);
+        let path: Vec<&str> = name.split('.').collect();
+        let error = |message: String| {
+            Some(self.diagnostic(Severity::Error, Some(identifier.position), message))
+        };
+
+        if !identifier.is_macro() {
+            return self.resolve(self.collection, &path).err().and_then(error);
+        }
+
+        match path.as_slice() {
+            ["@request", "auth", field, rest @ ..] => self.auth_field(identifier, field, rest),
+            ["@request", "data", field, ..] if matches!(self.rule, "listRule" | "viewRule") => {
+                Some(self.diagnostic(
+                    Severity::Warning,
+                    Some(identifier.position),
+                    format!("@request.data.{} is always empty in a {}", field, self.rule),
+                ))
+            }
+            ["@request", "data", rest @ ..] if !rest.is_empty() => {
+                self.resolve(self.collection, rest).err().and_then(error)
+            }
+            ["@request", "query" | "headers", _, ..] | ["@request", "method" | "context"] => None,
+            ["@collection", collection, rest @ ..] if !rest.is_empty() => {
+                match self.find_collection(collection) {
+                    Some(collection) => self.resolve(collection, rest).err().and_then(error),
+                    None => error(format!("unknown collection {:?}", collection)),
+                }
+            }
+            [name] if DATE_MACROS.contains(name) => None,
+            _ => error(format!("unknown identifier {}", name)),
+        }
+    }
+
+    // `@request.auth` fields must exist in at least one auth collection.
+    fn auth_field(
+        &self,
+        identifier: &Identifier,
+        field: &str,
+        rest: &[&str],
+    ) -> Option<Diagnostic> {
+        let auth_collections: Vec<&Collection> = self
+            .collections
+            .iter()
+            .filter(|collection| collection.collection_type == "auth")
+            .collect();
+
+        if auth_collections.is_empty() {
+            return None;
+        }
+
+        let path: Vec<&str> = std::iter::once(field).chain(rest.iter().copied()).collect();
+        let errors: Vec<String> = auth_collections
+            .iter()
+            .filter_map(|collection| self.resolve(collection, &path).err())
+            .collect();
+
+        if errors.len() < auth_collections.len() {
+            return None;
+        }
+
+        Some(self.diagnostic(
+            Severity::Error,
+            Some(identifier.position),
+            format!(
+                "no auth collection has the field @request.auth.{}",
+                path.join(".")
+            ),
+        ))
+    }
+
+    fn find_collection(&self, id_or_name: &str) -> Option<&Collection> {
+        self.collections
+            .iter()
+            .find(|collection| collection.base.id == id_or_name || collection.name == id_or_name)
+    }
+
+    // returns an error message if the path doesn't exist in the collection,
+    // relations are followed into the related collections.
+    fn resolve(&self, collection: &Collection, path: &[&str]) -> Result<(), String> {
+        let (name, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        let is_system = BASE_FIELDS.contains(name)
+            || (collection.collection_type == "auth" && AUTH_FIELDS.contains(name));
+        if is_system {
+            return match rest.is_empty() {
+                true => Ok(()),
+                false => Err(format!("field {:?} is not a relation", name)),
+            };
+        }
+
+        // back relations, eg. `comments_via_post.message`.
+        if let Some((target, field)) = name.split_once("_via_") {
+            let target = self
+                .find_collection(target)
+                .ok_or_else(|| format!("unknown collection {:?}", target))?;
+            let relation = target
+                .schema
+                .iter()
+                .find(|schema| schema.name == field && schema.field_type == "relation")
+                .ok_or_else(|| format!("unknown relation {:?} in {:?}", field, target.name))?;
+
+            return match self.related(relation) {
+                Some(related) if related.base.id != collection.base.id => Err(format!(
+                    "relation {:?} of {:?} doesn't reference {:?}",
+                    field, target.name, collection.name
+                )),
+                _ => self.resolve(target, rest),
+            };
+        }
+
+        let field = collection
+            .schema
+            .iter()
+            .find(|field| field.name == *name)
+            .ok_or_else(|| format!("unknown field {:?} in {:?}", name, collection.name))?;
+
+        match field.field_type.as_str() {
+            _ if rest.is_empty() => Ok(()),
+            "json" => Ok(()),
+            "relation" => match self.related(field) {
+                Some(related) => self.resolve(related, rest),
+                None => Ok(()),
+            },
+            _ => Err(format!("field {:?} is not a relation", name)),
+        }
+    }
+
+    fn related(&self, field: &SchemaField) -> Option<&Collection> {
+        field
+            .options
+            .get("collectionId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| self.find_collection(id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::BaseModel;
+
+    fn field(name: &str, field_type: &str, collection_id: Option<&str>) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+            options: collection_id
+                .map(|id| [("collectionId".to_string(), serde_json::json!(id))].into())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn collection(id: &str, collection_type: &str, schema: Vec<SchemaField>) -> Collection {
+        Collection {
+            base: BaseModel {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            name: id.to_string(),
+            collection_type: collection_type.to_string(),
+            schema,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lint_collection() {
+        let users = Collection {
+            view_rule: Some("".to_string()),
+            create_rule: Some("".to_string()),
+            ..collection("users", "auth", vec![field("role", "select", None)])
+        };
+        let posts = Collection {
+            list_rule: Some("author.role = 'editor' || comments_via_post.id != ''".to_string()),
+            view_rule: Some("author.name = 'x' && title.length > 1".to_string()),
+            create_rule: Some(
+                "@request.auth.role = 'editor' && @request.data.title != ''".to_string(),
+            ),
+            update_rule: Some(
+                "author = @request.auth.id && @request.auth.nickname != ''".to_string(),
+            ),
+            delete_rule: Some("author = @request.auth.id &&".to_string()),
+            ..collection(
+                "posts",
+                "base",
+                vec![
+                    field("title", "text", None),
+                    field("author", "relation", Some("users")),
+                ],
+            )
+        };
+        let comments = Collection {
+            list_rule: Some(" ".to_string()),
+            view_rule: Some("null".to_string()),
+            create_rule: Some(
+                "@collection.posts.title != '' && @collection.tags.id != ''".to_string(),
+            ),
+            update_rule: Some(
+                "@request.data.post:isset = false && created > @todayStart".to_string(),
+            ),
+            delete_rule: Some("@request.unknown = 1".to_string()),
+            ..collection(
+                "comments",
+                "base",
+                vec![field("post", "relation", Some("posts"))],
+            )
+        };
+
+        let diagnostics: Vec<String> = lint_collections(&[users, posts, comments])
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                "warning: users.viewRule: public rule on an auth collection, anyone can access its records",
+                "error: posts.viewRule at position 0: unknown field \"name\" in \"users\"",
+                "error: posts.viewRule at position 21: field \"title\" is not a relation",
+                "error: posts.updateRule at position 29: no auth collection has the field @request.auth.nickname",
+                "error: posts.deleteRule at position 28: expected a field or a value",
+                "warning: comments.listRule: blank rule allows everyone, use \"\" for public access or null for admins only",
+                "warning: comments.viewRule: rule is the text null, use null for admins only or \"\" for public access",
+                "error: comments.createRule at position 33: unknown collection \"tags\"",
+                "error: comments.deleteRule at position 0: unknown identifier @request.unknown",
+            ]
+        );
+    }
+}
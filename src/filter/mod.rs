@@ -0,0 +1,152 @@
+mod ast;
+mod eval;
+mod lexer;
+mod lint;
+mod parser;
+
+pub use ast::{Condition, Expr, Identifier, Operand, Operator};
+pub use eval::FilterContext;
+pub use lint::{lint_collection, lint_collections, Diagnostic, Severity};
+
+use crate::error::RPocketError;
+
+/// MAX_FILTER_DEPTH is the max nesting of parentheses in a filter expression.
+pub const MAX_FILTER_DEPTH: usize = 64;
+
+/// MAX_FILTER_CONDITIONS is the max number of conditions in a filter expression.
+pub const MAX_FILTER_CONDITIONS: usize = 1000;
+
+/// FilterError is a syntax or evaluation error of a filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    /// byte offset of the error in the expression.
+    pub position: usize,
+    pub message: String,
+}
+
+impl FilterError {
+    pub(crate) fn new<T: Into<String>>(position: usize, message: T) -> Self {
+        FilterError {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl From<FilterError> for RPocketError {
+    fn from(error: FilterError) -> Self {
+        RPocketError::Error(Box::new(error))
+    }
+}
+
+/// parses a filter or an API rule, eg. `status = "active" && editors ?= @request.auth.id`.
+pub fn parse(filter: &str) -> Result<Expr, FilterError> {
+    parser::Parser::new(lexer::tokenize(filter)?, filter.len()).parse()
+}
+
+/// quotes a string literal for a filter, eg. `it's` becomes `"it's"`.
+/// only the quote is escaped since PocketBase keeps the other backslashes as written.
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let expr = parse("a = 1 || b ?!= 'x' && (c ~ \"y\" // comment\n || d = null)").unwrap();
+
+        let conditions = expr.conditions();
+        assert_eq!(conditions.len(), 4);
+        assert_eq!(conditions[1].operator, Operator::NotEqual);
+        assert!(conditions[1].any);
+        assert_eq!(conditions[3].right, Operand::Null);
+        assert!(matches!(expr, Expr::Or(_, ref right) if matches!(**right, Expr::And(_, _))));
+
+        let identifiers = expr.identifiers();
+        assert_eq!(identifiers[0].name, "a");
+        assert_eq!(identifiers[1].position, 9);
+
+        let expr = parse("@request.auth.id != '' && tags:length > -1.5").unwrap();
+        assert!(expr.identifiers()[0].is_macro());
+        assert_eq!(expr.conditions()[1].right, Operand::Number(-1.5));
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        let expr = parse(r#"a = 'it\'s' && b = "a\b\\" && c = "\"""#).unwrap();
+
+        let conditions = expr.conditions();
+        assert_eq!(conditions[0].right, Operand::Text("it's".to_string()));
+        assert_eq!(conditions[1].right, Operand::Text(r"a\b\\".to_string()));
+        assert_eq!(conditions[2].right, Operand::Text("\"".to_string()));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("it's \"x\""), r#""it's \"x\"""#);
+
+        let value = "a\\b\nc\t\u{e9}\"";
+        let quoted = quote(value);
+        assert_eq!(quoted, "\"a\\b\nc\t\u{e9}\\\"\"");
+
+        let expr = parse(&format!("a = {}", quoted)).unwrap();
+        assert_eq!(expr.conditions()[0].right, Operand::Text(value.to_string()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", 0, "empty expression"),
+            ("a = ", 4, "expected a field or a value"),
+            ("a 1", 2, "expected an operator"),
+            ("a = 1 b = 2", 6, "expected && or || between conditions"),
+            ("(a = 1", 0, "missing closing parenthesis"),
+            ("a = 1)", 5, "unexpected closing parenthesis"),
+            ("a = 'x", 4, "unterminated string"),
+            ("a # 1", 2, "unexpected character '#'"),
+        ];
+
+        for (filter, position, message) in cases {
+            let error = parse(filter).unwrap_err();
+            assert_eq!(error, FilterError::new(position, message), "{}", filter);
+        }
+
+        assert_eq!(
+            parse("a = 1 &&").unwrap_err().to_string(),
+            "expected a field or a value at position 8"
+        );
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let nested = |depth: usize| format!("{}a = 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAX_FILTER_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_FILTER_DEPTH + 1)).unwrap_err(),
+            FilterError::new(MAX_FILTER_DEPTH, "too deeply nested parentheses")
+        );
+        // would overflow the stack without the limit.
+        assert!(parse(&"(".repeat(200_000)).is_err());
+
+        let chain = |count: usize| vec!["a = 1"; count].join(" && ");
+        assert!(parse(&chain(MAX_FILTER_CONDITIONS)).is_ok());
+        assert_eq!(
+            parse(&chain(MAX_FILTER_CONDITIONS + 1))
+                .unwrap_err()
+                .message,
+            "too many conditions"
+        );
+        assert!(parse(&chain(200_000)).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use super::ast::{Condition, Expr, Identifier, Operand};
+use super::lexer::{Token, TokenKind};
+use super::{FilterError, MAX_FILTER_CONDITIONS, MAX_FILTER_DEPTH};
+
+// Parser builds the expression tree, `&&` binds tighter than `||`.
+// the nesting and the number of conditions are limited, so untrusted filters
+// can't overflow the stack when the tree is parsed, evaluated or dropped.
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+    depth: usize,
+    conditions: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(tokens: Vec<Token>, end: usize) -> Self {
+        Parser {
+            tokens,
+            index: 0,
+            end,
+            depth: 0,
+            conditions: 0,
+        }
+    }
+
+    pub(crate) fn parse(mut self) -> Result<Expr, FilterError> {
+        if self.tokens.is_empty() {
+            return Err(FilterError::new(0, "empty expression"));
+        }
+
+        let expr = self.parse_or()?;
+
+        match self.peek() {
+            None => Ok(expr),
+            Some(token) if token.kind == TokenKind::CloseParen => Err(FilterError::new(
+                token.position,
+                "unexpected closing parenthesis",
+            )),
+            Some(token) => Err(FilterError::new(
+                token.position,
+                "expected && or || between conditions",
+            )),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    // returns the position of the current token, or the end of the input.
+    fn position(&self) -> usize {
+        self.peek().map_or(self.end, |token| token.position)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.parse_and()?;
+
+        while matches!(self.peek(), Some(token) if token.kind == TokenKind::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.parse_primary()?;
+
+        while matches!(self.peek(), Some(token) if token.kind == TokenKind::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_primary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, FilterError> {
+        if let Some(token) = self.peek() {
+            if token.kind == TokenKind::OpenParen {
+                let position = token.position;
+                if self.depth == MAX_FILTER_DEPTH {
+                    return Err(FilterError::new(position, "too deeply nested parentheses"));
+                }
+                self.next();
+
+                if matches!(self.peek(), Some(token) if token.kind == TokenKind::CloseParen) {
+                    return Err(FilterError::new(position, "empty parentheses"));
+                }
+
+                self.depth += 1;
+                let expr = self.parse_or()?;
+                self.depth -= 1;
+
+                return match self.next() {
+                    Some(token) if token.kind == TokenKind::CloseParen => Ok(expr),
+                    _ => Err(FilterError::new(position, "missing closing parenthesis")),
+                };
+            }
+        }
+
+        self.parse_condition()
+    }
+
+    fn parse_condition(&mut self) -> Result<Expr, FilterError> {
+        if self.conditions == MAX_FILTER_CONDITIONS {
+            return Err(FilterError::new(self.position(), "too many conditions"));
+        }
+        self.conditions += 1;
+
+        let left = self.parse_operand()?;
+
+        let position = self.position();
+        let (operator, any) = match self.next() {
+            Some(Token {
+                kind: TokenKind::Operator(operator, any),
+                ..
+            }) => (operator, any),
+            _ => return Err(FilterError::new(position, "expected an operator")),
+        };
+
+        let right = self.parse_operand()?;
+
+        Ok(Expr::Condition(Condition {
+            left,
+            operator,
+            any,
+            right,
+            position,
+        }))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, FilterError> {
+        let position = self.position();
+
+        match self.next().map(|token| token.kind) {
+            Some(TokenKind::Text(text)) => Ok(Operand::Text(text)),
+            Some(TokenKind::Number(number)) => Ok(Operand::Number(number)),
+            Some(TokenKind::Identifier(name)) => Ok(match name.as_str() {
+                "true" => Operand::Bool(true),
+                "false" => Operand::Bool(false),
+                "null" => Operand::Null,
+                _ => Operand::Identifier(Identifier { name, position }),
+            }),
+            _ => Err(FilterError::new(position, "expected a field or a value")),
+        }
+    }
+}
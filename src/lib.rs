@@ -1,7 +1,12 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod error;
+pub mod filter;
 pub mod model;
 pub mod rpocket;
 pub mod service;
 pub mod store;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use crate::rpocket::PocketBase;
@@ -8,12 +8,58 @@ pub fn get_default_collection_type() -> String {
     DEFAULT_COLLECTION_TYPE.to_string()
 }
 
+/// DATE_FORMAT is the date format used by PocketBase.
+pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3fZ";
+
+/// parses a PocketBase date, eg. `2022-06-01 19:00:00.000Z`.
+/// the trailing `Z` is optional and RFC 3339 dates are accepted too.
+pub fn parse_date(value: &str) -> Result<chrono::DateTime<chrono::Utc>, chrono::ParseError> {
+    let trimmed = value.trim().trim_end_matches('Z');
+
+    match chrono::NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f") {
+        Ok(date) => Ok(date.and_utc()),
+        Err(_) => {
+            Ok(chrono::DateTime::parse_from_rfc3339(value.trim())?.with_timezone(&chrono::Utc))
+        }
+    }
+}
+
+/// formats a date the way PocketBase does.
+pub fn format_date(date: &chrono::DateTime<chrono::Utc>) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// date_format (de)serializes PocketBase dates, to be used with `#[serde(with)]`.
+pub mod date_format {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(
+        date: &chrono::DateTime<chrono::Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&super::format_date(date))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<chrono::DateTime<chrono::Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        super::parse_date(&value).map_err(serde::de::Error::custom)
+    }
+}
+
 /// BaseModel is the base model for all models.
 #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BaseModel {
     pub id: String,
+    #[serde(default)]
     pub created: String,
+    #[serde(default)]
     pub updated: String,
 }
 
@@ -44,6 +90,8 @@ pub struct Record {
 pub struct Admin {
     #[serde(flatten)]
     pub base: BaseModel,
+    /// superusers have no avatar since `v0.23`.
+    #[serde(default)]
     pub avatar: i64,
     pub email: String,
 }
@@ -71,8 +119,10 @@ pub struct ExternalAuth {
 }
 
 /// SchemaField is the model for a schema field.
+/// since `v0.23` the options of a field are next to its name,
+/// they are read into `options`.
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", from = "SchemaFieldData")]
 pub struct SchemaField {
     pub id: String,
     pub name: String,
@@ -83,9 +133,42 @@ pub struct SchemaField {
     pub options: HashMap<String, serde_json::Value>,
 }
 
+// SchemaFieldData is a schema field of any API version.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaFieldData {
+    #[serde(default)]
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    field_type: String,
+    #[serde(default)]
+    system: bool,
+    #[serde(default)]
+    required: bool,
+    options: Option<HashMap<String, serde_json::Value>>,
+    #[serde(flatten)]
+    extra: HashMap<String, serde_json::Value>,
+}
+
+impl From<SchemaFieldData> for SchemaField {
+    fn from(data: SchemaFieldData) -> Self {
+        SchemaField {
+            id: data.id,
+            name: data.name,
+            field_type: data.field_type,
+            system: data.system,
+            required: data.required,
+            options: data.options.unwrap_or(data.extra),
+        }
+    }
+}
+
 /// Collection is the model for a collection.
+/// since `v0.23` the schema is named `fields` and the options are next to the rules,
+/// they are read into `schema` and `options`.
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", from = "CollectionData")]
 pub struct Collection {
     #[serde(flatten)]
     pub base: BaseModel,
@@ -103,12 +186,148 @@ pub struct Collection {
     pub options: HashMap<String, serde_json::Value>,
 }
 
+impl Collection {
+    /// serializes the collection in the format of the API version.
+    pub fn to_api_value(
+        &self,
+        api_version: crate::rpocket::ApiVersion,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        if api_version == crate::rpocket::ApiVersion::V0_14 {
+            return Ok(value);
+        }
+
+        // `options` are moved next to the rules and the fields.
+        let flatten = |value: &mut serde_json::Value| {
+            if let Some(object) = value.as_object_mut() {
+                if let Some(serde_json::Value::Object(options)) = object.remove("options") {
+                    for (key, option) in options {
+                        object.entry(key).or_insert(option);
+                    }
+                }
+            }
+        };
+
+        flatten(&mut value);
+        if let Some(object) = value.as_object_mut() {
+            if let Some(mut fields) = object.remove("schema") {
+                fields
+                    .as_array_mut()
+                    .into_iter()
+                    .flatten()
+                    .for_each(flatten);
+                object.insert("fields".to_string(), fields);
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+// CollectionData is a collection of any API version.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CollectionData {
+    #[serde(flatten)]
+    base: BaseModel,
+    name: String,
+    #[serde(rename = "type", default = "get_default_collection_type")]
+    collection_type: String,
+    #[serde(default, alias = "fields")]
+    schema: Vec<SchemaField>,
+    #[serde(default)]
+    indexes: Vec<String>,
+    #[serde(default)]
+    system: bool,
+    list_rule: Option<String>,
+    view_rule: Option<String>,
+    create_rule: Option<String>,
+    update_rule: Option<String>,
+    delete_rule: Option<String>,
+    options: Option<HashMap<String, serde_json::Value>>,
+    #[serde(flatten)]
+    extra: HashMap<String, serde_json::Value>,
+}
+
+impl From<CollectionData> for Collection {
+    fn from(data: CollectionData) -> Self {
+        let options = data.options.unwrap_or_else(|| {
+            let mut extra = data.extra;
+            extra.retain(|key, _| !matches!(key.as_str(), "id" | "created" | "updated"));
+            extra
+        });
+
+        Collection {
+            base: data.base,
+            name: data.name,
+            collection_type: data.collection_type,
+            schema: data.schema,
+            indexes: data.indexes,
+            system: data.system,
+            list_rule: data.list_rule,
+            view_rule: data.view_rule,
+            create_rule: data.create_rule,
+            update_rule: data.update_rule,
+            delete_rule: data.delete_rule,
+            options,
+        }
+    }
+}
+
+/// Log is the model for a log since `v0.23`,
+/// request logs have `data.type = "request"`.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    #[serde(flatten)]
+    pub base: BaseModel,
+    #[serde(default)]
+    pub level: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub data: HashMap<String, serde_json::Value>,
+}
+
+impl From<Log> for LogRequest {
+    /// converts a request log, the `data` that has no field is kept in `meta`.
+    fn from(log: Log) -> Self {
+        let mut meta = log.data;
+        let mut take = |key: &str| match meta.remove(key) {
+            Some(serde_json::Value::String(value)) => value,
+            Some(value) => {
+                meta.insert(key.to_string(), value);
+                String::new()
+            }
+            None => String::new(),
+        };
+
+        LogRequest {
+            base: log.base,
+            url: take("url"),
+            method: take("method"),
+            auth: take("auth"),
+            remote_ip: take("remoteIP"),
+            user_ip: take("userIP"),
+            referer: take("referer"),
+            user_agent: take("userAgent"),
+            status: meta
+                .remove("status")
+                .and_then(|status| status.as_i64())
+                .unwrap_or_default(),
+            meta,
+        }
+    }
+}
+
 /// LogRequest is the model for a log request.
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LogRequest {
     #[serde(flatten)]
     pub base: BaseModel,
+    #[serde(default)]
+    pub url: String,
     pub method: String,
     pub status: i64,
     pub auth: String,
@@ -118,3 +337,141 @@ pub struct LogRequest {
     pub user_agent: String,
     pub meta: HashMap<String, serde_json::Value>,
 }
+
+impl LogRequest {
+    /// returns the request execution time in milliseconds (`meta.execTime`).
+    pub fn exec_time(&self) -> Option<f64> {
+        self.meta.get("execTime").and_then(|value| value.as_f64())
+    }
+}
+
+/// Settings is the model for the application settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub meta: MetaConfig,
+    pub logs: LogsConfig,
+    pub smtp: SmtpConfig,
+    pub s3: S3Config,
+    pub backups: BackupsConfig,
+    pub admin_auth_token: TokenConfig,
+    pub admin_password_reset_token: TokenConfig,
+    pub admin_file_token: TokenConfig,
+    pub record_auth_token: TokenConfig,
+    pub record_password_reset_token: TokenConfig,
+    pub record_email_change_token: TokenConfig,
+    pub record_verification_token: TokenConfig,
+    pub record_file_token: TokenConfig,
+    pub google_auth: AuthProviderConfig,
+    pub facebook_auth: AuthProviderConfig,
+    pub github_auth: AuthProviderConfig,
+    pub gitlab_auth: AuthProviderConfig,
+    pub discord_auth: AuthProviderConfig,
+    pub twitter_auth: AuthProviderConfig,
+    pub microsoft_auth: AuthProviderConfig,
+    pub spotify_auth: AuthProviderConfig,
+    pub kakao_auth: AuthProviderConfig,
+    pub twitch_auth: AuthProviderConfig,
+    pub strava_auth: AuthProviderConfig,
+    pub gitee_auth: AuthProviderConfig,
+    pub livechat_auth: AuthProviderConfig,
+    pub gitea_auth: AuthProviderConfig,
+    pub oidc_auth: AuthProviderConfig,
+    pub oidc2_auth: AuthProviderConfig,
+    pub oidc3_auth: AuthProviderConfig,
+    pub apple_auth: AuthProviderConfig,
+    pub instagram_auth: AuthProviderConfig,
+    pub vk_auth: AuthProviderConfig,
+    pub yandex_auth: AuthProviderConfig,
+    pub patreon_auth: AuthProviderConfig,
+    pub mailcow_auth: AuthProviderConfig,
+    pub bitbucket_auth: AuthProviderConfig,
+    pub planningcenter_auth: AuthProviderConfig,
+}
+
+/// MetaConfig is the model for the application meta settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetaConfig {
+    pub app_name: String,
+    pub app_url: String,
+    pub hide_controls: bool,
+    pub sender_name: String,
+    pub sender_address: String,
+    pub verification_template: EmailTemplate,
+    pub reset_password_template: EmailTemplate,
+    pub confirm_email_change_template: EmailTemplate,
+}
+
+/// EmailTemplate is the model for an email template.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmailTemplate {
+    pub body: String,
+    pub subject: String,
+    pub action_url: String,
+}
+
+/// LogsConfig is the model for the logs settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogsConfig {
+    pub max_days: i64,
+}
+
+/// SmtpConfig is the model for the smtp settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SmtpConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: i64,
+    pub username: String,
+    pub password: String,
+    pub auth_method: String,
+    pub tls: bool,
+}
+
+/// S3Config is the model for the s3 storage settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct S3Config {
+    pub enabled: bool,
+    pub bucket: String,
+    pub region: String,
+    pub endpoint: String,
+    pub access_key: String,
+    pub secret: String,
+    pub force_path_style: bool,
+}
+
+/// BackupsConfig is the model for the backups settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupsConfig {
+    pub cron: String,
+    pub cron_max_keep: i64,
+    pub s3: S3Config,
+}
+
+/// TokenConfig is the model for a token settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TokenConfig {
+    pub secret: String,
+    pub duration: i64,
+}
+
+/// AuthProviderConfig is the model for an OAuth2 provider settings.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuthProviderConfig {
+    pub enabled: bool,
+    pub client_id: String,
+    pub client_secret: String,
+    pub auth_url: String,
+    pub token_url: String,
+    pub user_api_url: String,
+    pub display_name: String,
+    pub pkce: Option<bool>,
+}
@@ -1,12 +1,51 @@
 use crate::{error::RPocketError, service, store};
 use async_trait::async_trait;
-use futures::future::BoxFuture;
-use std::sync::Arc;
+use futures::future::{AbortHandle, Abortable, BoxFuture};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tower::layer::util::Identity;
 
 pub const TOKEN_KEY: &str = "pb_auth";
 pub const USER_OR_ADMIN_KEY: &str = "pb_user_or_admin";
 
+/// ApiVersion is the generation of the PocketBase API the client talks to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ApiVersion {
+    /// `v0.14.x` to `v0.22.x`, with `/api/admins`, collection `schema` and `/api/logs/requests`.
+    #[default]
+    V0_14,
+    /// `v0.23.x` and newer, admins are `_superusers` records, collections have `fields`
+    /// and the logs are served by `/api/logs`.
+    V0_23,
+}
+
+impl ApiVersion {
+    /// returns the base path of the admins crud.
+    pub fn admin_base_path(self) -> &'static [&'static str] {
+        match self {
+            ApiVersion::V0_14 => &["api", "admins"],
+            ApiVersion::V0_23 => &["api", "collections", "_superusers", "records"],
+        }
+    }
+
+    /// returns the path of an admin action, eg. `auth-with-password`.
+    pub fn admin_action_path(self, action: &str) -> Vec<&str> {
+        match self {
+            ApiVersion::V0_14 => vec!["api", "admins", action],
+            ApiVersion::V0_23 => vec!["api", "collections", "_superusers", action],
+        }
+    }
+
+    /// returns the base path of the request logs crud.
+    pub fn log_base_path(self) -> &'static [&'static str] {
+        match self {
+            ApiVersion::V0_14 => &["api", "logs", "requests"],
+            ApiVersion::V0_23 => &["api", "logs"],
+        }
+    }
+}
+
 #[async_trait]
 pub trait PocketBaseClient {
     /// returns the default language.
@@ -15,27 +54,41 @@ pub trait PocketBaseClient {
     /// returns the base url.
     fn base_url(&self) -> &url::Url;
 
+    /// returns the url of an endpoint built from its path segments,
+    /// see [`build_url`].
+    fn url(&self, segments: &[&str]) -> Result<url::Url, RPocketError> {
+        build_url(self.base_url(), segments)
+    }
+
     /// returns the request builder.
     fn request_builder(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder;
 
     /// returns the storage.
     fn storage(&self) -> Arc<dyn store::Storage + Sync + Send>;
 
+    /// returns the API version of the server, `ApiVersion::V0_14` by default.
+    fn api_version(&self) -> ApiVersion {
+        ApiVersion::default()
+    }
+
+    /// returns the registry of in-flight requests,
+    /// requests can't be cancelled if the client has none.
+    fn requests(&self) -> Option<&RequestRegistry> {
+        None
+    }
+
     /// execute a request.
-    async fn call(
-        &mut self,
-        request: PocketBaseRequest,
-    ) -> Result<PocketBaseResponse, RPocketError>
+    async fn call(&self, request: PocketBaseRequest) -> Result<PocketBaseResponse, RPocketError>
     where
         Self: Sized;
 
     /// returns auth state service.
-    fn auth_state(&mut self) -> service::auth_state::AuthStateService<Self>
+    fn auth_state(&self) -> service::auth_state::AuthStateService<'_, Self>
     where
         Self: Sized;
 
     /// returns http service.
-    fn http(&mut self) -> service::http::HTTPService<Self>
+    fn http(&self) -> service::http::HTTPService<'_, Self>
     where
         Self: Sized,
     {
@@ -43,7 +96,7 @@ pub trait PocketBaseClient {
     }
 
     /// returns crud service.
-    fn crud<'a>(&'a mut self, base_path: &'a str) -> service::crud::CRUDService<'a, Self>
+    fn crud<'a>(&'a self, base_path: &'a [&'a str]) -> service::crud::CRUDService<'a, Self>
     where
         Self: Sized,
     {
@@ -51,7 +104,7 @@ pub trait PocketBaseClient {
     }
 
     /// returns record service.
-    fn record<'a>(&'a mut self, name: &'a str) -> service::record::RecordService<'a, Self>
+    fn record<'a>(&'a self, name: &'a str) -> service::record::RecordService<'a, Self>
     where
         Self: Sized,
     {
@@ -59,7 +112,7 @@ pub trait PocketBaseClient {
     }
 
     /// returns admin service.
-    fn admin(&mut self) -> service::admin::AdminService<Self>
+    fn admin(&self) -> service::admin::AdminService<'_, Self>
     where
         Self: Sized,
     {
@@ -67,7 +120,7 @@ pub trait PocketBaseClient {
     }
 
     /// returns collection service.
-    fn collection(&mut self) -> service::collection::CollectionService<Self>
+    fn collection(&self) -> service::collection::CollectionService<'_, Self>
     where
         Self: Sized,
     {
@@ -75,7 +128,7 @@ pub trait PocketBaseClient {
     }
 
     /// returns log service.
-    fn log(&mut self) -> service::log::LogService<Self>
+    fn log(&self) -> service::log::LogService<'_, Self>
     where
         Self: Sized,
     {
@@ -83,7 +136,7 @@ pub trait PocketBaseClient {
     }
 
     /// returns setting service.
-    fn setting(&mut self) -> service::setting::SettingService<Self>
+    fn setting(&self) -> service::setting::SettingService<'_, Self>
     where
         Self: Sized,
     {
@@ -91,7 +144,7 @@ pub trait PocketBaseClient {
     }
 
     /// retuns health service.
-    fn health(&mut self) -> service::health::HealthService<Self>
+    fn health(&self) -> service::health::HealthService<'_, Self>
     where
         Self: Sized,
     {
@@ -123,15 +176,211 @@ pub enum PocketBaseResponse {
     HTTP(PocketBaseHTTPResponse),
 }
 
+/// RequestRegistry keeps track of the in-flight requests so they can be cancelled.
+#[derive(Debug, Default)]
+pub struct RequestRegistry {
+    next_id: AtomicU64,
+    in_flight: Mutex<HashMap<u64, (Option<String>, AbortHandle)>>,
+}
+
+impl RequestRegistry {
+    /// create a new RequestRegistry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// runs the future until it completes or is cancelled.
+    /// a running future with the same key is cancelled first.
+    pub async fn run<F, T>(&self, key: Option<&str>, future: F) -> Result<T, RPocketError>
+    where
+        F: std::future::Future<Output = Result<T, RPocketError>>,
+    {
+        let (handle, registration) = AbortHandle::new_pair();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        {
+            let mut in_flight = self
+                .in_flight
+                .lock()
+                .map_err(|_| RPocketError::MutexError)?;
+            if let Some(key) = key {
+                abort_where(&mut in_flight, |other| other == Some(key));
+            }
+            in_flight.insert(id, (key.map(str::to_string), handle));
+        }
+
+        let _guard = RequestGuard { registry: self, id };
+
+        match Abortable::new(future, registration).await {
+            Ok(result) => result,
+            Err(_) => Err(RPocketError::Cancelled),
+        }
+    }
+
+    /// cancels the in-flight requests with the given key.
+    pub fn cancel(&self, key: &str) -> Result<(), RPocketError> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        abort_where(&mut in_flight, |other| other == Some(key));
+        Ok(())
+    }
+
+    /// cancels all in-flight requests.
+    pub fn cancel_all(&self) -> Result<(), RPocketError> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        abort_where(&mut in_flight, |_| true);
+        Ok(())
+    }
+}
+
+// aborts and removes the requests whose key matches.
+fn abort_where<P>(in_flight: &mut HashMap<u64, (Option<String>, AbortHandle)>, predicate: P)
+where
+    P: Fn(Option<&str>) -> bool,
+{
+    in_flight.retain(|_, (key, handle)| {
+        if predicate(key.as_deref()) {
+            handle.abort();
+            return false;
+        }
+        true
+    });
+}
+
+// RequestGuard removes a request from the registry once it is done or dropped.
+struct RequestGuard<'a> {
+    registry: &'a RequestRegistry,
+    id: u64,
+}
+
+impl Drop for RequestGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.registry.in_flight.lock() {
+            in_flight.remove(&self.id);
+        }
+    }
+}
+
+/// normalizes the base URL so joining relative paths keeps its path prefix,
+/// eg. `https://host/pb` becomes `https://host/pb/`.
+pub fn normalize_base_url(base_url: &str) -> Result<url::Url, RPocketError> {
+    let mut url = url::Url::parse(base_url.trim())?;
+
+    if url.cannot_be_a_base() || !matches!(url.scheme(), "http" | "https") {
+        return Err(RPocketError::ConfigError(format!(
+            "base url must be an http or https url: {}",
+            base_url
+        )));
+    }
+
+    url.set_query(None);
+    url.set_fragment(None);
+
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+
+    Ok(url)
+}
+
+/// builds the url of an endpoint by appending the path segments to the base url,
+/// eg. `["api", "collections", "my posts"]` on `https://host/pb/` gives
+/// `https://host/pb/api/collections/my%20posts`.
+/// segments are percent-encoded, empty, `.` and `..` segments and segments
+/// containing `/` or `\` are rejected so ids can not escape their path.
+pub fn build_url(base_url: &url::Url, segments: &[&str]) -> Result<url::Url, RPocketError> {
+    let mut url = base_url.clone();
+
+    {
+        let mut path = url.path_segments_mut().map_err(|_| {
+            RPocketError::ConfigError(format!("base url can not be a base: {}", base_url))
+        })?;
+        path.pop_if_empty();
+
+        for segment in segments {
+            if segment.is_empty()
+                || *segment == "."
+                || *segment == ".."
+                || segment.contains(['/', '\\'])
+            {
+                return Err(RPocketError::PathError(segment.to_string()));
+            }
+
+            path.push(segment);
+        }
+    }
+
+    Ok(url)
+}
+
+// builds the headers sent with every request.
+pub(crate) fn build_default_headers(
+    headers: &[(String, String)],
+    user_agent: Option<&str>,
+) -> Result<reqwest::header::HeaderMap, RPocketError> {
+    let mut header_map = reqwest::header::HeaderMap::with_capacity(headers.len() + 1);
+
+    for (key, value) in headers {
+        let name = reqwest::header::HeaderName::from_bytes(key.as_bytes())
+            .map_err(|_| RPocketError::ConfigError(format!("invalid header name: {}", key)))?;
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| RPocketError::ConfigError(format!("invalid header value for {}", key)))?;
+        header_map.append(name, value);
+    }
+
+    if let Some(user_agent) = user_agent {
+        let value = reqwest::header::HeaderValue::from_str(user_agent)
+            .map_err(|_| RPocketError::ConfigError("invalid user agent".to_string()))?;
+        header_map.insert(reqwest::header::USER_AGENT, value);
+    }
+
+    Ok(header_map)
+}
+
+// validates the storage keys.
+pub(crate) fn validate_keys(
+    lang: &str,
+    token_key: &str,
+    user_or_admin_key: &str,
+) -> Result<(), RPocketError> {
+    if lang.is_empty() {
+        return Err(RPocketError::ConfigError(
+            "lang must not be empty".to_string(),
+        ));
+    }
+    if token_key.is_empty() || user_or_admin_key.is_empty() {
+        return Err(RPocketError::ConfigError(
+            "storage keys must not be empty".to_string(),
+        ));
+    }
+    if token_key == user_or_admin_key {
+        return Err(RPocketError::ConfigError(
+            "token key and user or admin key must be different".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// PocketBaseBuilder is the builder for PocketBase.
 /// it is used to create a PocketBase struct.
 pub struct PocketBaseBuilder<L> {
-    lang: &'static str,
-    token_key: &'static str,
-    user_or_admin_key: &'static str,
-    base_url: url::Url,
+    lang: String,
+    token_key: String,
+    user_or_admin_key: String,
+    base_url: String,
+    headers: Vec<(String, String)>,
+    timeout: Option<std::time::Duration>,
+    user_agent: Option<String>,
     storage: Arc<dyn store::Storage + Sync + Send>,
     http_client: reqwest::Client,
+    api_version: ApiVersion,
     layer: L,
 }
 
@@ -139,12 +388,16 @@ impl PocketBaseBuilder<Identity> {
     /// create a new PocketBaseBuilder.
     pub fn new() -> Self {
         PocketBaseBuilder {
-            lang: "en",
-            token_key: TOKEN_KEY,
-            user_or_admin_key: USER_OR_ADMIN_KEY,
-            base_url: url::Url::parse("https://pocketbase.io").unwrap(),
+            lang: "en".to_string(),
+            token_key: TOKEN_KEY.to_string(),
+            user_or_admin_key: USER_OR_ADMIN_KEY.to_string(),
+            base_url: "https://pocketbase.io".to_string(),
+            headers: Vec::new(),
+            timeout: None,
+            user_agent: None,
             storage: Arc::new(store::MemoryStorage::new()),
             http_client: reqwest::Client::new(),
+            api_version: ApiVersion::default(),
             layer: Identity::new(),
         }
     }
@@ -158,26 +411,45 @@ impl Default for PocketBaseBuilder<Identity> {
 
 impl<L> PocketBaseBuilder<L> {
     /// set the language.
-    pub fn lang(mut self, lang: &'static str) -> Self {
-        self.lang = lang;
+    pub fn lang<T: Into<String>>(mut self, lang: T) -> Self {
+        self.lang = lang.into();
         self
     }
 
     /// set the base URL.
-    pub fn base_url(mut self, base_url: &str) -> Self {
-        self.base_url = url::Url::parse(base_url).unwrap();
+    /// the URL is validated by `build`, a path prefix like `/pb` is kept.
+    pub fn base_url<T: Into<String>>(mut self, base_url: T) -> Self {
+        self.base_url = base_url.into();
         self
     }
 
     /// set token key
-    pub fn token_key(mut self, token_key: &'static str) -> Self {
-        self.token_key = token_key;
+    pub fn token_key<T: Into<String>>(mut self, token_key: T) -> Self {
+        self.token_key = token_key.into();
         self
     }
 
     /// set user or admin key
-    pub fn user_or_admin_key(mut self, user_or_admin_key: &'static str) -> Self {
-        self.user_or_admin_key = user_or_admin_key;
+    pub fn user_or_admin_key<T: Into<String>>(mut self, user_or_admin_key: T) -> Self {
+        self.user_or_admin_key = user_or_admin_key.into();
+        self
+    }
+
+    /// add a header sent with every request.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// set the timeout of every request.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// set the user agent.
+    pub fn user_agent<T: Into<String>>(mut self, user_agent: T) -> Self {
+        self.user_agent = Some(user_agent.into());
         self
     }
 
@@ -193,6 +465,13 @@ impl<L> PocketBaseBuilder<L> {
         self
     }
 
+    /// set the API version of the server,
+    /// use `PocketBase::detect_api_version` to ask the server instead.
+    pub fn api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
     /// add middlewares.
     pub fn layer<T>(self, layer: T) -> PocketBaseBuilder<tower::layer::util::Stack<T, L>> {
         PocketBaseBuilder {
@@ -200,14 +479,19 @@ impl<L> PocketBaseBuilder<L> {
             token_key: self.token_key,
             user_or_admin_key: self.user_or_admin_key,
             base_url: self.base_url,
+            headers: self.headers,
+            timeout: self.timeout,
+            user_agent: self.user_agent,
             storage: self.storage,
             layer: tower::layer::util::Stack::new(layer, self.layer),
             http_client: self.http_client,
+            api_version: self.api_version,
         }
     }
 
     /// build the PocketBase.
-    pub fn build(self) -> PocketBase<L::Service>
+    /// returns an error if the base url, the headers or the keys are invalid.
+    pub fn build(self) -> Result<PocketBase<L::Service>, RPocketError>
     where
         L: tower::Layer<PocketBaseService>,
         L::Service: tower_service::Service<
@@ -219,13 +503,19 @@ impl<L> PocketBaseBuilder<L> {
             + Send
             + Sync,
     {
+        validate_keys(&self.lang, &self.token_key, &self.user_or_admin_key)?;
+
         let inner = Arc::new(PocketBaseRef {
+            base_url: normalize_base_url(&self.base_url)?,
+            default_headers: build_default_headers(&self.headers, self.user_agent.as_deref())?,
+            timeout: self.timeout,
             lang: self.lang,
             token_key: self.token_key,
             user_or_admin_key: self.user_or_admin_key,
-            base_url: self.base_url,
             storage: self.storage,
             http_client: self.http_client,
+            api_version: Mutex::new(self.api_version),
+            requests: RequestRegistry::new(),
         });
 
         let client = PocketBaseService {
@@ -234,20 +524,23 @@ impl<L> PocketBaseBuilder<L> {
 
         let client = self.layer.layer(client);
 
-        PocketBase { inner, client }
+        Ok(PocketBase { inner, client })
     }
 }
 
 // PocketBaseRef is the reference of PocketBase state.
 // it is used to clone PocketBase.
-#[derive(Clone)]
 struct PocketBaseRef {
-    lang: &'static str,
-    token_key: &'static str,
-    user_or_admin_key: &'static str,
+    lang: String,
+    token_key: String,
+    user_or_admin_key: String,
     base_url: url::Url,
+    default_headers: reqwest::header::HeaderMap,
+    timeout: Option<std::time::Duration>,
     storage: Arc<dyn store::Storage + Sync + Send>,
     http_client: reqwest::Client,
+    api_version: Mutex<ApiVersion>,
+    requests: RequestRegistry,
 }
 
 /// PocketBaseService is the service for sending requests.
@@ -296,11 +589,113 @@ pub struct PocketBase<S> {
 
 impl PocketBase<PocketBaseService> {
     /// create a new PocketBase.
-    pub fn new(base_url: &str, lang: &'static str) -> Self {
+    ///
+    /// # Panics
+    ///
+    /// panics if the base url is invalid, use `PocketBaseBuilder` to handle the error.
+    pub fn new(base_url: &str, lang: &str) -> Self {
         PocketBaseBuilder::new()
             .base_url(base_url)
             .lang(lang)
             .build()
+            .expect("invalid PocketBase config")
+    }
+}
+
+impl<S> PocketBase<S> {
+    /// cancels the in-flight requests sent with the given request key.
+    pub fn cancel(&self, key: &str) -> Result<(), RPocketError> {
+        self.inner.requests.cancel(key)
+    }
+
+    /// cancels all in-flight requests.
+    pub fn cancel_all(&self) -> Result<(), RPocketError> {
+        self.inner.requests.cancel_all()
+    }
+
+    /// sets the API version of the server for this client and its clones.
+    pub fn set_api_version(&self, api_version: ApiVersion) -> Result<(), RPocketError> {
+        *self
+            .inner
+            .api_version
+            .lock()
+            .map_err(|_| RPocketError::MutexError)? = api_version;
+        Ok(())
+    }
+
+    /// returns a client for the same server and middlewares with another storage,
+    /// so another session. its in-flight requests are cancelled separately.
+    pub fn with_storage(
+        &self,
+        storage: Arc<dyn store::Storage + Sync + Send>,
+    ) -> Result<Self, RPocketError>
+    where
+        S: Clone,
+    {
+        let api_version = *self
+            .inner
+            .api_version
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+
+        Ok(PocketBase {
+            inner: Arc::new(PocketBaseRef {
+                lang: self.inner.lang.clone(),
+                token_key: self.inner.token_key.clone(),
+                user_or_admin_key: self.inner.user_or_admin_key.clone(),
+                base_url: self.inner.base_url.clone(),
+                default_headers: self.inner.default_headers.clone(),
+                timeout: self.inner.timeout,
+                storage,
+                http_client: self.inner.http_client.clone(),
+                api_version: Mutex::new(api_version),
+                requests: RequestRegistry::new(),
+            }),
+            client: self.client.clone(),
+        })
+    }
+}
+
+impl<S> PocketBase<S>
+where
+    Self: PocketBaseClient,
+{
+    /// asks the server for its API version and uses it for the next requests.
+    pub async fn detect_api_version(&self) -> Result<ApiVersion, RPocketError> {
+        let api_version = self
+            .health()
+            .detect_api_version(&service::health::HealthCheckConfig::default())
+            .await?;
+        self.set_api_version(api_version)?;
+        Ok(api_version)
+    }
+
+    /// returns a client authenticated as an auth record, with the token of the
+    /// impersonate endpoint in its own `MemoryStorage`, the session of this client is kept.
+    /// the token can't be refreshed, impersonate again once it expired.
+    pub async fn impersonate<B>(
+        &self,
+        config: &service::admin::AdminImpersonateConfig<B>,
+    ) -> Result<Self, RPocketError>
+    where
+        B: serde::Serialize,
+        S: Clone,
+    {
+        let response = self
+            .admin()
+            .impersonate::<service::record::RecordAuthResponse<crate::model::Record>, B>(config)
+            .await?;
+
+        let client = self.with_storage(Arc::new(store::MemoryStorage::new()))?;
+        client
+            .auth_state()
+            .save(
+                &response.token,
+                &service::auth_state::AuthPayload::User(response.record),
+            )
+            .await?;
+
+        Ok(client)
     }
 }
 
@@ -312,7 +707,8 @@ where
             Response = PocketBaseResponse,
             Error = RPocketError,
             Future = BoxFuture<'static, Result<PocketBaseResponse, RPocketError>>,
-        > + Send
+        > + Clone
+        + Send
         + Sync,
 {
     /// get the base URL.
@@ -322,7 +718,7 @@ where
 
     /// get the language.
     fn lang(&self) -> &str {
-        self.inner.lang
+        &self.inner.lang
     }
 
     /// get the storage.
@@ -330,25 +726,52 @@ where
         self.inner.storage.clone()
     }
 
-    fn auth_state(&mut self) -> service::auth_state::AuthStateService<Self> {
+    /// get the API version of the server.
+    fn api_version(&self) -> ApiVersion {
+        self.inner
+            .api_version
+            .lock()
+            .map(|api_version| *api_version)
+            .unwrap_or_default()
+    }
+
+    /// get the registry of in-flight requests.
+    fn requests(&self) -> Option<&RequestRegistry> {
+        Some(&self.inner.requests)
+    }
+
+    fn auth_state(&self) -> service::auth_state::AuthStateService<'_, Self> {
         service::auth_state::AuthStateService::new(
             self,
-            self.inner.token_key,
-            self.inner.user_or_admin_key,
+            &self.inner.token_key,
+            &self.inner.user_or_admin_key,
         )
     }
 
     /// get request builder.
     fn request_builder(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
-        self.inner.http_client.request(method, url)
+        let mut request_builder = self
+            .inner
+            .http_client
+            .request(method, url)
+            .headers(self.inner.default_headers.clone());
+
+        if let Some(timeout) = self.inner.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        request_builder
     }
 
     /// execute a request.
-    async fn call(
-        &mut self,
-        request: PocketBaseRequest,
-    ) -> Result<PocketBaseResponse, RPocketError> {
-        tower::util::Ready::new(&mut self.client)
+    /// the middleware stack is cloned for every request, as recommended by tower,
+    /// so concurrent requests don't wait on each other.
+    /// state that has to be shared between requests must live behind an `Arc`
+    /// in the middleware, eg. `tower::limit::ConcurrencyLimit`.
+    async fn call(&self, request: PocketBaseRequest) -> Result<PocketBaseResponse, RPocketError> {
+        let mut client = self.client.clone();
+
+        tower::util::Ready::new(&mut client)
             .await?
             .call(request)
             .await
@@ -375,9 +798,135 @@ mod test {
         assert_eq!(request.url().as_str(), "http://localhost:8080/");
     }
 
+    #[test]
+    fn test_pocket_base_builder_invalid_config() {
+        assert!(matches!(
+            PocketBaseBuilder::new().base_url("not a url").build(),
+            Err(RPocketError::UrlError(..))
+        ));
+        assert!(matches!(
+            PocketBaseBuilder::new()
+                .base_url("mailto:test@example.com")
+                .build(),
+            Err(RPocketError::ConfigError(..))
+        ));
+        assert!(matches!(
+            PocketBaseBuilder::new()
+                .header("bad header", "value")
+                .build(),
+            Err(RPocketError::ConfigError(..))
+        ));
+        assert!(matches!(
+            PocketBaseBuilder::new().token_key("").build(),
+            Err(RPocketError::ConfigError(..))
+        ));
+    }
+
+    #[test]
+    fn test_pocket_base_builder_base_url_prefix() {
+        let base = PocketBaseBuilder::new()
+            .base_url(String::from("https://example.com/pb?x=1"))
+            .lang(String::from("de"))
+            .token_key(String::from("token"))
+            .user_or_admin_key(String::from("model"))
+            .build()
+            .unwrap();
+
+        assert_eq!(base.base_url().as_str(), "https://example.com/pb/");
+        assert_eq!(
+            base.base_url().join("api/health").unwrap().as_str(),
+            "https://example.com/pb/api/health"
+        );
+        assert_eq!(base.lang(), "de");
+    }
+
+    #[test]
+    fn test_build_url() {
+        let base_url = normalize_base_url("https://example.com/pocketbase").unwrap();
+
+        assert_eq!(
+            build_url(
+                &base_url,
+                &["api", "collections", "my posts", "records", "a?b#c"]
+            )
+            .unwrap()
+            .as_str(),
+            "https://example.com/pocketbase/api/collections/my%20posts/records/a%3Fb%23c"
+        );
+
+        for segment in ["", ".", "..", "../admins", "a\\b"] {
+            assert!(matches!(
+                build_url(
+                    &base_url,
+                    &["api", "collections", "posts", "records", segment]
+                ),
+                Err(RPocketError::PathError(..))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_registry_cancel_same_key() {
+        let requests = RequestRegistry::new();
+
+        let (first, second) = tokio::join!(
+            requests.run(Some("search"), futures::future::pending::<Result<(), _>>()),
+            requests.run(Some("search"), async { Ok("done") }),
+        );
+
+        assert!(matches!(first, Err(RPocketError::Cancelled)));
+        assert_eq!(second.unwrap(), "done");
+        assert!(requests.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pocket_base_cancel() {
+        let base = PocketBase::new("http://localhost:8080", "en");
+        let requests = base.requests().unwrap();
+
+        let (keyed, other, _) = tokio::join!(
+            requests.run(Some("a"), futures::future::pending::<Result<(), _>>()),
+            requests.run(None, async { Ok(()) }),
+            async { base.cancel("a").unwrap() },
+        );
+        assert!(matches!(keyed, Err(RPocketError::Cancelled)));
+        assert!(other.is_ok());
+
+        let (keyed, unkeyed, _) = tokio::join!(
+            requests.run(Some("b"), futures::future::pending::<Result<(), _>>()),
+            requests.run(None, futures::future::pending::<Result<(), _>>()),
+            async { base.cancel_all().unwrap() },
+        );
+        assert!(matches!(keyed, Err(RPocketError::Cancelled)));
+        assert!(matches!(unkeyed, Err(RPocketError::Cancelled)));
+    }
+
+    #[test]
+    fn test_pocket_base_builder_request_defaults() {
+        let base = PocketBaseBuilder::new()
+            .base_url("http://localhost:8080")
+            .header("X-Tenant", "acme")
+            .user_agent("rpocket-test")
+            .timeout(std::time::Duration::from_secs(5))
+            .build()
+            .unwrap();
+
+        let request = base
+            .request_builder(reqwest::Method::GET, "http://localhost:8080")
+            .build()
+            .unwrap();
+
+        assert_eq!(request.headers()["X-Tenant"], "acme");
+        assert_eq!(
+            request.headers()[reqwest::header::USER_AGENT],
+            "rpocket-test"
+        );
+        assert_eq!(request.timeout(), Some(&std::time::Duration::from_secs(5)));
+    }
+
     #[tokio::test]
     async fn test_pocket_base_send_request() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -387,10 +936,11 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBaseBuilder::new()
+        let base = PocketBaseBuilder::new()
             .base_url(url.as_str())
             .lang("en")
-            .build();
+            .build()
+            .unwrap();
 
         let request_builder = base.request_builder(reqwest::Method::GET, url.as_str());
 
@@ -438,7 +988,7 @@ mod test {
 
     #[tokio::test]
     async fn test_pocket_base_builder_add_middlewares() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -449,14 +999,12 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBaseBuilder::new()
+        let base = PocketBaseBuilder::new()
             .base_url("http://localhost:8080")
             .lang("en")
-            .layer(tower::layer::layer_fn(|s| {
-                let service = TestService { inner: s };
-                service
-            }))
-            .build();
+            .layer(tower::layer::layer_fn(|s| TestService { inner: s }))
+            .build()
+            .unwrap();
 
         let request_builder = base.request_builder(reqwest::Method::GET, url.as_str());
         base.call(PocketBaseRequest::HTTP(PocketBaseHTTPRequest {
@@ -466,4 +1014,94 @@ mod test {
         .unwrap();
         mock.assert_async().await;
     }
+
+    #[derive(Clone)]
+    struct CountService<S> {
+        inner: S,
+        count: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl<S> tower_service::Service<PocketBaseRequest> for CountService<S>
+    where
+        S: tower_service::Service<PocketBaseRequest>,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: PocketBaseRequest) -> Self::Future {
+            self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.call(req)
+        }
+    }
+
+    struct Services<'a, C> {
+        health: service::health::HealthService<'a, C>,
+        records: service::record::RecordService<'a, C>,
+    }
+
+    #[tokio::test]
+    async fn test_pocket_base_concurrent_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let health_mock = server
+            .mock("GET", "/api/health")
+            .with_status(200)
+            .with_body(r#"{"status": 200, "message": "API is healthy."}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let record_mock = server
+            .mock("GET", "/api/collections/posts/records/1")
+            .with_status(200)
+            .with_body(r#"{"id": "1", "collectionId": "a", "collectionName": "posts"}"#)
+            .create_async()
+            .await;
+
+        let count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let layer_count = count.clone();
+        let base = PocketBaseBuilder::new()
+            .base_url(url.as_str())
+            .layer(tower::layer::layer_fn(move |s| CountService {
+                inner: s,
+                count: layer_count.clone(),
+            }))
+            .build()
+            .unwrap();
+
+        let services = Services {
+            health: base.health(),
+            records: base.record("posts"),
+        };
+        let health_config = service::health::HealthCheckConfig::default();
+        let record_config = service::crud::CRUDGetOneConfig {
+            id: "1".to_string(),
+            ..Default::default()
+        };
+
+        let health = base.health();
+        let crud = services.records.crud();
+        let (first, second, record) = tokio::join!(
+            services
+                .health
+                .check::<service::health::HealthCheckResponse>(&health_config),
+            health.check::<service::health::HealthCheckResponse>(&health_config),
+            crud.get_one::<crate::model::Record>(&record_config),
+        );
+
+        health_mock.assert_async().await;
+        record_mock.assert_async().await;
+        assert!(first.unwrap().status == 200);
+        assert!(second.unwrap().status == 200);
+        assert!(record.unwrap().base.id == "1");
+        assert!(count.load(std::sync::atomic::Ordering::SeqCst) == 3);
+    }
 }
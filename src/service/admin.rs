@@ -2,13 +2,24 @@ use crate::service;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::{error::RPocketError, model::Admin, service::auth_state::AuthPayload};
+use crate::{
+    error::RPocketError,
+    model::Admin,
+    rpocket::ApiVersion,
+    service::{
+        auth_state::{AuthPayload, SavedAuthResponse},
+        http::RequestOptions,
+        request::ApiRequest,
+    },
+};
 
 /// AdminAuthResponse is the response for the admin auth.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminAuthResponse {
     pub token: String,
+    /// the `record` of the `_superusers` auth response since `v0.23`.
+    #[serde(alias = "record")]
     pub admin: Admin,
 
     #[serde(flatten)]
@@ -25,6 +36,8 @@ pub struct AdminAuthWithPasswordConfig<T> {
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
     #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
     pub without_saving: bool,
 }
 
@@ -36,6 +49,8 @@ pub struct AdminAuthRefreshConfig<T> {
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
     #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
     pub without_saving: bool,
 }
 
@@ -47,6 +62,8 @@ pub struct AdminRequestPasswordResetConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// AdminConfirmPasswordResetConfig is the config for the admin confirm password reset.
@@ -60,12 +77,150 @@ pub struct AdminConfirmPasswordResetConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+/// AdminImpersonateConfig is the config for the admin impersonate, since `v0.23`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdminImpersonateConfig<T> {
+    /// auth collection of the record.
+    #[serde(skip)]
+    pub collection: String,
+    /// id of the record.
+    #[serde(skip)]
+    pub id: String,
+    /// validity of the token in seconds, the auth token duration of the collection if missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    #[serde(flatten)]
+    pub body: T,
+    #[serde(skip)]
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+impl SavedAuthResponse for AdminAuthResponse {
+    fn auth_payload(&self) -> (&str, AuthPayload) {
+        (&self.token, AuthPayload::Admin(self.admin.clone()))
+    }
+}
+
+// AdminRequests builds the admin requests of the async and the blocking services.
+pub(crate) struct AdminRequests {
+    pub(crate) api_version: ApiVersion,
+}
+
+impl AdminRequests {
+    fn action<'c, B>(
+        &self,
+        action: &str,
+        body: &B,
+        query_params: &[(String, String)],
+        options: &'c RequestOptions,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        let path = self.api_version.admin_action_path(action);
+        ApiRequest::new(reqwest::Method::POST, &path, options)
+            .query(query_params)
+            .json(body)
+    }
+
+    pub(crate) fn auth_with_password<'c, B>(
+        &self,
+        config: &'c AdminAuthWithPasswordConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "auth-with-password",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    // the impersonate endpoint exists since `v0.23`.
+    pub(crate) fn impersonate<'c, B>(
+        &self,
+        config: &'c AdminImpersonateConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        if self.api_version == ApiVersion::V0_14 {
+            return Err(RPocketError::ConfigError(
+                "impersonate requires the v0.23 API".to_string(),
+            ));
+        }
+
+        let path = [
+            "api",
+            "collections",
+            &config.collection,
+            "impersonate",
+            &config.id,
+        ];
+        ApiRequest::new(reqwest::Method::POST, &path, &config.options)
+            .query(&config.query_params)
+            .json(config)
+    }
+
+    pub(crate) fn auth_refresh<'c, B>(
+        &self,
+        config: &'c AdminAuthRefreshConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "auth-refresh",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn request_password_reset<'c, B>(
+        &self,
+        config: &'c AdminRequestPasswordResetConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "request-password-reset",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn confirm_password_reset<'c, B>(
+        &self,
+        config: &'c AdminConfirmPasswordResetConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "confirm-password-reset",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
 }
 
 /// AdminService is the service for the admin.
 pub struct AdminService<'a, C> {
-    client: &'a mut C,
-    admin_base_path: String,
+    client: &'a C,
+    admin_base_path: &'static [&'static str],
+    requests: AdminRequests,
 }
 
 impl<'a, C> AdminService<'a, C>
@@ -73,157 +228,93 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new AdminService.
-    pub fn new(client: &'a mut C) -> Self {
+    pub fn new(client: &'a C) -> Self {
         AdminService {
             client,
-            admin_base_path: "api/admins".to_string(),
+            admin_base_path: client.api_version().admin_base_path(),
+            requests: AdminRequests {
+                api_version: client.api_version(),
+            },
         }
     }
 
     /// returns crud service.
-    pub fn crud(&'a mut self) -> service::crud::CRUDService<'a, C> {
-        self.client.crud(&self.admin_base_path)
+    pub fn crud(&self) -> service::crud::CRUDService<'_, C> {
+        self.client.crud(self.admin_base_path)
     }
 
-    async fn save_auth_response<T>(
-        &mut self,
-        response: reqwest::Response,
+    /// authenticate with password
+    pub async fn auth_with_password<T, B>(
+        &self,
+        config: &AdminAuthWithPasswordConfig<B>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
+        B: Serialize,
     {
-        let auth_state = self.client.auth_state();
-        let auth_response = response.json::<AdminAuthResponse>().await?;
-
-        let token = auth_response.token;
-        let extra = auth_response.extra;
-        let user = AuthPayload::Admin(auth_response.admin);
-
-        auth_state.save(token.as_str(), &user).await?;
-
-        let admin = match user {
-            AuthPayload::Admin(admin) => admin,
-            _ => unreachable!(),
-        };
-
-        let auth_response = AdminAuthResponse {
-            token,
-            admin,
-            extra,
-        };
-
-        let auth_response = serde_json::to_value(&auth_response)?;
-        let response = serde_json::from_value(auth_response)?;
-
-        Ok(response)
+        let request = self.requests.auth_with_password(config)?;
+        self.client
+            .http()
+            .execute_auth::<AdminAuthResponse, T>(&request, config.without_saving)
+            .await
     }
 
-    /// authenticate with password
-    pub async fn auth_with_password<T, B>(
-        &mut self,
-        config: &AdminAuthWithPasswordConfig<B>,
+    /// returns a non-refreshable token of an auth record, the admin session is kept.
+    /// the impersonate endpoint exists since `v0.23`.
+    pub async fn impersonate<T, B>(
+        &self,
+        config: &AdminImpersonateConfig<B>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/auth-with-password", self.admin_base_path).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        let response = self.client.http().send(request_builder).await?;
-
-        if !config.without_saving {
-            return self.save_auth_response::<T>(response).await;
-        }
+        let request = self.requests.impersonate(config)?;
+        let response = self.client.http().execute(&request).await?;
 
         Ok(response.json::<T>().await?)
     }
 
     /// refreshes the current authenticated admin instance and
     pub async fn auth_refresh<T, B>(
-        &mut self,
+        &self,
         config: &AdminAuthRefreshConfig<B>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/auth-refresh", self.admin_base_path).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        let response = self.client.http().send(request_builder).await?;
-
-        if !config.without_saving {
-            return self.save_auth_response::<T>(response).await;
-        }
-
-        Ok(response.json::<T>().await?)
+        let request = self.requests.auth_refresh(config)?;
+        self.client
+            .http()
+            .execute_auth::<AdminAuthResponse, T>(&request, config.without_saving)
+            .await
     }
 
     /// ends auth admin password reset request.
     pub async fn request_password_reset<B>(
-        &mut self,
+        &self,
         config: &AdminRequestPasswordResetConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/request-password-reset", self.admin_base_path).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        let request = self.requests.request_password_reset(config)?;
+        self.client.http().execute(&request).await?;
 
         Ok(())
     }
 
     /// confirms auth admin password reset request.
     pub async fn confirm_password_reset<B>(
-        &mut self,
+        &self,
         config: &AdminConfirmPasswordResetConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/confirm-password-reset", self.admin_base_path).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        let request = self.requests.confirm_password_reset(config)?;
+        self.client.http().execute(&request).await?;
 
         Ok(())
     }
@@ -237,15 +328,15 @@ mod test {
 
     #[test]
     fn test_admin_crud() {
-        let mut base = PocketBase::new("http://test.com", "en");
-        let admin_service = AdminService::new(&mut base);
+        let base = PocketBase::new("http://test.com", "en");
+        let admin_service = AdminService::new(&base);
 
-        assert!(admin_service.admin_base_path == "api/admins");
+        assert!(admin_service.admin_base_path == ["api", "admins"]);
     }
 
     #[tokio::test]
     async fn test_admin_auth_with_password() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -268,8 +359,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut admin_service = AdminService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let admin_service = AdminService::new(&base);
         let config = AdminAuthWithPasswordConfig::<HashMap<String, String>> {
             identity: String::from_str("test").unwrap(),
             password: String::from_str("12345678").unwrap(),
@@ -314,7 +405,7 @@ mod test {
 
     #[tokio::test]
     async fn test_admin_auth_refresh() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -337,8 +428,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut admin_service = AdminService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let admin_service = AdminService::new(&base);
         let config = AdminAuthRefreshConfig::<HashMap<String, String>> {
             body: HashMap::new(),
             query_params: Vec::new(),
@@ -362,7 +453,7 @@ mod test {
 
     #[tokio::test]
     async fn test_admin_request_password_reset() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -374,12 +465,13 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut admin_service = AdminService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let admin_service = AdminService::new(&base);
         let config = AdminRequestPasswordResetConfig::<HashMap<String, String>> {
             email: String::from_str("example@example.com").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = admin_service
@@ -392,7 +484,7 @@ mod test {
 
     #[tokio::test]
     async fn test_admin_confirm_password_reset() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -404,14 +496,15 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut admin_service = AdminService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let admin_service = AdminService::new(&base);
         let config = AdminConfirmPasswordResetConfig::<HashMap<String, String>> {
             token: String::from_str("test").unwrap(),
             password: String::from_str("test").unwrap(),
             password_confirm: String::from_str("test").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = admin_service
@@ -421,4 +514,146 @@ mod test {
         mock.assert_async().await;
         response.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_admin_auth_with_password_superusers() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("POST", "/api/collections/_superusers/auth-with-password")
+            .with_status(200)
+            .match_body(r#"{"identity":"test@example.com","password":"12345678"}"#)
+            .with_body(
+                r#"{
+  "token": "token",
+  "record": {
+    "id": "b6e4b08274f34e9",
+    "collectionId": "pbc_3142635823",
+    "collectionName": "_superusers",
+    "created": "2024-11-01 07:13:09.735Z",
+    "updated": "2024-11-01 07:13:09.735Z",
+    "email": "test@example.com",
+    "emailVisibility": false,
+    "verified": true
+  }
+}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = crate::rpocket::PocketBaseBuilder::new()
+            .base_url(url)
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let admin_service = AdminService::new(&base);
+        assert!(admin_service.admin_base_path == ["api", "collections", "_superusers", "records"]);
+
+        let response = admin_service
+            .auth_with_password::<AdminAuthResponse, HashMap<String, String>>(
+                &AdminAuthWithPasswordConfig {
+                    identity: "test@example.com".to_string(),
+                    password: "12345678".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        mock.assert_async().await;
+        let response = response.unwrap();
+        assert_eq!(response.admin.base.id, "b6e4b08274f34e9");
+        assert_eq!(response.admin.email, "test@example.com");
+        assert!(matches!(
+            base.auth_state().get_user_or_admin().await.unwrap(),
+            Some(AuthPayload::Admin(admin)) if admin.email == "test@example.com"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admin_impersonate() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let impersonate_mock = server
+            .mock("POST", "/api/collections/users/impersonate/8171022dc95a4ed")
+            .match_header(reqwest::header::AUTHORIZATION.as_str(), "admin-token")
+            .match_body(r#"{"duration":3600}"#)
+            .with_status(200)
+            .with_body(
+                r#"{
+  "token": "user-token",
+  "record": {
+    "id": "8171022dc95a4ed",
+    "collectionId": "_pb_users_auth_",
+    "collectionName": "users",
+    "created": "2024-11-01 07:13:09.735Z",
+    "updated": "2024-11-01 07:13:09.735Z",
+    "email": "test@example.com"
+  }
+}"#,
+            )
+            .create_async()
+            .await;
+        let get_mock = server
+            .mock("GET", "/api/collections/users/records/8171022dc95a4ed")
+            .match_header(reqwest::header::AUTHORIZATION.as_str(), "user-token")
+            .with_status(200)
+            .with_body(
+                r#"{"id": "8171022dc95a4ed", "collectionId": "_pb_users_auth_", "collectionName": "users", "created": "", "updated": ""}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = crate::rpocket::PocketBaseBuilder::new()
+            .base_url(url)
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        base.storage()
+            .set(crate::rpocket::TOKEN_KEY, "admin-token")
+            .await
+            .unwrap();
+
+        let user = base
+            .impersonate(&AdminImpersonateConfig::<HashMap<String, String>> {
+                collection: "users".to_string(),
+                id: "8171022dc95a4ed".to_string(),
+                duration: Some(3600),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        impersonate_mock.assert_async().await;
+
+        user.record("users")
+            .crud()
+            .get_one::<crate::model::Record>(&crate::service::crud::CRUDGetOneConfig {
+                id: "8171022dc95a4ed".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        get_mock.assert_async().await;
+
+        assert_eq!(
+            user.auth_state().get_token().await.unwrap().unwrap(),
+            "user-token"
+        );
+        assert!(matches!(
+            user.auth_state().get_user_or_admin().await.unwrap(),
+            Some(AuthPayload::User(record)) if record.base.id == "8171022dc95a4ed"
+        ));
+        assert_eq!(
+            base.auth_state().get_token().await.unwrap().unwrap(),
+            "admin-token"
+        );
+
+        // the endpoint is missing before v0.23.
+        let result = PocketBase::new(server.url().as_str(), "en")
+            .admin()
+            .impersonate::<AdminAuthResponse, HashMap<String, String>>(&Default::default())
+            .await;
+        assert!(matches!(result, Err(RPocketError::ConfigError(_))));
+    }
 }
@@ -11,9 +11,23 @@ pub enum AuthPayload {
     Admin(Admin),
 }
 
+// SavedAuthResponse is an auth response whose token and record are saved in the auth state.
+pub(crate) trait SavedAuthResponse: Serialize + serde::de::DeserializeOwned {
+    // returns the token and the record to save.
+    fn auth_payload(&self) -> (&str, AuthPayload);
+
+    // converts the response to the type asked by the caller.
+    fn into_response<T>(self) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(serde_json::from_value(serde_json::to_value(self)?)?)
+    }
+}
+
 /// AuthStateService is the service for the auth state.
 pub struct AuthStateService<'a, C> {
-    client: &'a mut C,
+    client: &'a C,
     token_key: &'a str,
     user_or_admin_key: &'a str,
 }
@@ -23,7 +37,7 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new AuthStateService with custom keys.
-    pub fn new(client: &'a mut C, token_key: &'a str, user_or_admin_key: &'a str) -> Self {
+    pub fn new(client: &'a C, token_key: &'a str, user_or_admin_key: &'a str) -> Self {
         AuthStateService {
             client,
             token_key,
@@ -84,8 +98,8 @@ mod test {
 
     #[tokio::test]
     async fn test_auth_storage() {
-        let mut base = PocketBase::new("http://hello.world", "en");
-        let auth_service = AuthStateService::new(&mut base, "foo", "bar");
+        let base = PocketBase::new("http://hello.world", "en");
+        let auth_service = AuthStateService::new(&base, "foo", "bar");
 
         // test clear
         assert!(auth_service.clear().await.is_ok());
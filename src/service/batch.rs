@@ -0,0 +1,619 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    error::{APIError, RPocketError},
+    rpocket::{ApiVersion, PocketBaseClient},
+    service::crud::{CRUDDeleteConfig, CRUDMutateConfig},
+    service::http::RequestOptions,
+};
+
+/// RECORD_BATCH_CONCURRENCY is the default number of operations sent at the same time.
+pub const RECORD_BATCH_CONCURRENCY: usize = 4;
+/// RECORD_BATCH_SIZE is the default number of operations per `/api/batch` request,
+/// it is the default `batch.maxRequests` of the server.
+pub const RECORD_BATCH_SIZE: usize = 50;
+
+/// RecordBatchOperation is an operation of a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum RecordBatchOperation<T> {
+    Create { body: T },
+    Update { id: String, body: T },
+    Delete { id: String },
+}
+
+/// RecordBatchConfig is the config for the batch method.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordBatchConfig<T> {
+    pub operations: Vec<RecordBatchOperation<T>>,
+    /// send the operations to `/api/batch`, one transaction per `batch_size` operations.
+    /// operations are sent one by one with `ApiVersion::V0_14` or if the server doesn't allow it.
+    pub transactional: bool,
+    /// number of operations per `/api/batch` request.
+    pub batch_size: usize,
+    /// number of operations sent at the same time when sent one by one.
+    pub concurrency: usize,
+    /// maximum number of operations started per second when sent one by one.
+    pub rate_limit: Option<u32>,
+    /// skip the operations, or the transactions, that didn't start before the first error.
+    pub stop_on_error: bool,
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+impl<T> Default for RecordBatchConfig<T> {
+    /// create a default RecordBatchConfig.
+    fn default() -> Self {
+        RecordBatchConfig {
+            operations: Vec::new(),
+            transactional: true,
+            batch_size: RECORD_BATCH_SIZE,
+            concurrency: RECORD_BATCH_CONCURRENCY,
+            rate_limit: None,
+            stop_on_error: false,
+            query_params: Vec::new(),
+            options: RequestOptions::default(),
+        }
+    }
+}
+
+/// RecordBatchResult is the result of an operation of a batch.
+#[derive(Debug)]
+pub enum RecordBatchResult<R> {
+    Created(R),
+    Updated(R),
+    Deleted,
+    Failed(RPocketError),
+    /// the operation was not sent after an error, or was rolled back with its transaction.
+    Skipped,
+}
+
+impl<R> RecordBatchResult<R> {
+    /// returns true if the operation failed.
+    pub fn is_failed(&self) -> bool {
+        matches!(self, RecordBatchResult::Failed(_))
+    }
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    status: u16,
+    body: Value,
+}
+
+// runs the operations with `/api/batch` if it is available, one by one otherwise.
+pub(crate) async fn batch<C, T, R>(
+    client: &C,
+    collection: &str,
+    config: &RecordBatchConfig<T>,
+) -> Result<Vec<RecordBatchResult<R>>, RPocketError>
+where
+    C: PocketBaseClient,
+    T: Serialize,
+    R: serde::de::DeserializeOwned,
+{
+    // `/api/batch` was added in `v0.23`.
+    if !config.transactional || client.api_version() == ApiVersion::V0_14 {
+        return Ok(send_each(client, collection, &config.operations, config).await);
+    }
+
+    let mut results = Vec::with_capacity(config.operations.len());
+    let mut failed = false;
+
+    for operations in config.operations.chunks(config.batch_size.max(1)) {
+        if failed && config.stop_on_error {
+            results.extend(operations.iter().map(|_| RecordBatchResult::Skipped));
+            continue;
+        }
+
+        match send_batch(client, collection, operations, config).await {
+            // older servers don't have the endpoint and it can be disabled in the settings.
+            Err(RPocketError::APIError(APIError {
+                code: 403 | 404, ..
+            })) if results.is_empty() => {
+                return Ok(send_each(client, collection, &config.operations, config).await);
+            }
+            Err(RPocketError::APIError(error)) => {
+                failed = true;
+                results.extend(transaction_failure(error, operations.len()));
+            }
+            Err(error) => return Err(error),
+            Ok(responses) if responses.len() != operations.len() => {
+                return Err(RPocketError::Error(
+                    format!(
+                        "batch returned {} responses for {} requests",
+                        responses.len(),
+                        operations.len()
+                    )
+                    .into(),
+                ));
+            }
+            Ok(responses) => {
+                for (operation, response) in operations.iter().zip(responses) {
+                    let result = result(operation, response);
+                    failed |= result.is_failed();
+                    results.push(result);
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+async fn send_batch<C, T>(
+    client: &C,
+    collection: &str,
+    operations: &[RecordBatchOperation<T>],
+    config: &RecordBatchConfig<T>,
+) -> Result<Vec<BatchResponse>, RPocketError>
+where
+    C: PocketBaseClient,
+    T: Serialize,
+{
+    let requests = operations
+        .iter()
+        .map(|operation| {
+            let (method, id, body) = match operation {
+                RecordBatchOperation::Create { body } => ("POST", None, Some(body)),
+                RecordBatchOperation::Update { id, body } => {
+                    ("PATCH", Some(id.as_str()), Some(body))
+                }
+                RecordBatchOperation::Delete { id } => ("DELETE", Some(id.as_str()), None),
+            };
+
+            let mut request = serde_json::json!({
+                "method": method,
+                "url": batch_url(collection, id, &config.query_params)?,
+            });
+            if let Some(body) = body {
+                request["body"] = serde_json::to_value(body)?;
+            }
+            Ok(request)
+        })
+        .collect::<Result<Vec<Value>, RPocketError>>()?;
+
+    let url = client.url(&["api", "batch"])?;
+    let request_builder = client
+        .request_builder(reqwest::Method::POST, url.as_str())
+        .json(&serde_json::json!({ "requests": requests }));
+
+    let response = client
+        .http()
+        .send_with_options(request_builder, &config.options)
+        .await?;
+
+    Ok(response.json::<Vec<BatchResponse>>().await?)
+}
+
+// the url of a batch request is relative to the server root, eg. `/api/collections/posts/records/id`.
+fn batch_url(
+    collection: &str,
+    id: Option<&str>,
+    query_params: &[(String, String)],
+) -> Result<String, RPocketError> {
+    let mut url = url::Url::parse("http://batch/")?;
+    url.path_segments_mut()
+        .map_err(|_| RPocketError::PathError("invalid batch url".to_string()))?
+        .extend(["api", "collections", collection, "records"])
+        .extend(id);
+    if !query_params.is_empty() {
+        url.query_pairs_mut().extend_pairs(query_params);
+    }
+
+    Ok(url[url::Position::BeforePath..].to_string())
+}
+
+fn result<T, R>(
+    operation: &RecordBatchOperation<T>,
+    response: BatchResponse,
+) -> RecordBatchResult<R>
+where
+    R: serde::de::DeserializeOwned,
+{
+    if response.status >= 400 {
+        return RecordBatchResult::Failed(api_error(response.body, response.status.into()));
+    }
+
+    let record = |body: Value| serde_json::from_value(body).map_err(RPocketError::from);
+    match (operation, record(response.body)) {
+        (RecordBatchOperation::Delete { .. }, _) => RecordBatchResult::Deleted,
+        (_, Err(error)) => RecordBatchResult::Failed(error),
+        (RecordBatchOperation::Create { .. }, Ok(record)) => RecordBatchResult::Created(record),
+        (RecordBatchOperation::Update { .. }, Ok(record)) => RecordBatchResult::Updated(record),
+    }
+}
+
+// the failed request of a transaction is in `data.requests.<index>.response`,
+// the other requests were rolled back.
+fn transaction_failure<R>(error: APIError, len: usize) -> Vec<RecordBatchResult<R>> {
+    let failed = error.data["requests"].as_object().and_then(|requests| {
+        requests.iter().find_map(|(index, request)| {
+            let index = index.parse::<usize>().ok().filter(|index| *index < len)?;
+            Some((index, request["response"].clone()))
+        })
+    });
+
+    match failed {
+        Some((index, response)) => (0..len)
+            .map(|i| match i == index {
+                true => RecordBatchResult::Failed(api_error(response.clone(), error.code)),
+                false => RecordBatchResult::Skipped,
+            })
+            .collect(),
+        None => (0..len)
+            .map(|_| {
+                RecordBatchResult::Failed(RPocketError::APIError(APIError {
+                    code: error.code,
+                    message: error.message.clone(),
+                    data: error.data.clone(),
+                }))
+            })
+            .collect(),
+    }
+}
+
+fn api_error(body: Value, status: i64) -> RPocketError {
+    RPocketError::APIError(APIError {
+        code: body["code"].as_i64().unwrap_or(status),
+        message: body["message"].as_str().unwrap_or_default().to_string(),
+        data: body.get("data").cloned().unwrap_or(Value::Null),
+    })
+}
+
+// sends the operations one by one with bounded concurrency and the rate limit.
+async fn send_each<C, T, R>(
+    client: &C,
+    collection: &str,
+    operations: &[RecordBatchOperation<T>],
+    config: &RecordBatchConfig<T>,
+) -> Vec<RecordBatchResult<R>>
+where
+    C: PocketBaseClient,
+    T: Serialize,
+    R: serde::de::DeserializeOwned,
+{
+    let base_path = ["api", "collections", collection, "records"];
+    let crud = &client.crud(&base_path);
+    let limiter = &RateLimiter::new(config.rate_limit);
+    let failed = &AtomicBool::new(false);
+    // a shared request key would cancel the concurrent operations.
+    let options = &RequestOptions {
+        request_key: None,
+        ..config.options.clone()
+    };
+
+    let mut results = futures::stream::iter(operations.iter().enumerate())
+        .map(|(index, operation)| async move {
+            if config.stop_on_error && failed.load(Ordering::SeqCst) {
+                return (index, RecordBatchResult::Skipped);
+            }
+            limiter.wait().await;
+
+            let mutate = |id: Option<&String>, body| CRUDMutateConfig {
+                id: id.cloned(),
+                body,
+                query_params: config.query_params.clone(),
+                options: options.clone(),
+            };
+            let result = match operation {
+                RecordBatchOperation::Create { body } => crud
+                    .mutate(&mutate(None, body))
+                    .await
+                    .map(RecordBatchResult::Created),
+                RecordBatchOperation::Update { id, body } => crud
+                    .mutate(&mutate(Some(id), body))
+                    .await
+                    .map(RecordBatchResult::Updated),
+                RecordBatchOperation::Delete { id } => crud
+                    .delete(&CRUDDeleteConfig {
+                        id: id.clone(),
+                        query_params: config.query_params.clone(),
+                        options: options.clone(),
+                    })
+                    .await
+                    .map(|_| RecordBatchResult::Deleted),
+            };
+
+            let result = result.unwrap_or_else(|error| {
+                failed.store(true, Ordering::SeqCst);
+                RecordBatchResult::Failed(error)
+            });
+            (index, result)
+        })
+        .buffer_unordered(config.concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// RateLimiter spaces the starts of the operations evenly.
+struct RateLimiter {
+    interval: Option<Duration>,
+    next: std::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(per_second: Option<u32>) -> Self {
+        RateLimiter {
+            interval: per_second
+                .filter(|per_second| *per_second > 0)
+                .map(|per_second| Duration::from_secs(1) / per_second),
+            next: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return,
+        };
+
+        let start = {
+            let mut next = self.next.lock().unwrap_or_else(|error| error.into_inner());
+            let start = (*next).max(Instant::now());
+            *next = start + interval;
+            start
+        };
+
+        // a runtime-agnostic timer, the client doesn't need a tokio runtime.
+        futures_timer::Delay::new(start.saturating_duration_since(Instant::now())).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::Record;
+    use crate::rpocket::PocketBaseBuilder;
+    use crate::testing::FakePocketBase;
+    use crate::PocketBase;
+
+    #[test]
+    fn test_batch_url() {
+        assert_eq!(
+            batch_url("posts", None, &[]).unwrap(),
+            "/api/collections/posts/records"
+        );
+        assert_eq!(
+            batch_url(
+                "my posts",
+                Some("abc"),
+                &[("expand".to_string(), "author,tags".to_string())]
+            )
+            .unwrap(),
+            "/api/collections/my%20posts/records/abc?expand=author%2Ctags"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_transaction() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let record = r#"{"id":"a","created":"","updated":"","collectionId":"b","collectionName":"posts","title":"x"}"#;
+        let ok_mock = server
+            .mock("POST", "/api/batch")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "requests": [
+                    {"method": "POST", "url": "/api/collections/posts/records", "body": {"title": "x"}},
+                    {"method": "DELETE", "url": "/api/collections/posts/records/b"},
+                ],
+            })))
+            .with_status(200)
+            .with_body(format!(
+                r#"[{{"status":200,"body":{}}},{{"status":204,"body":null}}]"#,
+                record
+            ))
+            .create_async()
+            .await;
+        let failed_mock = server
+            .mock("POST", "/api/batch")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requests": [{"method": "PATCH", "url": "/api/collections/posts/records/c"}],
+            })))
+            .with_status(400)
+            .with_body(
+                r#"{"code":400,"message":"Batch transaction failed.","data":{"requests":{"1":{"code":"batch_request_failed","message":"Batch request failed.","response":{"code":404,"message":"The requested resource wasn't found.","data":{}}}}}}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBaseBuilder::new()
+            .base_url(url.as_str())
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let results: Vec<RecordBatchResult<Record>> = batch(
+            &base,
+            "posts",
+            &RecordBatchConfig {
+                operations: vec![
+                    RecordBatchOperation::Create {
+                        body: serde_json::json!({"title": "x"}),
+                    },
+                    RecordBatchOperation::Delete {
+                        id: "b".to_string(),
+                    },
+                    RecordBatchOperation::Update {
+                        id: "c".to_string(),
+                        body: serde_json::json!({}),
+                    },
+                    RecordBatchOperation::Delete {
+                        id: "d".to_string(),
+                    },
+                    RecordBatchOperation::Delete {
+                        id: "e".to_string(),
+                    },
+                ],
+                batch_size: 2,
+                stop_on_error: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        ok_mock.assert_async().await;
+        failed_mock.assert_async().await;
+        assert!(matches!(&results[0], RecordBatchResult::Created(record) if record.base.id == "a"));
+        assert!(matches!(results[1], RecordBatchResult::Deleted));
+        assert!(matches!(results[2], RecordBatchResult::Skipped));
+        assert!(matches!(
+            &results[3],
+            RecordBatchResult::Failed(RPocketError::APIError(error)) if error.code == 404
+        ));
+        assert!(matches!(results[4], RecordBatchResult::Skipped));
+    }
+
+    #[tokio::test]
+    async fn test_batch_fallback() {
+        let fake = FakePocketBase::start().await.unwrap();
+        fake.create_collection(serde_json::json!({
+            "name": "posts",
+            "schema": [{"name": "title", "type": "text"}],
+            "createRule": "",
+            "updateRule": "",
+            "deleteRule": "",
+        }))
+        .unwrap();
+        fake.create_record(
+            "posts",
+            serde_json::json!({"id": "post0000000000a", "title": "a"}),
+        )
+        .unwrap();
+
+        let base = PocketBaseBuilder::new()
+            .base_url(fake.url())
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let mut operations: Vec<RecordBatchOperation<Value>> = (0..4)
+            .map(|i| RecordBatchOperation::Create {
+                body: serde_json::json!({ "title": format!("new {}", i) }),
+            })
+            .collect();
+        operations.insert(
+            1,
+            RecordBatchOperation::Update {
+                id: "post0000000000a".to_string(),
+                body: serde_json::json!({"title": "b"}),
+            },
+        );
+        operations.insert(
+            2,
+            RecordBatchOperation::Delete {
+                id: "missing00000000".to_string(),
+            },
+        );
+
+        let started = Instant::now();
+        let results: Vec<RecordBatchResult<Record>> = base
+            .record("posts")
+            .batch(&RecordBatchConfig {
+                operations,
+                concurrency: 2,
+                rate_limit: Some(50),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // 6 operations at 50 per second start over at least 100ms.
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(results.len(), 6);
+        assert!(
+            matches!(&results[0], RecordBatchResult::Created(record) if record.data["title"] == "new 0")
+        );
+        assert!(
+            matches!(&results[1], RecordBatchResult::Updated(record) if record.data["title"] == "b")
+        );
+        assert!(matches!(
+            &results[2],
+            RecordBatchResult::Failed(RPocketError::APIError(error)) if error.code == 404
+        ));
+        assert!(
+            matches!(&results[5], RecordBatchResult::Created(record) if record.data["title"] == "new 3")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_v0_14() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let batch_mock = server
+            .mock("POST", "/api/batch")
+            .expect(0)
+            .create_async()
+            .await;
+        let delete_mock = server
+            .mock("DELETE", "/api/collections/posts/records/a")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let results: Vec<RecordBatchResult<Record>> = batch(
+            &base,
+            "posts",
+            &RecordBatchConfig::<Value> {
+                operations: vec![RecordBatchOperation::Delete {
+                    id: "a".to_string(),
+                }],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        batch_mock.assert_async().await;
+        delete_mock.assert_async().await;
+        assert!(matches!(results[0], RecordBatchResult::Deleted));
+    }
+
+    #[tokio::test]
+    async fn test_batch_missing_responses() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        server
+            .mock("POST", "/api/batch")
+            .with_status(200)
+            .with_body(r#"[{"status":204,"body":null}]"#)
+            .create_async()
+            .await;
+
+        let base = PocketBaseBuilder::new()
+            .base_url(url.as_str())
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let result = batch::<_, Value, Record>(
+            &base,
+            "posts",
+            &RecordBatchConfig {
+                operations: vec![
+                    RecordBatchOperation::Delete {
+                        id: "a".to_string(),
+                    },
+                    RecordBatchOperation::Delete {
+                        id: "b".to_string(),
+                    },
+                ],
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(RPocketError::Error(_))));
+    }
+}
@@ -0,0 +1,986 @@
+use std::collections::HashMap;
+
+use futures::{AsyncWrite, AsyncWriteExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    error::RPocketError,
+    model::{self, Collection, ListResult, Record, SchemaField},
+    rpocket::PocketBaseClient,
+    service::crud::{CRUDGetListConfig, CRUDGetOneConfig, CRUDMutateConfig},
+    service::http::RequestOptions,
+    service::record::{RecordService, RecordUpsertConfig},
+};
+
+/// RECORD_IMPORT_CONCURRENCY is the default number of records written at the same time.
+pub const RECORD_IMPORT_CONCURRENCY: usize = 4;
+
+/// RECORD_EXPORT_PER_PAGE is the default number of records fetched per page when exporting.
+pub const RECORD_EXPORT_PER_PAGE: i64 = 500;
+
+/// RecordImportConfig is the config for importing records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordImportConfig {
+    /// maps source columns to schema fields, the other columns keep their name.
+    pub columns: HashMap<String, String>,
+    /// field used to find the record to update, records are only created if `None`.
+    pub upsert_by: Option<String>,
+    /// number of records written at the same time.
+    pub concurrency: usize,
+    /// separator of the multi-value cells, eg. `a,b` for a select with `maxSelect` > 1.
+    pub list_separator: char,
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+impl Default for RecordImportConfig {
+    /// create a default RecordImportConfig.
+    fn default() -> Self {
+        RecordImportConfig {
+            columns: HashMap::new(),
+            upsert_by: None,
+            concurrency: RECORD_IMPORT_CONCURRENCY,
+            list_separator: ',',
+            query_params: Vec::new(),
+            options: RequestOptions::default(),
+        }
+    }
+}
+
+/// RecordImportFailure is a row that could not be imported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordImportFailure {
+    /// 1-based index of the row, the CSV header is not counted.
+    pub row: usize,
+    pub message: String,
+    /// the `data` of the API error, eg. the validation errors of each field.
+    pub details: Value,
+}
+
+/// RecordImportReport is the result of an import.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordImportReport {
+    pub created: usize,
+    pub updated: usize,
+    /// failures sorted by row.
+    pub failures: Vec<RecordImportFailure>,
+}
+
+// RecordImportRow is a parsed row or the reason it couldn't be parsed.
+pub(crate) type RecordImportRow = Result<Map<String, Value>, String>;
+
+enum Imported {
+    Created,
+    Updated,
+}
+
+// reads one json object per line, blank lines are skipped.
+pub(crate) fn ndjson_rows<R>(reader: R) -> impl Iterator<Item = RecordImportRow>
+where
+    R: std::io::BufRead,
+{
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(
+            |line| match serde_json::from_str(&line.map_err(|error| error.to_string())?) {
+                Ok(Value::Object(row)) => Ok(row),
+                Ok(_) => Err("row is not a json object".to_string()),
+                Err(error) => Err(error.to_string()),
+            },
+        )
+}
+
+// reads the rows of a CSV with a header, every cell is a string.
+#[cfg(feature = "csv")]
+pub(crate) fn csv_rows<R>(reader: R) -> Result<impl Iterator<Item = RecordImportRow>, RPocketError>
+where
+    R: std::io::Read,
+{
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader
+        .headers()
+        .map_err(|error| RPocketError::Error(Box::new(error)))?
+        .clone();
+
+    Ok(reader.into_records().map(move |record| {
+        let record = record.map_err(|error| error.to_string())?;
+        Ok(headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, cell)| (header.to_string(), Value::String(cell.to_string())))
+            .collect())
+    }))
+}
+
+// coerces the row to the collection schema and writes the records with bounded concurrency.
+pub(crate) async fn import_rows<C, I>(
+    client: &C,
+    collection: &str,
+    rows: I,
+    config: &RecordImportConfig,
+) -> Result<RecordImportReport, RPocketError>
+where
+    C: PocketBaseClient,
+    I: Iterator<Item = RecordImportRow>,
+{
+    let collection: Collection = client
+        .collection()
+        .crud()
+        .get_one(&CRUDGetOneConfig {
+            id: collection.to_string(),
+            options: config.options.clone(),
+            ..Default::default()
+        })
+        .await?;
+
+    let records = &RecordService::new(client, collection.name.as_str());
+    let collection = &collection;
+
+    // the results are counted as they arrive, only the failures are kept.
+    let mut report = futures::stream::iter(rows.enumerate())
+        .map(|(index, row)| async move {
+            let row = row.and_then(|row| coerce_row(row, &collection.schema, config));
+            let result = match row {
+                Ok(row) => import_row(records, row, config).await,
+                Err(message) => Err(RecordImportFailure {
+                    message,
+                    details: Value::Null,
+                    ..Default::default()
+                }),
+            };
+            (index + 1, result)
+        })
+        .buffer_unordered(config.concurrency.max(1))
+        .fold(
+            RecordImportReport::default(),
+            |mut report, (row, result)| {
+                match result {
+                    Ok(Imported::Created) => report.created += 1,
+                    Ok(Imported::Updated) => report.updated += 1,
+                    Err(failure) => report.failures.push(RecordImportFailure { row, ..failure }),
+                }
+                futures::future::ready(report)
+            },
+        )
+        .await;
+    report.failures.sort_by_key(|failure| failure.row);
+
+    Ok(report)
+}
+
+async fn import_row<C>(
+    records: &RecordService<'_, C>,
+    row: Map<String, Value>,
+    config: &RecordImportConfig,
+) -> Result<Imported, RecordImportFailure>
+where
+    C: PocketBaseClient,
+{
+    // rows without a value for the upsert field are always created.
+    let upsert_by = config
+        .upsert_by
+        .as_deref()
+        .filter(|field| row.get(*field).is_some_and(|value| !value.is_null()));
+
+    let created = match upsert_by {
+        Some(field) => {
+            records
+                .upsert_by::<Record, _>(&RecordUpsertConfig {
+                    fields: vec![field.to_string()],
+                    body: row,
+                    query_params: config.query_params.clone(),
+                    options: config.options.clone(),
+                    ..Default::default()
+                })
+                .await
+                .map_err(failure)?
+                .created
+        }
+        None => {
+            records
+                .crud()
+                .mutate::<Record, _>(&CRUDMutateConfig {
+                    id: None,
+                    body: row,
+                    query_params: config.query_params.clone(),
+                    options: config.options.clone(),
+                })
+                .await
+                .map_err(failure)?;
+            true
+        }
+    };
+
+    match created {
+        true => Ok(Imported::Created),
+        false => Ok(Imported::Updated),
+    }
+}
+
+fn failure(error: RPocketError) -> RecordImportFailure {
+    match error {
+        RPocketError::APIError(error) => RecordImportFailure {
+            message: error.message,
+            details: error.data,
+            ..Default::default()
+        },
+        error => RecordImportFailure {
+            message: error.to_string(),
+            details: Value::Null,
+            ..Default::default()
+        },
+    }
+}
+
+fn coerce_row(
+    row: Map<String, Value>,
+    schema: &[SchemaField],
+    config: &RecordImportConfig,
+) -> Result<Map<String, Value>, String> {
+    let mut coerced = Map::with_capacity(row.len());
+
+    for (column, value) in row {
+        let name = config.columns.get(&column).cloned().unwrap_or(column);
+        let value = match schema.iter().find(|field| field.name == name) {
+            Some(field) => coerce(value, field, config.list_separator)
+                .map_err(|message| format!("{}: {}", name, message))?,
+            None => value,
+        };
+        coerced.insert(name, value);
+    }
+
+    Ok(coerced)
+}
+
+// converts a string to the type of the field, other values are kept.
+fn coerce(value: Value, field: &SchemaField, separator: char) -> Result<Value, String> {
+    let text = match value {
+        Value::String(text) => text,
+        value => return Ok(value),
+    };
+    let trimmed = text.trim();
+    // a relation without `maxSelect` has no limit.
+    let is_multiple = match field.options.get("maxSelect") {
+        Some(Value::Null) => field.field_type == "relation",
+        Some(max_select) => max_select.as_i64().is_some_and(|max_select| max_select > 1),
+        None => false,
+    };
+
+    match field.field_type.as_str() {
+        "number" if trimmed.is_empty() => Ok(Value::Null),
+        "number" => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| format!("invalid number {:?}", text)),
+        "bool" => match trimmed.to_lowercase().as_str() {
+            "true" | "1" | "yes" | "y" | "on" => Ok(Value::Bool(true)),
+            "false" | "0" | "no" | "n" | "off" | "" => Ok(Value::Bool(false)),
+            _ => Err(format!("invalid bool {:?}", text)),
+        },
+        "date" if trimmed.is_empty() => Ok(Value::String(String::new())),
+        "date" => parse_date(trimmed)
+            .map(|date| Value::String(model::format_date(&date)))
+            .ok_or_else(|| format!("invalid date {:?}", text)),
+        "json" if trimmed.is_empty() => Ok(Value::Null),
+        "json" => serde_json::from_str(trimmed).map_err(|_| format!("invalid json {:?}", text)),
+        "select" | "relation" | "file" if is_multiple => Ok(Value::Array(
+            trimmed
+                .split(separator)
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        )),
+        _ => Ok(Value::String(text)),
+    }
+}
+
+// parses PocketBase and RFC 3339 dates, or a day like `2023-04-01`.
+fn parse_date(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    model::parse_date(value).ok().or_else(|| {
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+            .map(|date| date.and_utc())
+    })
+}
+
+/// RecordExportFormat is the output format of an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordExportFormat {
+    /// one json object per line.
+    #[default]
+    Ndjson,
+    /// a json array of records.
+    Json,
+    /// a CSV with a header row, nested values are flattened to dotted columns,
+    /// eg. `expand.author.name`.
+    #[cfg(feature = "csv")]
+    Csv,
+}
+
+/// RecordExportConfig is the config for exporting records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordExportConfig {
+    pub format: RecordExportFormat,
+    pub filter: Option<String>,
+    pub expand: Option<String>,
+    pub sort: Option<String>,
+    /// CSV columns, the columns of the first page are used if empty.
+    pub columns: Vec<String>,
+    /// number of records fetched per page, must be positive and may be capped by the server.
+    pub per_page: i64,
+    /// separator of the list values in CSV cells, eg. `a,b` for `["a", "b"]`.
+    pub list_separator: char,
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+impl Default for RecordExportConfig {
+    /// create a default RecordExportConfig.
+    fn default() -> Self {
+        RecordExportConfig {
+            format: RecordExportFormat::default(),
+            filter: None,
+            expand: None,
+            sort: None,
+            columns: Vec::new(),
+            per_page: RECORD_EXPORT_PER_PAGE,
+            list_separator: ',',
+            query_params: Vec::new(),
+            options: RequestOptions::default(),
+        }
+    }
+}
+
+// Encoder turns pages of records into bytes of the export format.
+struct Encoder<'a> {
+    config: &'a RecordExportConfig,
+    #[cfg(feature = "csv")]
+    columns: Vec<String>,
+    count: usize,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(config: &'a RecordExportConfig) -> Self {
+        Encoder {
+            config,
+            #[cfg(feature = "csv")]
+            columns: config.columns.clone(),
+            count: 0,
+        }
+    }
+
+    fn page(&mut self, items: &[Value]) -> Result<Vec<u8>, RPocketError> {
+        let mut buffer = Vec::new();
+
+        match self.config.format {
+            RecordExportFormat::Ndjson => {
+                for item in items {
+                    serde_json::to_writer(&mut buffer, item)?;
+                    buffer.push(b'\n');
+                }
+            }
+            RecordExportFormat::Json => {
+                for (index, item) in items.iter().enumerate() {
+                    let prefix: &[u8] = match self.count + index {
+                        0 => b"[\n",
+                        _ => b",\n",
+                    };
+                    buffer.extend_from_slice(prefix);
+                    serde_json::to_writer(&mut buffer, item)?;
+                }
+            }
+            #[cfg(feature = "csv")]
+            RecordExportFormat::Csv => {
+                let rows: Vec<Vec<(String, String)>> = items
+                    .iter()
+                    .map(|item| {
+                        let mut cells = Vec::new();
+                        flatten("", item, self.config.list_separator, &mut cells);
+                        cells
+                    })
+                    .collect();
+
+                if self.count == 0 && self.columns.is_empty() {
+                    for (column, _) in rows.iter().flatten() {
+                        if !self.columns.contains(column) {
+                            self.columns.push(column.clone());
+                        }
+                    }
+                }
+
+                let mut writer = csv::Writer::from_writer(&mut buffer);
+                if self.count == 0 && !rows.is_empty() {
+                    writer.write_record(&self.columns).map_err(csv_error)?;
+                }
+                for cells in rows {
+                    let cells: HashMap<String, String> = cells.into_iter().collect();
+                    writer
+                        .write_record(
+                            self.columns
+                                .iter()
+                                .map(|column| cells.get(column).map_or("", String::as_str)),
+                        )
+                        .map_err(csv_error)?;
+                }
+                writer.flush()?;
+            }
+        }
+
+        self.count += items.len();
+        Ok(buffer)
+    }
+
+    fn finish(&self) -> Result<Vec<u8>, RPocketError> {
+        let mut buffer = Vec::new();
+
+        match self.config.format {
+            RecordExportFormat::Ndjson => {}
+            RecordExportFormat::Json => match self.count {
+                0 => buffer.extend_from_slice(b"[]\n"),
+                _ => buffer.extend_from_slice(b"\n]\n"),
+            },
+            // an empty export still has the header if the columns are known.
+            #[cfg(feature = "csv")]
+            RecordExportFormat::Csv if self.count == 0 && !self.columns.is_empty() => {
+                let mut writer = csv::Writer::from_writer(&mut buffer);
+                writer.write_record(&self.columns).map_err(csv_error)?;
+                writer.flush()?;
+            }
+            #[cfg(feature = "csv")]
+            RecordExportFormat::Csv => {}
+        }
+
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "csv")]
+fn csv_error(error: csv::Error) -> RPocketError {
+    RPocketError::Error(Box::new(error))
+}
+
+// flattens objects to dotted columns, lists of scalars are joined with the separator
+// and other lists are kept as json.
+#[cfg(feature = "csv")]
+fn flatten(prefix: &str, value: &Value, separator: char, cells: &mut Vec<(String, String)>) {
+    let scalar = |value: &Value| match value {
+        Value::Null => Some(String::new()),
+        Value::String(value) => Some(value.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        _ => None,
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = match prefix.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", prefix, key),
+                };
+                flatten(&path, value, separator, cells);
+            }
+        }
+        Value::Array(items) => {
+            let cell = items
+                .iter()
+                .map(scalar)
+                .collect::<Option<Vec<_>>>()
+                .map(|items| items.join(&separator.to_string()))
+                .unwrap_or_else(|| value.to_string());
+            cells.push((prefix.to_string(), cell));
+        }
+        value => cells.push((prefix.to_string(), scalar(value).unwrap_or_default())),
+    }
+}
+
+// fetches one page of records with the export filter, sort and expand,
+// returns the records and whether there may be a next page.
+async fn export_page<C>(
+    client: &C,
+    collection: &str,
+    page: i64,
+    config: &RecordExportConfig,
+) -> Result<(Vec<Value>, bool), RPocketError>
+where
+    C: PocketBaseClient,
+{
+    let mut query_params: Vec<(String, String)> = [
+        ("filter", &config.filter),
+        ("sort", &config.sort),
+        ("expand", &config.expand),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+    .collect();
+    query_params.push(("skipTotal".to_string(), "1".to_string()));
+    query_params.extend(config.query_params.iter().cloned());
+
+    let list: ListResult<Value> = client
+        .crud(&["api", "collections", collection, "records"])
+        .get_list(&CRUDGetListConfig {
+            per_page: config.per_page,
+            page,
+            query_params,
+            options: config.options.clone(),
+        })
+        .await?;
+
+    // the server caps perPage, so a full page is compared to the returned perPage.
+    let next = !list.items.is_empty() && list.items.len() as i64 >= list.per_page;
+    Ok((list.items, next))
+}
+
+fn validate_export_config(config: &RecordExportConfig) -> Result<(), RPocketError> {
+    if config.per_page <= 0 {
+        return Err(RPocketError::ConfigError(
+            "export per_page must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// pages through the collection and writes every page before fetching the next one.
+pub(crate) async fn export_records<C, W>(
+    client: &C,
+    collection: &str,
+    mut writer: W,
+    config: &RecordExportConfig,
+) -> Result<usize, RPocketError>
+where
+    C: PocketBaseClient,
+    W: std::io::Write,
+{
+    validate_export_config(config)?;
+    let mut encoder = Encoder::new(config);
+
+    for page in 1.. {
+        let (items, next) = export_page(client, collection, page, config).await?;
+        writer.write_all(&encoder.page(&items)?)?;
+        if !next {
+            break;
+        }
+    }
+
+    writer.write_all(&encoder.finish()?)?;
+    writer.flush()?;
+    Ok(encoder.count)
+}
+
+// same as `export_records` for an async writer.
+pub(crate) async fn export_records_async<C, W>(
+    client: &C,
+    collection: &str,
+    mut writer: W,
+    config: &RecordExportConfig,
+) -> Result<usize, RPocketError>
+where
+    C: PocketBaseClient,
+    W: AsyncWrite + Unpin,
+{
+    validate_export_config(config)?;
+    let mut encoder = Encoder::new(config);
+
+    for page in 1.. {
+        let (items, next) = export_page(client, collection, page, config).await?;
+        writer.write_all(&encoder.page(&items)?).await?;
+        if !next {
+            break;
+        }
+    }
+
+    writer.write_all(&encoder.finish()?).await?;
+    writer.flush().await?;
+    Ok(encoder.count)
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::FakePocketBase;
+    use crate::PocketBase;
+
+    async fn posts(fake: &FakePocketBase) -> PocketBase<crate::rpocket::PocketBaseService> {
+        fake.create_admin("admin@example.com", "password123")
+            .unwrap();
+        fake.create_collection(serde_json::json!({
+            "name": "posts",
+            "schema": [
+                {"name": "title", "type": "text"},
+                {"name": "views", "type": "number"},
+                {"name": "draft", "type": "bool"},
+                {"name": "published", "type": "date"},
+                {"name": "meta", "type": "json"},
+                {"name": "tags", "type": "select", "options": {"maxSelect": 3}},
+            ],
+        }))
+        .unwrap();
+
+        let base = PocketBase::new(fake.url(), "en");
+        base.admin()
+            .auth_with_password::<crate::service::admin::AdminAuthResponse, HashMap<String, String>>(
+                &crate::service::admin::AdminAuthWithPasswordConfig {
+                    identity: "admin@example.com".to_string(),
+                    password: "password123".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        base
+    }
+
+    #[test]
+    fn test_coerce() {
+        let field = |field_type: &str, options: Value| SchemaField {
+            field_type: field_type.to_string(),
+            options: serde_json::from_value(options).unwrap(),
+            ..Default::default()
+        };
+        let coerce = |value: &str, field: &SchemaField| coerce(Value::from(value), field, '|');
+
+        let number = field("number", serde_json::json!({}));
+        assert_eq!(coerce(" 12.5 ", &number), Ok(serde_json::json!(12.5)));
+        assert_eq!(coerce("", &number), Ok(Value::Null));
+        assert!(coerce("twelve", &number).is_err());
+
+        let bool = field("bool", serde_json::json!({}));
+        assert_eq!(coerce("Yes", &bool), Ok(Value::Bool(true)));
+        assert_eq!(coerce("0", &bool), Ok(Value::Bool(false)));
+
+        let date = field("date", serde_json::json!({}));
+        assert_eq!(
+            coerce("2023-04-01T10:00:00Z", &date),
+            Ok(Value::from("2023-04-01 10:00:00.000Z"))
+        );
+
+        let json = field("json", serde_json::json!({}));
+        assert_eq!(
+            coerce("{\"a\": [1]}", &json),
+            Ok(serde_json::json!({"a": [1]}))
+        );
+
+        let tags = field("select", serde_json::json!({"maxSelect": 3}));
+        assert_eq!(coerce("a| b|", &tags), Ok(serde_json::json!(["a", "b"])));
+        let tag = field("select", serde_json::json!({"maxSelect": 1}));
+        assert_eq!(coerce("a|b", &tag), Ok(Value::from("a|b")));
+
+        assert_eq!(
+            super::coerce(serde_json::json!(3), &number, ','),
+            Ok(serde_json::json!(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_ndjson() {
+        let fake = FakePocketBase::start().await.unwrap();
+        let base = posts(&fake).await;
+
+        let data = concat!(
+            "{\"id\": \"post000000000a1\", \"name\": \"a\", \"views\": \"3\", \"tags\": \"x,y\"}\n",
+            "\n",
+            "{\"id\": \"post000000000a1\", \"name\": \"b\"}\n",
+            "not json\n",
+            "{\"name\": \"c\", \"views\": \"many\"}\n",
+        );
+
+        let report = base
+            .record("posts")
+            .import_ndjson(
+                data.as_bytes(),
+                &RecordImportConfig {
+                    columns: HashMap::from([("name".to_string(), "title".to_string())]),
+                    concurrency: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.created, 1);
+        assert_eq!(report.updated, 0);
+        assert_eq!(
+            report
+                .failures
+                .iter()
+                .map(|failure| (failure.row, failure.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, "Failed to create record."),
+                (3, "expected ident at line 1 column 2"),
+                (4, "views: invalid number \"many\""),
+            ]
+        );
+
+        let record: Record = base
+            .record("posts")
+            .crud()
+            .get_one(&CRUDGetOneConfig {
+                id: "post000000000a1".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(record.data["title"], "a");
+        assert_eq!(record.data["views"], 3.0);
+        assert_eq!(record.data["tags"], serde_json::json!(["x", "y"]));
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_upsert() {
+        let fake = FakePocketBase::start().await.unwrap();
+        let base = posts(&fake).await;
+        fake.create_record("posts", serde_json::json!({"title": "a", "views": 1}))
+            .unwrap();
+
+        let data =
+            "title,views,draft,published,meta\na,5,yes,2023-04-01,\"{\"\"k\"\": 1}\"\nb,2,no,,\n";
+
+        let report = base
+            .record("posts")
+            .import_csv(
+                data.as_bytes(),
+                &RecordImportConfig {
+                    upsert_by: Some("title".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            RecordImportReport {
+                created: 1,
+                updated: 1,
+                failures: Vec::new(),
+            }
+        );
+
+        let list: ListResult<Record> = base
+            .record("posts")
+            .crud()
+            .get_list(&CRUDGetListConfig {
+                query_params: vec![("sort".to_string(), "title".to_string())],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(list.total_items, 2);
+        assert_eq!(list.items[0].data["views"], 5.0);
+        assert_eq!(list.items[0].data["draft"], true);
+        assert_eq!(list.items[0].data["published"], "2023-04-01 00:00:00.000Z");
+        assert_eq!(list.items[0].data["meta"], serde_json::json!({"k": 1}));
+        assert_eq!(list.items[1].data["published"], "");
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let fake = FakePocketBase::start().await.unwrap();
+        let base = posts(&fake).await;
+        for (title, views) in [("a", 1), ("b", 2), ("c", 3)] {
+            fake.create_record(
+                "posts",
+                serde_json::json!({
+                    "id": format!("post00000000{}00", title),
+                    "title": title,
+                    "views": views,
+                    "meta": {"author": {"name": title.to_uppercase()}},
+                    "tags": ["x", "y"],
+                }),
+            )
+            .unwrap();
+        }
+
+        let mut out = Vec::new();
+        let count = base
+            .record("posts")
+            .export_to(
+                &mut out,
+                &RecordExportConfig {
+                    filter: Some("views >= 2".to_string()),
+                    sort: Some("-views".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let titles: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["title"].clone())
+            .collect();
+        assert_eq!(count, 2);
+        assert_eq!(titles, vec!["c", "b"]);
+
+        let mut out = Vec::new();
+        let count = base
+            .record("posts")
+            .export_to_async(
+                &mut out,
+                &RecordExportConfig {
+                    format: RecordExportFormat::Json,
+                    sort: Some("title".to_string()),
+                    per_page: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let records: Vec<Record> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.data["title"].clone())
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+
+        let mut out = Vec::new();
+        base.record("posts")
+            .export_to(
+                &mut out,
+                &RecordExportConfig {
+                    format: RecordExportFormat::Csv,
+                    sort: Some("title".to_string()),
+                    columns: vec![
+                        "id".to_string(),
+                        "meta.author.name".to_string(),
+                        "tags".to_string(),
+                        "missing".to_string(),
+                    ],
+                    list_separator: '|',
+                    per_page: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "id,meta.author.name,tags,missing\n",
+                "post00000000a00,A,x|y,\n",
+                "post00000000b00,B,x|y,\n",
+                "post00000000c00,C,x|y,\n",
+            )
+        );
+
+        let mut out = Vec::new();
+        let count = base
+            .record("posts")
+            .export_to(
+                &mut out,
+                &RecordExportConfig {
+                    format: RecordExportFormat::Json,
+                    filter: Some("views > 10".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[tokio::test]
+    async fn test_export_capped_per_page() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let record =
+            r#"{"id":"a","created":"","updated":"","collectionId":"b","collectionName":"posts"}"#;
+
+        // the server returns at most 2 records per page whatever perPage is asked.
+        let mut mocks = Vec::new();
+        for (page, items) in [(1, 2), (2, 2), (3, 0)] {
+            let items = vec![record; items].join(",");
+            mocks.push(
+                server
+                    .mock("GET", "/api/collections/posts/records")
+                    .match_query(mockito::Matcher::UrlEncoded(
+                        "page".into(),
+                        page.to_string(),
+                    ))
+                    .with_status(200)
+                    .with_body(format!(
+                        r#"{{"page":{},"perPage":2,"totalItems":-1,"totalPages":-1,"items":[{}]}}"#,
+                        page, items
+                    ))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let base = crate::rpocket::PocketBase::new(url.as_str(), "en");
+        let mut out = Vec::new();
+        let count = base
+            .record("posts")
+            .export_to(
+                &mut out,
+                &RecordExportConfig {
+                    per_page: 1000,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        assert_eq!(count, 4);
+
+        let error = base
+            .record("posts")
+            .export_to(
+                &mut Vec::new(),
+                &RecordExportConfig {
+                    per_page: 0,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RPocketError::ConfigError(_)));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut cells = Vec::new();
+        flatten(
+            "",
+            &serde_json::json!({
+                "id": "a",
+                "draft": false,
+                "expand": {"author": {"name": "x", "roles": [{"id": 1}]}},
+                "tags": [],
+                "meta": null,
+            }),
+            ',',
+            &mut cells,
+        );
+
+        assert_eq!(
+            cells,
+            vec![
+                ("draft".to_string(), "false".to_string()),
+                ("expand.author.name".to_string(), "x".to_string()),
+                (
+                    "expand.author.roles".to_string(),
+                    "[{\"id\":1}]".to_string()
+                ),
+                ("id".to_string(), "a".to_string()),
+                ("meta".to_string(), "".to_string()),
+                ("tags".to_string(), "".to_string()),
+            ]
+        );
+    }
+}
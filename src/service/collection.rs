@@ -1,6 +1,18 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{error::RPocketError, model::Collection, service};
+use crate::{
+    error::RPocketError,
+    model::{Collection, ListResult},
+    rpocket::ApiVersion,
+    service,
+    service::{http::RequestOptions, request::ApiRequest},
+};
+
+/// COLLECTION_EXPORT_PER_PAGE is the page size used while exporting collections.
+pub const COLLECTION_EXPORT_PER_PAGE: i64 = 200;
+
+// fields that change on every save and would make the exported snapshot noisy.
+const COLLECTION_VOLATILE_FIELDS: [&str; 2] = ["created", "updated"];
 
 /// CollectionImportConfig is the config for importing collections.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -12,12 +24,154 @@ pub struct CollectionImportConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+impl<T> CollectionImportConfig<T>
+where
+    T: Default,
+{
+    /// create a new CollectionImportConfig from an exported collections file.
+    pub fn from_file<P>(path: P) -> Result<Self, RPocketError>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        Ok(CollectionImportConfig {
+            collections: read_collections_file(path)?,
+            ..Default::default()
+        })
+    }
+}
+
+impl<T> CollectionImportConfig<T>
+where
+    T: Serialize,
+{
+    // returns the request body with the collections in the format of the API version.
+    pub(crate) fn body(&self, api_version: ApiVersion) -> Result<serde_json::Value, RPocketError> {
+        let mut body = serde_json::to_value(self)?;
+        body["collections"] = self
+            .collections
+            .iter()
+            .map(|collection| collection.to_api_value(api_version))
+            .collect::<Result<_, _>>()?;
+        Ok(body)
+    }
+}
+
+/// CollectionExportConfig is the config for exporting collections.
+/// it is only read locally, so it is not serializable.
+#[derive(Debug, Clone, Default)]
+pub struct CollectionExportConfig {
+    pub path: std::path::PathBuf,
+    pub query_params: Vec<(String, String)>,
+    pub options: RequestOptions,
+}
+
+/// serializes the collections into the admin UI import format.
+/// volatile fields are stripped and collections are sorted by name.
+pub fn export_collections(collections: &[Collection]) -> Result<String, RPocketError> {
+    let mut collections = collections.iter().collect::<Vec<_>>();
+    collections.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.base.id.cmp(&b.base.id)));
+
+    let mut values = Vec::with_capacity(collections.len());
+    for collection in collections {
+        let mut value = serde_json::to_value(collection)?;
+        if let serde_json::Value::Object(ref mut object) = value {
+            for field in COLLECTION_VOLATILE_FIELDS {
+                object.remove(field);
+            }
+        }
+        values.push(value);
+    }
+
+    let mut data = serde_json::to_string_pretty(&values)?;
+    data.push('\n');
+
+    Ok(data)
+}
+
+/// parses collections from the admin UI import format.
+pub fn import_collections(data: &str) -> Result<Vec<Collection>, RPocketError> {
+    Ok(serde_json::from_str::<Vec<Collection>>(data)?)
+}
+
+/// writes the collections into a file using the admin UI import format.
+pub fn write_collections_file<P>(path: P, collections: &[Collection]) -> Result<(), RPocketError>
+where
+    P: AsRef<std::path::Path>,
+{
+    std::fs::write(path, export_collections(collections)?)?;
+    Ok(())
+}
+
+/// reads collections from a file using the admin UI import format.
+pub fn read_collections_file<P>(path: P) -> Result<Vec<Collection>, RPocketError>
+where
+    P: AsRef<std::path::Path>,
+{
+    import_collections(&std::fs::read_to_string(path)?)
+}
+
+// builds the import request, the collections are converted to the `v0.23` format if needed.
+pub(crate) fn import_request<B>(
+    api_version: ApiVersion,
+    config: &CollectionImportConfig<B>,
+) -> Result<ApiRequest<'_>, RPocketError>
+where
+    B: Serialize,
+{
+    let request = ApiRequest::new(
+        reqwest::Method::PUT,
+        &["api", "collections", "import"],
+        &config.options,
+    )
+    .query(&config.query_params);
+
+    match api_version {
+        ApiVersion::V0_14 => request.json(config),
+        api_version => request.json(&config.body(api_version)?),
+    }
+}
+
+// CollectionPager collects the pages of the collections list.
+pub(crate) struct CollectionPager {
+    pub(crate) config: service::crud::CRUDGetListConfig,
+    pub(crate) collections: Vec<Collection>,
+}
+
+impl CollectionPager {
+    pub(crate) fn new(query_params: &[(String, String)], options: &RequestOptions) -> Self {
+        CollectionPager {
+            config: service::crud::CRUDGetListConfig {
+                per_page: COLLECTION_EXPORT_PER_PAGE,
+                query_params: query_params.to_vec(),
+                options: options.clone(),
+                ..Default::default()
+            },
+            collections: Vec::new(),
+        }
+    }
+
+    // adds a fetched page, returns true if there is a next page to fetch.
+    pub(crate) fn push(&mut self, result: ListResult<Collection>) -> bool {
+        let fetched = result.items.len() as i64;
+        self.collections.extend(result.items);
+
+        if fetched < self.config.per_page || self.collections.len() as i64 >= result.total_items {
+            return false;
+        }
+
+        self.config.page += 1;
+        true
+    }
 }
 
 /// CollectionService is the service for collections.
 pub struct CollectionService<'a, C> {
-    client: &'a mut C,
-    collection_base_path: String,
+    client: &'a C,
+    collection_base_path: [&'static str; 2],
 }
 
 impl<'a, C> CollectionService<'a, C>
@@ -25,41 +179,67 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new CollectionService.
-    pub fn new(client: &'a mut C) -> Self {
+    pub fn new(client: &'a C) -> Self {
         CollectionService {
             client,
-            collection_base_path: "api/collections".to_string(),
+            collection_base_path: ["api", "collections"],
         }
     }
 
     /// returns crud service.
-    pub fn crud(&'a mut self) -> service::crud::CRUDService<'a, C> {
+    pub fn crud(&self) -> service::crud::CRUDService<'_, C> {
         self.client.crud(&self.collection_base_path)
     }
 
     /// imports the provided collections.
-    pub async fn import<B>(
-        &'a mut self,
-        config: &CollectionImportConfig<B>,
-    ) -> Result<(), RPocketError>
+    pub async fn import<B>(&self, config: &CollectionImportConfig<B>) -> Result<(), RPocketError>
     where
         B: serde::Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/import", self.collection_base_path).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::PUT, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        let request = import_request(self.client.api_version(), config)?;
+        self.client.http().execute(&request).await?;
         Ok(())
     }
+
+    /// returns all collections, fetching every page.
+    pub async fn get_full_list(
+        &self,
+        query_params: &[(String, String)],
+    ) -> Result<Vec<Collection>, RPocketError> {
+        self.get_full_list_with_options(query_params, &RequestOptions::default())
+            .await
+    }
+
+    /// returns all collections with the given request options, fetching every page.
+    pub async fn get_full_list_with_options(
+        &self,
+        query_params: &[(String, String)],
+        options: &RequestOptions,
+    ) -> Result<Vec<Collection>, RPocketError> {
+        let mut pager = CollectionPager::new(query_params, options);
+
+        loop {
+            let result = self.crud().get_list::<Collection>(&pager.config).await?;
+            if !pager.push(result) {
+                return Ok(pager.collections);
+            }
+        }
+    }
+
+    /// exports all collections into the file at config.path.
+    /// the file can be loaded back with `CollectionImportConfig::from_file`.
+    pub async fn export(
+        &self,
+        config: &CollectionExportConfig,
+    ) -> Result<Vec<Collection>, RPocketError> {
+        let collections = self
+            .get_full_list_with_options(&config.query_params, &config.options)
+            .await?;
+
+        write_collections_file(&config.path, &collections)?;
+
+        Ok(collections)
+    }
 }
 
 #[cfg(test)]
@@ -72,15 +252,15 @@ mod test {
 
     #[test]
     fn test_collection_crud() {
-        let mut base = PocketBase::new("http://test.com", "en");
-        let collection_service = CollectionService::new(&mut base);
+        let base = PocketBase::new("http://test.com", "en");
+        let collection_service = CollectionService::new(&base);
 
-        assert!(collection_service.collection_base_path == "api/collections");
+        assert!(collection_service.collection_base_path == ["api", "collections"]);
     }
 
     #[tokio::test]
     async fn test_collection_import() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -94,8 +274,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut collection_service = CollectionService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let collection_service = CollectionService::new(&base);
         let config = CollectionImportConfig::<HashMap<String, String>> {
             collections: vec![
                 Collection {
@@ -124,4 +304,202 @@ mod test {
         mock.assert_async().await;
         response.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_collection_export() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/collections?perPage=200&page=1")
+            .with_status(200)
+            .with_header("Accept-Language", "en")
+            .match_header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
+            .with_body(
+                r#"{
+  "page": 1,
+  "perPage": 200,
+  "totalItems": 2,
+  "items": [
+    {
+      "id": "2",
+      "created": "2022-06-22 07:13:09.735Z",
+      "updated": "2022-06-22 07:13:09.735Z",
+      "name": "users",
+      "type": "auth",
+      "schema": [],
+      "indexes": [],
+      "system": false,
+      "listRule": "id = @request.auth.id",
+      "viewRule": null,
+      "createRule": "",
+      "updateRule": null,
+      "deleteRule": null,
+      "options": {"allowEmailAuth": true}
+    },
+    {
+      "id": "1",
+      "created": "2022-06-22 07:13:09.735Z",
+      "updated": "2022-06-22 07:13:09.735Z",
+      "name": "posts",
+      "type": "base",
+      "schema": [],
+      "indexes": [],
+      "system": false,
+      "listRule": null,
+      "viewRule": null,
+      "createRule": null,
+      "updateRule": null,
+      "deleteRule": null,
+      "options": {}
+    }
+  ]
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let path = std::env::temp_dir().join(format!(
+            "rpocket_collection_export_{}.json",
+            std::process::id()
+        ));
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let collection_service = CollectionService::new(&base);
+        let config = CollectionExportConfig {
+            path: path.clone(),
+            ..Default::default()
+        };
+
+        let response = collection_service.export(&config).await;
+        mock.assert_async().await;
+        let response = response.unwrap();
+        assert!(response.len() == 2);
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        assert!(!data.contains("created"));
+        assert!(!data.contains("updated"));
+        assert!(data.find("posts").unwrap() < data.find("users").unwrap());
+
+        let config = CollectionImportConfig::<HashMap<String, String>>::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(config.collections.len() == 2);
+        assert!(config.collections[0].base.id == "1");
+        assert!(config.collections[0].base.created.is_empty());
+        assert!(config.collections[1].name == "users");
+        assert!(config.collections[1].collection_type == "auth");
+        assert!(config.collections[1].create_rule == Some("".to_string()));
+        assert!(config.collections[1].view_rule.is_none());
+        assert!(!config.delete_missing);
+    }
+
+    #[test]
+    fn test_export_collections_is_deterministic() {
+        let collections = vec![
+            Collection {
+                base: BaseModel {
+                    id: "b".to_string(),
+                    created: "2022-06-22 07:13:09.735Z".to_string(),
+                    updated: "2022-06-22 07:13:09.735Z".to_string(),
+                },
+                name: "b".to_string(),
+                ..Default::default()
+            },
+            Collection {
+                base: BaseModel {
+                    id: "a".to_string(),
+                    ..Default::default()
+                },
+                name: "a".to_string(),
+                ..Default::default()
+            },
+        ];
+        let reversed = collections.iter().rev().cloned().collect::<Vec<_>>();
+
+        let data = export_collections(&collections).unwrap();
+        assert_eq!(data, export_collections(&reversed).unwrap());
+
+        let imported = import_collections(&data).unwrap();
+        assert!(imported[0].name == "a");
+        assert!(imported[1].name == "b");
+        assert!(imported[1].base.updated.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_collection_v0_23() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let collection: Collection = serde_json::from_str(
+            r#"{
+  "id": "pbc_1",
+  "name": "users",
+  "type": "auth",
+  "system": false,
+  "listRule": "id = @request.auth.id",
+  "viewRule": null,
+  "createRule": "",
+  "updateRule": null,
+  "deleteRule": null,
+  "fields": [
+    {"id": "text1", "name": "name", "type": "text", "system": false, "required": false, "hidden": false, "max": 255},
+    {"id": "relation1", "name": "team", "type": "relation", "collectionId": "pbc_2", "maxSelect": 1}
+  ],
+  "indexes": [],
+  "passwordAuth": {"enabled": true, "identityFields": ["email"]},
+  "created": "2024-11-01 10:00:00.000Z",
+  "updated": "2024-11-01 10:00:00.000Z"
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(collection.base.id, "pbc_1");
+        assert_eq!(collection.schema.len(), 2);
+        assert_eq!(collection.schema[0].options["max"], 255);
+        assert_eq!(collection.schema[1].options["collectionId"], "pbc_2");
+        assert_eq!(
+            collection.options,
+            HashMap::from([(
+                "passwordAuth".to_string(),
+                serde_json::json!({"enabled": true, "identityFields": ["email"]})
+            )])
+        );
+
+        let mock = server
+            .mock("PUT", "/api/collections/import")
+            .with_status(204)
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "collections": [{
+                    "id": "pbc_1",
+                    "fields": [
+                        {"name": "name", "max": 255, "hidden": false},
+                        {"name": "team", "collectionId": "pbc_2", "maxSelect": 1},
+                    ],
+                    "passwordAuth": {"enabled": true, "identityFields": ["email"]},
+                }],
+                "deleteMissing": true,
+            })))
+            .create_async()
+            .await;
+
+        let base = crate::rpocket::PocketBaseBuilder::new()
+            .base_url(url)
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let value = collection.to_api_value(ApiVersion::V0_23).unwrap();
+        assert!(value.get("schema").is_none() && value.get("options").is_none());
+
+        let response = CollectionService::new(&base)
+            .import(&CollectionImportConfig::<HashMap<String, String>> {
+                collections: vec![collection],
+                delete_missing: true,
+                ..Default::default()
+            })
+            .await;
+
+        mock.assert_async().await;
+        response.unwrap();
+    }
 }
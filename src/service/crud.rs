@@ -1,4 +1,8 @@
-use crate::{error::RPocketError, model::ListResult};
+use crate::{
+    error::RPocketError,
+    model::ListResult,
+    service::{http::RequestOptions, request::ApiRequest},
+};
 #[cfg(feature = "multipart")]
 use reqwest::multipart;
 use serde::{Deserialize, Serialize};
@@ -12,6 +16,8 @@ pub struct CRUDGetListConfig {
     pub per_page: i64,
     pub page: i64,
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 impl Default for CRUDGetListConfig {
@@ -21,6 +27,7 @@ impl Default for CRUDGetListConfig {
             per_page: DEFAULT_PER_PAGE,
             page: DEFAULT_PAGE,
             query_params: Vec::new(),
+            options: RequestOptions::default(),
         }
     }
 }
@@ -30,6 +37,8 @@ impl Default for CRUDGetListConfig {
 pub struct CRUDGetOneConfig {
     pub id: String,
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// CRUDMutateConfig is the config for the mutate method.
@@ -41,6 +50,8 @@ pub struct CRUDMutateConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// CRUDDeleteConfig is the config for the delete method.
@@ -48,12 +59,82 @@ pub struct CRUDMutateConfig<T> {
 pub struct CRUDDeleteConfig {
     pub id: String,
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+// CRUDRequests builds the CRUD requests of the async and the blocking services.
+pub(crate) struct CRUDRequests<'a> {
+    pub(crate) base_path: &'a [&'a str],
+}
+
+impl<'a> CRUDRequests<'a> {
+    fn record_path<'s>(&self, id: &'s str) -> Vec<&'s str>
+    where
+        'a: 's,
+    {
+        [self.base_path, &[id]].concat()
+    }
+
+    pub(crate) fn get_list<'c>(&self, config: &'c CRUDGetListConfig) -> ApiRequest<'c> {
+        let page = [
+            ("perPage".to_string(), config.per_page.to_string()),
+            ("page".to_string(), config.page.to_string()),
+        ];
+
+        ApiRequest::new(reqwest::Method::GET, self.base_path, &config.options)
+            .query(&page)
+            .query(&config.query_params)
+    }
+
+    pub(crate) fn get_one<'c>(&self, config: &'c CRUDGetOneConfig) -> ApiRequest<'c> {
+        ApiRequest::new(
+            reqwest::Method::GET,
+            &self.record_path(&config.id),
+            &config.options,
+        )
+        .query(&config.query_params)
+    }
+
+    // a record is created without id and updated with one.
+    pub(crate) fn mutate_target<'s>(&self, id: Option<&'s str>) -> (reqwest::Method, Vec<&'s str>)
+    where
+        'a: 's,
+    {
+        match id {
+            Some(id) => (reqwest::Method::PATCH, self.record_path(id)),
+            None => (reqwest::Method::POST, self.base_path.to_vec()),
+        }
+    }
+
+    pub(crate) fn mutate<'c, B>(
+        &self,
+        config: &'c CRUDMutateConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        let (method, path) = self.mutate_target(config.id.as_deref());
+
+        ApiRequest::new(method, &path, &config.options)
+            .query(&config.query_params)
+            .json(config)
+    }
+
+    pub(crate) fn delete<'c>(&self, config: &'c CRUDDeleteConfig) -> ApiRequest<'c> {
+        ApiRequest::new(
+            reqwest::Method::DELETE,
+            &self.record_path(&config.id),
+            &config.options,
+        )
+        .query(&config.query_params)
+    }
 }
 
 /// CRUDService is the service for CRUD operations.
 pub struct CRUDService<'a, C> {
-    client: &'a mut C,
-    base_path: &'a str,
+    client: &'a C,
+    requests: CRUDRequests<'a>,
 }
 
 impl<'a, C> CRUDService<'a, C>
@@ -61,58 +142,40 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new CRUDService.
-    pub fn new(client: &'a mut C, base_path: &'a str) -> Self {
-        CRUDService { client, base_path }
+    pub fn new(client: &'a C, base_path: &'a [&'a str]) -> Self {
+        CRUDService {
+            client,
+            requests: CRUDRequests { base_path },
+        }
     }
 
     /// get a list of records.
     pub async fn get_list<T>(
-        &mut self,
+        &self,
         config: &CRUDGetListConfig,
     ) -> Result<ListResult<T>, RPocketError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let url = self.client.base_url().join(self.base_path)?;
-        let mut queries: Vec<(&str, &str)> = Vec::with_capacity(2 + config.query_params.len());
-        let per_page = &config.per_page.to_string();
-        let page = &config.page.to_string();
-
-        queries.push(("perPage", per_page));
-        queries.push(("page", page));
-
-        for (key, value) in &config.query_params {
-            queries.push((key, value));
-        }
-
-        let request_builder = self
+        let response = self
             .client
-            .request_builder(reqwest::Method::GET, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&queries);
-
-        let response = self.client.http().send(request_builder).await?;
+            .http()
+            .execute(&self.requests.get_list(config))
+            .await?;
 
         Ok(response.json::<ListResult<T>>().await?)
     }
 
     /// get a record.
-    pub async fn get_one<T>(&mut self, config: &CRUDGetOneConfig) -> Result<T, RPocketError>
+    pub async fn get_one<T>(&self, config: &CRUDGetOneConfig) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/{}", self.base_path, config.id).as_str())?;
-
-        let request_builder = self
+        let response = self
             .client
-            .request_builder(reqwest::Method::GET, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params);
-
-        let response = self.client.http().send(request_builder).await?;
+            .http()
+            .execute(&self.requests.get_one(config))
+            .await?;
 
         Ok(response.json::<T>().await?)
     }
@@ -120,29 +183,16 @@ where
     /// mutate a record
     /// function will create a new record if config.id is None.
     /// function will update a record if config.id is Some.
-    pub async fn mutate<T, B>(&mut self, config: &CRUDMutateConfig<B>) -> Result<T, RPocketError>
+    pub async fn mutate<T, B>(&self, config: &CRUDMutateConfig<B>) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
         B: Serialize,
     {
-        let mut method = reqwest::Method::POST;
-        let mut url = self.client.base_url().join(self.base_path)?;
-
-        if let Some(ref id) = config.id {
-            method = reqwest::Method::PATCH;
-            url = self
-                .client
-                .base_url()
-                .join(format!("{}/{}", self.base_path, id).as_str())?;
-        }
-
-        let request_builder = self
+        let response = self
             .client
-            .request_builder(method, url.as_str())
-            .json(&config)
-            .query(&config.query_params);
-
-        let response = self.client.http().send(request_builder).await?;
+            .http()
+            .execute(&self.requests.mutate(config)?)
+            .await?;
 
         Ok(response.json::<T>().await?)
     }
@@ -152,22 +202,14 @@ where
     /// function will update a record if config.id is Some.
     #[cfg(feature = "multipart")]
     pub async fn multipart_mutate<T>(
-        &mut self,
+        &self,
         config: CRUDMutateConfig<multipart::Form>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let mut method = reqwest::Method::POST;
-        let mut url = self.client.base_url().join(self.base_path)?;
-
-        if let Some(ref id) = config.id {
-            method = reqwest::Method::PATCH;
-            url = self
-                .client
-                .base_url()
-                .join(format!("{}/{}", self.base_path, id).as_str())?;
-        }
+        let (method, path) = self.requests.mutate_target(config.id.as_deref());
+        let url = self.client.url(&path)?;
 
         let request_builder = self
             .client
@@ -175,25 +217,21 @@ where
             .multipart(config.body)
             .query(&config.query_params);
 
-        let response = self.client.http().send(request_builder).await?;
+        let response = self
+            .client
+            .http()
+            .send_with_options(request_builder, &config.options)
+            .await?;
 
         Ok(response.json::<T>().await?)
     }
 
     /// delete a record
-    pub async fn delete(&mut self, config: &CRUDDeleteConfig) -> Result<(), RPocketError> {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("{}/{}", self.base_path, config.id).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::DELETE, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params);
-
-        self.client.http().send(request_builder).await?;
+    pub async fn delete(&self, config: &CRUDDeleteConfig) -> Result<(), RPocketError> {
+        self.client
+            .http()
+            .execute(&self.requests.delete(config))
+            .await?;
         Ok(())
     }
 }
@@ -210,7 +248,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_record_get_list() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -264,8 +302,8 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDGetListConfig {
             per_page: 10,
             page: 1,
@@ -327,7 +365,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_record_get_one() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -348,8 +386,8 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDGetOneConfig {
             id: String::from_str("1").unwrap(),
             ..Default::default()
@@ -367,9 +405,52 @@ mod tests {
         assert!(response.data["title"] == "test2");
     }
 
+    #[tokio::test]
+    async fn test_record_get_one_with_base_url_path() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock(
+                "GET",
+                "/pocketbase/api/collections/my%20posts/records/abc%20123",
+            )
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "abc 123",
+                "collectionId": "a98f514eb05f454",
+                "collectionName": "my posts"
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(format!("{}/pocketbase", url).as_str(), "en");
+        let record_service =
+            CRUDService::new(&base, &["api", "collections", "my posts", "records"]);
+
+        let response = record_service
+            .get_one::<Record>(&CRUDGetOneConfig {
+                id: String::from("abc 123"),
+                ..Default::default()
+            })
+            .await;
+        mock.assert_async().await;
+        assert!(response.unwrap().collection_name == "my posts");
+
+        let response = record_service
+            .get_one::<Record>(&CRUDGetOneConfig {
+                id: String::from(".."),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(response, Err(RPocketError::PathError(..))));
+    }
+
     #[tokio::test]
     async fn test_record_mutate_create() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -393,8 +474,8 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDMutateConfig {
             id: None,
             body: Record {
@@ -412,6 +493,7 @@ mod tests {
                 expand: Option::None,
             },
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service.mutate::<Record, Record>(&config).await;
@@ -428,7 +510,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_record_mutate_update() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -452,8 +534,8 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDMutateConfig {
             id: Some(String::from_str("d08dfc4f4d84419").unwrap()),
             body: Record {
@@ -471,6 +553,7 @@ mod tests {
                 expand: Option::None,
             },
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service.mutate::<Record, Record>(&config).await;
@@ -488,7 +571,7 @@ mod tests {
     #[tokio::test]
     #[cfg(feature = "multipart")]
     async fn test_record_multipart_mutate_create() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
         let body = multipart::Form::default()
             .text("id", "d08dfc4f4d84419")
@@ -519,12 +602,13 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDMutateConfig {
             id: None,
             body,
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service.multipart_mutate::<Record>(config).await;
@@ -542,7 +626,7 @@ mod tests {
     #[tokio::test]
     #[cfg(feature = "multipart")]
     async fn test_record_multipart_mutate_update() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
         let body = multipart::Form::default()
             .text("id", "d08dfc4f4d84419")
@@ -573,12 +657,13 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDMutateConfig {
             id: Some(String::from("d08dfc4f4d84419")),
             body,
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service.multipart_mutate::<Record>(config).await;
@@ -595,7 +680,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_record_delete() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -606,11 +691,12 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = CRUDService::new(&mut base, "api/collections/test/records");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = CRUDService::new(&base, &["api", "collections", "test", "records"]);
         let config = CRUDDeleteConfig {
             id: String::from("d08dfc4f4d84419"),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service.delete(&config).await;
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::error::RPocketError;
+use crate::error::{APIError, RPocketError};
+use crate::rpocket::ApiVersion;
+use crate::service::http::RequestOptions;
+use crate::service::request::ApiRequest;
 
 /// HealthCheckResponse is the response for the health check.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -13,11 +16,18 @@ pub struct HealthCheckResponse {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HealthCheckConfig {
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+pub(crate) fn check_request(config: &HealthCheckConfig) -> ApiRequest<'_> {
+    ApiRequest::new(reqwest::Method::GET, &["api", "health"], &config.options)
+        .query(&config.query_params)
 }
 
 /// HealthService is the service for health.
 pub struct HealthService<'a, C> {
-    client: &'a mut C,
+    client: &'a C,
 }
 
 impl<'a, C> HealthService<'a, C>
@@ -25,16 +35,29 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new HealthService.
-    pub fn new(client: &'a mut C) -> Self {
+    pub fn new(client: &'a C) -> Self {
         HealthService { client }
     }
 
     /// get the health status of the server.
-    pub async fn check<T>(&mut self, config: &HealthCheckConfig) -> Result<T, RPocketError>
+    pub async fn check<T>(&self, config: &HealthCheckConfig) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let url = self.client.base_url().join("api/health")?;
+        let response = self.client.http().execute(&check_request(config)).await?;
+
+        Ok(response.json::<T>().await?)
+    }
+
+    /// detects the API version of the server.
+    /// the `_superusers` collection only exists since `v0.23`, its auth methods are public.
+    pub async fn detect_api_version(
+        &self,
+        config: &HealthCheckConfig,
+    ) -> Result<ApiVersion, RPocketError> {
+        let url = self
+            .client
+            .url(&["api", "collections", "_superusers", "auth-methods"])?;
 
         let request_builder = self
             .client
@@ -42,20 +65,30 @@ where
             .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
             .query(&config.query_params);
 
-        let response = self.client.http().send(request_builder).await?;
+        let response = self
+            .client
+            .http()
+            .send_with_options(request_builder, &config.options)
+            .await;
 
-        Ok(response.json::<T>().await?)
+        match response {
+            Ok(_) => Ok(ApiVersion::V0_23),
+            Err(RPocketError::APIError(APIError {
+                code: 400 | 404, ..
+            })) => Ok(ApiVersion::V0_14),
+            Err(error) => Err(error),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rpocket::PocketBase;
+    use crate::rpocket::{PocketBase, PocketBaseClient};
 
     #[tokio::test]
     async fn test_health_check() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -72,8 +105,8 @@ mod tests {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut health_service = HealthService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let health_service = HealthService::new(&base);
         let config = HealthCheckConfig {
             ..Default::default()
         };
@@ -86,4 +119,36 @@ mod tests {
         assert_eq!(response.status, 200);
         assert_eq!(response.message, "API is healthy.");
     }
+
+    #[tokio::test]
+    async fn test_detect_api_version() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/collections/_superusers/auth-methods")
+            .with_status(404)
+            .with_body(r#"{"code":404,"message":"Missing collection context.","data":{}}"#)
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let api_version = base.detect_api_version().await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(api_version, ApiVersion::V0_14);
+        assert_eq!(base.api_version(), ApiVersion::V0_14);
+
+        mock.remove_async().await;
+        let mock = server
+            .mock("GET", "/api/collections/_superusers/auth-methods")
+            .with_status(200)
+            .with_body(r#"{"password":{"enabled":true,"identityFields":["email"]}}"#)
+            .create_async()
+            .await;
+
+        let api_version = base.detect_api_version().await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(api_version, ApiVersion::V0_23);
+        assert_eq!(base.clone().api_version(), ApiVersion::V0_23);
+    }
 }
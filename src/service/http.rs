@@ -3,10 +3,108 @@ use crate::error::RPocketError;
 use crate::rpocket::{
     PocketBaseHTTPRequest, PocketBaseHTTPResponse, PocketBaseRequest, PocketBaseResponse,
 };
+use crate::service::auth_state::SavedAuthResponse;
+use crate::service::request::ApiRequest;
+
+/// RequestAuth is the Authorization used by a request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum RequestAuth {
+    /// use the token from the auth store.
+    #[default]
+    Stored,
+    /// send the request without Authorization header.
+    Skip,
+    /// use the given token instead of the stored one.
+    Token(String),
+}
+
+/// RequestOptions are the options of a single request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestOptions {
+    pub headers: Vec<(String, String)>,
+    pub lang: Option<String>,
+    pub timeout: Option<std::time::Duration>,
+    pub auth: RequestAuth,
+    /// a new request with the same key cancels the in-flight one,
+    /// it is ignored by the blocking client.
+    pub request_key: Option<String>,
+}
+
+impl RequestOptions {
+    /// adds a header, it replaces the client default header with the same name.
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// overrides the Accept-Language of the client.
+    pub fn lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
+    /// sets the timeout of the request.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// sends the request without the stored token.
+    pub fn skip_auth(mut self) -> Self {
+        self.auth = RequestAuth::Skip;
+        self
+    }
+
+    /// sends the request with the given token instead of the stored one.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.auth = RequestAuth::Token(token.into());
+        self
+    }
+
+    /// sets the request key, see `PocketBase::cancel`.
+    pub fn request_key(mut self, key: impl Into<String>) -> Self {
+        self.request_key = Some(key.into());
+        self
+    }
+
+    // builds the headers of the request from the client lang and the stored token.
+    pub(crate) fn build_headers(
+        &self,
+        lang: &str,
+        stored_token: Option<String>,
+    ) -> Result<reqwest::header::HeaderMap, RPocketError> {
+        let mut header_map = reqwest::header::HeaderMap::new();
+
+        let lang = self.lang.as_deref().unwrap_or(lang);
+        header_map.insert(
+            reqwest::header::ACCEPT_LANGUAGE,
+            reqwest::header::HeaderValue::from_str(lang)
+                .map_err(|_| RPocketError::ConfigError(format!("invalid lang: {}", lang)))?,
+        );
+
+        let token = match self.auth {
+            RequestAuth::Stored => stored_token,
+            RequestAuth::Skip => None,
+            RequestAuth::Token(ref token) => Some(token.clone()),
+        };
+
+        if let Some(token) = token {
+            header_map.insert(
+                reqwest::header::AUTHORIZATION,
+                reqwest::header::HeaderValue::from_str(&token)
+                    .map_err(|_| RPocketError::ConfigError("invalid auth token".to_string()))?,
+            );
+        }
+
+        header_map.extend(crate::rpocket::build_default_headers(&self.headers, None)?);
+
+        Ok(header_map)
+    }
+}
 
 /// HTTPRequest is the request for the HTTP service.
 pub struct HTTPService<'a, C> {
-    client: &'a mut C,
+    client: &'a C,
 }
 
 impl<'a, C> HTTPService<'a, C>
@@ -14,39 +112,113 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new HTTPService.
-    pub fn new(client: &'a mut C) -> Self {
+    pub fn new(client: &'a C) -> Self {
         HTTPService { client }
     }
 
     /// send a request.
     pub async fn send(
-        &mut self,
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, RPocketError> {
+        self.send_with_options(request_builder, &RequestOptions::default())
+            .await
+    }
+
+    /// send a request with the given options.
+    pub async fn send_with_options(
+        &self,
         mut request_builder: reqwest::RequestBuilder,
+        options: &RequestOptions,
     ) -> Result<reqwest::Response, RPocketError> {
-        request_builder = request_builder.header(
-            reqwest::header::ACCEPT_LANGUAGE.as_str(),
-            self.client.lang(),
-        );
+        let stored_token = match options.auth {
+            RequestAuth::Stored => self.client.auth_state().get_token().await?,
+            _ => None,
+        };
+
+        request_builder =
+            request_builder.headers(options.build_headers(self.client.lang(), stored_token)?);
 
-        if let Some(token) = self.client.auth_state().get_token().await? {
-            request_builder =
-                request_builder.header(reqwest::header::AUTHORIZATION.as_str(), token);
+        if let Some(timeout) = options.timeout {
+            request_builder = request_builder.timeout(timeout);
         }
 
         let pb_request = PocketBaseRequest::HTTP(PocketBaseHTTPRequest { request_builder });
-        let pb_response = self.client.call(pb_request).await?;
-
-        match pb_response {
-            PocketBaseResponse::HTTP(PocketBaseHTTPResponse { response }) => {
-                if !response.status().is_success() {
-                    return Err(RPocketError::APIError(response.json::<APIError>().await?));
-                }
-                Ok(response)
-            }
+        // the body is read before the request is done, so cancelling it stops the download.
+        let future = async {
+            let PocketBaseResponse::HTTP(PocketBaseHTTPResponse { response }) =
+                self.client.call(pb_request).await?;
+            buffer_response(response).await
+        };
+        let response = match self.client.requests() {
+            Some(requests) => requests.run(options.request_key.as_deref(), future).await?,
+            None => future.await?,
+        };
+
+        if !response.status().is_success() {
+            return Err(RPocketError::APIError(response.json::<APIError>().await?));
         }
+        Ok(response)
+    }
+
+    // sends a request built by a service.
+    pub(crate) async fn execute(
+        &self,
+        request: &ApiRequest<'_>,
+    ) -> Result<reqwest::Response, RPocketError> {
+        let url = request.url(self.client.base_url())?;
+        let mut request_builder = self
+            .client
+            .request_builder(request.method.clone(), url.as_str())
+            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
+            .query(&request.query);
+
+        if let Some(ref body) = request.body {
+            request_builder = request_builder.body(body.clone());
+        }
+
+        self.send_with_options(request_builder, request.options)
+            .await
+    }
+
+    // sends an auth request, the token and the record of the response are saved
+    // unless `without_saving` is set.
+    pub(crate) async fn execute_auth<A, T>(
+        &self,
+        request: &ApiRequest<'_>,
+        without_saving: bool,
+    ) -> Result<T, RPocketError>
+    where
+        A: SavedAuthResponse,
+        T: serde::de::DeserializeOwned,
+    {
+        let response = self.execute(request).await?;
+        if without_saving {
+            return Ok(response.json::<T>().await?);
+        }
+
+        let auth_response = response.json::<A>().await?;
+        let (token, payload) = auth_response.auth_payload();
+        self.client.auth_state().save(token, &payload).await?;
+
+        auth_response.into_response()
     }
 }
 
+// reads the whole body, the returned response reads it from memory.
+async fn buffer_response(response: reqwest::Response) -> Result<reqwest::Response, RPocketError> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+
+    let mut buffered = http::Response::new(response.bytes().await?);
+    *buffered.status_mut() = status;
+    *buffered.version_mut() = version;
+    *buffered.headers_mut() = headers;
+
+    Ok(reqwest::Response::from(buffered))
+}
+
 #[cfg(test)]
 mod test {
     use crate::{rpocket::PocketBaseClient, rpocket::TOKEN_KEY, PocketBase};
@@ -55,7 +227,7 @@ mod test {
 
     #[tokio::test]
     async fn test_http_send() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -74,11 +246,11 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
+        let base = PocketBase::new(url.as_str(), "en");
         base.storage().set(TOKEN_KEY, "test").await.unwrap();
 
         let request_builder = base.request_builder(reqwest::Method::GET, url.as_str());
-        let mut http_service = HTTPService::new(&mut base);
+        let http_service = HTTPService::new(&base);
 
         let response = http_service.send(request_builder).await.unwrap_err();
         mock.create_async().await;
@@ -98,4 +270,90 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_http_send_with_options() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let token_mock = server
+            .mock("GET", "/token")
+            .with_status(200)
+            .match_header(reqwest::header::ACCEPT_LANGUAGE.as_str(), "fr")
+            .match_header(reqwest::header::AUTHORIZATION.as_str(), "explicit")
+            .match_header("X-Tenant", "acme")
+            .create_async()
+            .await;
+        let skip_mock = server
+            .mock("GET", "/skip")
+            .with_status(200)
+            .match_header(reqwest::header::ACCEPT_LANGUAGE.as_str(), "en")
+            .match_header(
+                reqwest::header::AUTHORIZATION.as_str(),
+                mockito::Matcher::Missing,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        base.storage().set(TOKEN_KEY, "stored").await.unwrap();
+        let http_service = HTTPService::new(&base);
+
+        let options = RequestOptions::default()
+            .lang("fr")
+            .token("explicit")
+            .header("X-Tenant", "acme")
+            .timeout(std::time::Duration::from_secs(5));
+        let request_builder =
+            base.request_builder(reqwest::Method::GET, format!("{}/token", url).as_str());
+        http_service
+            .send_with_options(request_builder, &options)
+            .await
+            .unwrap();
+        token_mock.assert_async().await;
+
+        let options = RequestOptions::default().skip_auth();
+        let request_builder =
+            base.request_builder(reqwest::Method::GET, format!("{}/skip", url).as_str());
+        http_service
+            .send_with_options(request_builder, &options)
+            .await
+            .unwrap();
+        skip_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_http_cancel_body() {
+        // the server sends the headers and stalls in the body.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            use std::io::{Read, Write};
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n{")
+                .unwrap();
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        });
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let http_service = HTTPService::new(&base);
+        let options = RequestOptions::default().request_key("slow");
+        let started = std::time::Instant::now();
+
+        let (response, _) = tokio::join!(
+            http_service.send_with_options(
+                base.request_builder(reqwest::Method::GET, url.as_str()),
+                &options,
+            ),
+            async {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                base.cancel("slow").unwrap();
+            },
+        );
+
+        assert!(matches!(response, Err(RPocketError::Cancelled)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
 }
@@ -1,24 +1,347 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Bound, RangeBounds};
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{error::RPocketError, service};
+use crate::{
+    error::RPocketError,
+    filter,
+    model::{self, ListResult, Log, LogRequest},
+    rpocket::ApiVersion,
+    service,
+    service::{http::RequestOptions, request::ApiRequest},
+};
 
 /// LogGetRequestsStatsResponse is the response for the get requests stats.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LogGetRequestsStatsResponse {
     pub total: i64,
-    pub date: String,
+    #[serde(with = "model::date_format")]
+    pub date: DateTime<Utc>,
+}
+
+/// LogRequestFilter is a typed filter for the request logs.
+/// all the set conditions have to match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogRequestFilter {
+    /// inclusive min status.
+    pub status_min: Option<i64>,
+    /// inclusive max status.
+    pub status_max: Option<i64>,
+    /// any of the methods has to match.
+    pub methods: Vec<String>,
+    pub url_contains: Option<String>,
+    /// auth type, eg. `guest`, `admin` or `authRecord`.
+    pub auth: Option<String>,
+    pub remote_ip: Option<String>,
+    /// min created date, inclusive unless `created_from_exclusive` is set.
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_from_exclusive: bool,
+    /// max created date, exclusive unless `created_to_inclusive` is set.
+    pub created_to: Option<DateTime<Utc>>,
+    pub created_to_inclusive: bool,
+}
+
+impl LogRequestFilter {
+    /// create a new empty LogRequestFilter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// match the status codes in range, eg. `400..500` or `500..`.
+    pub fn status<R>(mut self, range: R) -> Self
+    where
+        R: RangeBounds<i64>,
+    {
+        self.status_min = match range.start_bound() {
+            Bound::Included(start) => Some(*start),
+            Bound::Excluded(start) => Some(start.saturating_add(1)),
+            Bound::Unbounded => None,
+        };
+        self.status_max = match range.end_bound() {
+            Bound::Included(end) => Some(*end),
+            Bound::Excluded(end) => Some(end.saturating_sub(1)),
+            Bound::Unbounded => None,
+        };
+        self
+    }
+
+    /// match the request method, can be called multiple times.
+    pub fn method(mut self, method: &str) -> Self {
+        self.methods.push(method.to_uppercase());
+        self
+    }
+
+    /// match the urls containing the value.
+    pub fn url_contains(mut self, value: &str) -> Self {
+        self.url_contains = Some(value.to_string());
+        self
+    }
+
+    /// match the auth type.
+    pub fn auth(mut self, auth: &str) -> Self {
+        self.auth = Some(auth.to_string());
+        self
+    }
+
+    /// match the remote ip.
+    pub fn remote_ip(mut self, remote_ip: &str) -> Self {
+        self.remote_ip = Some(remote_ip.to_string());
+        self
+    }
+
+    /// match the requests created in range.
+    pub fn created<R>(mut self, range: R) -> Self
+    where
+        R: RangeBounds<DateTime<Utc>>,
+    {
+        (self.created_from, self.created_from_exclusive) = match range.start_bound() {
+            Bound::Included(start) => (Some(*start), false),
+            Bound::Excluded(start) => (Some(*start), true),
+            Bound::Unbounded => (None, false),
+        };
+        (self.created_to, self.created_to_inclusive) = match range.end_bound() {
+            Bound::Included(end) => (Some(*end), true),
+            Bound::Excluded(end) => (Some(*end), false),
+            Bound::Unbounded => (None, false),
+        };
+        self
+    }
+
+    /// returns the filter expression, empty if no condition was set.
+    pub fn build(&self) -> String {
+        self.build_for(ApiVersion::V0_14)
+    }
+
+    /// returns the filter expression for the API version,
+    /// since `v0.23` the request fields are in `data` and other log types are excluded.
+    pub fn build_for(&self, api_version: ApiVersion) -> String {
+        let field = |name: &'static str| match api_version {
+            ApiVersion::V0_14 => name.to_string(),
+            ApiVersion::V0_23 if name == "remoteIp" => "data.remoteIP".to_string(),
+            ApiVersion::V0_23 if name == "created" => name.to_string(),
+            ApiVersion::V0_23 => format!("data.{}", name),
+        };
+        let mut conditions = Vec::new();
+
+        if api_version == ApiVersion::V0_23 {
+            conditions.push("data.type = \"request\"".to_string());
+        }
+        if let Some(status_min) = self.status_min {
+            conditions.push(format!("{} >= {}", field("status"), status_min));
+        }
+        if let Some(status_max) = self.status_max {
+            conditions.push(format!("{} <= {}", field("status"), status_max));
+        }
+        if !self.methods.is_empty() {
+            let methods = self
+                .methods
+                .iter()
+                .map(|method| format!("{} = {}", field("method"), filter::quote(method)))
+                .collect::<Vec<_>>();
+            conditions.push(format!("({})", methods.join(" || ")));
+        }
+        if let Some(ref url) = self.url_contains {
+            conditions.push(format!("{} ~ {}", field("url"), filter::quote(url)));
+        }
+        if let Some(ref auth) = self.auth {
+            conditions.push(format!("{} = {}", field("auth"), filter::quote(auth)));
+        }
+        if let Some(ref remote_ip) = self.remote_ip {
+            conditions.push(format!(
+                "{} = {}",
+                field("remoteIp"),
+                filter::quote(remote_ip)
+            ));
+        }
+        if let Some(ref created_from) = self.created_from {
+            conditions.push(format!(
+                "{} {} {}",
+                field("created"),
+                if self.created_from_exclusive {
+                    ">"
+                } else {
+                    ">="
+                },
+                filter::quote(&model::format_date(created_from))
+            ));
+        }
+        if let Some(ref created_to) = self.created_to {
+            conditions.push(format!(
+                "{} {} {}",
+                field("created"),
+                if self.created_to_inclusive { "<=" } else { "<" },
+                filter::quote(&model::format_date(created_to))
+            ));
+        }
+
+        conditions.join(" && ")
+    }
+
+    /// returns the filter as query params.
+    pub fn to_query_params(&self) -> Vec<(String, String)> {
+        self.to_query_params_for(ApiVersion::V0_14)
+    }
+
+    /// returns the filter for the API version as query params.
+    pub fn to_query_params_for(&self, api_version: ApiVersion) -> Vec<(String, String)> {
+        let filter = self.build_for(api_version);
+        if filter.is_empty() {
+            return Vec::new();
+        }
+
+        vec![("filter".to_string(), filter)]
+    }
+}
+
+// LogRequests builds the log requests of the async and the blocking services.
+pub(crate) struct LogRequests {
+    pub(crate) api_version: ApiVersion,
+}
+
+impl LogRequests {
+    pub(crate) fn stats<'c>(&self, config: &'c LogGetRequestsStatsConfig) -> ApiRequest<'c> {
+        let path = [self.api_version.log_base_path(), &["stats"]].concat();
+        ApiRequest::new(reqwest::Method::GET, &path, &config.options).query(&config.query_params)
+    }
+
+    // adds the filter, with the field names of the API version, to the list config.
+    pub(crate) fn requests_list_config(
+        &self,
+        filter: &LogRequestFilter,
+        config: &service::crud::CRUDGetListConfig,
+    ) -> service::crud::CRUDGetListConfig {
+        let mut config = config.clone();
+        config
+            .query_params
+            .extend(filter.to_query_params_for(self.api_version));
+        config
+    }
+
+    // reads the listed logs, the logs of `v0.23` are converted to request logs.
+    pub(crate) fn requests_list(
+        &self,
+        logs: ListResult<serde_json::Value>,
+    ) -> Result<ListResult<LogRequest>, RPocketError> {
+        let items = logs.items.into_iter().map(|item| match self.api_version {
+            ApiVersion::V0_14 => serde_json::from_value::<LogRequest>(item),
+            ApiVersion::V0_23 => serde_json::from_value::<Log>(item).map(LogRequest::from),
+        });
+
+        Ok(ListResult {
+            page: logs.page,
+            per_page: logs.per_page,
+            total_items: logs.total_items,
+            items: items.collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// LogHourlyErrorRate is the error rate of the requests in an hour.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogHourlyErrorRate {
+    pub hour: DateTime<Utc>,
+    pub total: i64,
+    /// requests with status >= 400.
+    pub errors: i64,
+    pub rate: f64,
+}
+
+/// LogEndpointCount is the number of requests to an endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEndpointCount {
+    pub method: String,
+    /// the request url without the query string.
+    pub url: String,
+    pub count: i64,
+}
+
+/// returns the error rate per hour, sorted by hour.
+/// logs with an invalid created date are skipped.
+pub fn error_rate_per_hour(logs: &[LogRequest]) -> Vec<LogHourlyErrorRate> {
+    let mut hours: BTreeMap<DateTime<Utc>, (i64, i64)> = BTreeMap::new();
+
+    for log in logs {
+        let hour = match model::parse_date(&log.base.created)
+            .ok()
+            .and_then(|created| created.duration_trunc(Duration::hours(1)).ok())
+        {
+            Some(hour) => hour,
+            None => continue,
+        };
+
+        let entry = hours.entry(hour).or_insert((0, 0));
+        entry.0 += 1;
+        if log.status >= 400 {
+            entry.1 += 1;
+        }
+    }
+
+    hours
+        .into_iter()
+        .map(|(hour, (total, errors))| LogHourlyErrorRate {
+            hour,
+            total,
+            errors,
+            rate: errors as f64 / total as f64,
+        })
+        .collect()
+}
+
+/// returns the most requested endpoints, sorted by count.
+pub fn top_endpoints(logs: &[LogRequest], limit: usize) -> Vec<LogEndpointCount> {
+    let mut counts: HashMap<(&str, &str), i64> = HashMap::new();
+
+    for log in logs {
+        let url = log.url.split('?').next().unwrap_or_default();
+        *counts.entry((log.method.as_str(), url)).or_insert(0) += 1;
+    }
+
+    let mut endpoints = counts
+        .into_iter()
+        .map(|((method, url), count)| LogEndpointCount {
+            method: method.to_string(),
+            url: url.to_string(),
+            count,
+        })
+        .collect::<Vec<_>>();
+
+    endpoints.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.url.cmp(&b.url))
+            .then_with(|| a.method.cmp(&b.method))
+    });
+    endpoints.truncate(limit);
+    endpoints
+}
+
+/// returns the slowest requests by `meta.execTime`, slowest first.
+/// logs without an execution time are skipped.
+pub fn slowest_requests(logs: &[LogRequest], limit: usize) -> Vec<&LogRequest> {
+    let mut timed = logs
+        .iter()
+        .filter_map(|log| log.exec_time().map(|exec_time| (exec_time, log)))
+        .collect::<Vec<_>>();
+
+    timed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    timed.into_iter().take(limit).map(|(_, log)| log).collect()
 }
 
 /// LogGetRequestsStatsConfig is the config for the get requests stats.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LogGetRequestsStatsConfig {
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// LogService is the service for logs.
 pub struct LogService<'a, C> {
-    client: &'a mut C,
-    request_base_path: String,
+    client: &'a C,
+    request_base_path: &'static [&'static str],
+    requests: LogRequests,
 }
 
 impl<'a, C> LogService<'a, C>
@@ -26,60 +349,69 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new LogService.
-    pub fn new(client: &'a mut C) -> Self {
+    pub fn new(client: &'a C) -> Self {
         LogService {
             client,
-            request_base_path: "api/logs/requests".to_string(),
+            request_base_path: client.api_version().log_base_path(),
+            requests: LogRequests {
+                api_version: client.api_version(),
+            },
         }
     }
 
     /// returns crud service.
-    pub fn crud(&'a mut self) -> service::crud::CRUDService<'a, C> {
-        self.client.crud(&self.request_base_path)
+    pub fn crud(&self) -> service::crud::CRUDService<'_, C> {
+        self.client.crud(self.request_base_path)
     }
 
     /// get the requests stats of the server.
     pub async fn get_requests_stats<T>(
-        &mut self,
+        &self,
         config: &LogGetRequestsStatsConfig,
     ) -> Result<Vec<T>, RPocketError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let url = self
+        let response = self
             .client
-            .base_url()
-            .join(format!("{}/stats", self.request_base_path).as_str())?;
+            .http()
+            .execute(&self.requests.stats(config))
+            .await?;
 
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::GET, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params);
+        Ok(response.json::<Vec<T>>().await?)
+    }
 
-        let response = self.client.http().send(request_builder).await?;
+    /// get a list of request logs matching the filter.
+    pub async fn get_requests_list(
+        &self,
+        filter: &LogRequestFilter,
+        config: &service::crud::CRUDGetListConfig,
+    ) -> Result<ListResult<LogRequest>, RPocketError> {
+        let config = self.requests.requests_list_config(filter, config);
+        let logs = self.crud().get_list(&config).await?;
 
-        Ok(response.json::<Vec<T>>().await?)
+        self.requests.requests_list(logs)
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::PocketBase;
+    use chrono::TimeZone;
 
     use super::*;
 
     #[test]
     fn test_log_crud() {
-        let mut base = PocketBase::new("http://test.com", "en");
-        let log_service = LogService::new(&mut base);
+        let base = PocketBase::new("http://test.com", "en");
+        let log_service = LogService::new(&base);
 
-        assert!(log_service.request_base_path == "api/logs/requests");
+        assert!(log_service.request_base_path == ["api", "logs", "requests"]);
     }
 
     #[tokio::test]
     async fn test_log_get_requests_stats() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -106,8 +438,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut log_service = LogService::new(&mut base);
+        let base = PocketBase::new(url.as_str(), "en");
+        let log_service = LogService::new(&base);
         let config = LogGetRequestsStatsConfig {
             ..Default::default()
         };
@@ -121,6 +453,258 @@ mod test {
 
         assert!(response.len() == 3);
         assert!(response[0].total == 4);
-        assert!(response[0].date == "2022-06-01 19:00:00.000");
+        assert!(response[0].date == Utc.with_ymd_and_hms(2022, 6, 1, 19, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_log_request_filter_build() {
+        assert!(LogRequestFilter::new().build().is_empty());
+        assert!(LogRequestFilter::new().to_query_params().is_empty());
+
+        let filter = LogRequestFilter::new()
+            .status(400..500)
+            .method("get")
+            .method("POST")
+            .url_contains("/api/collections/\"posts\"")
+            .auth("guest")
+            .remote_ip("127.0.0.1")
+            .created(
+                Utc.with_ymd_and_hms(2022, 6, 1, 0, 0, 0).unwrap()
+                    ..Utc.with_ymd_and_hms(2022, 6, 2, 0, 0, 0).unwrap(),
+            );
+
+        assert_eq!(
+            filter.build(),
+            r#"status >= 400 && status <= 499 && (method = "GET" || method = "POST") && url ~ "/api/collections/\"posts\"" && auth = "guest" && remoteIp = "127.0.0.1" && created >= "2022-06-01 00:00:00.000Z" && created < "2022-06-02 00:00:00.000Z""#
+        );
+        assert_eq!(
+            LogRequestFilter::new().status(500..).build(),
+            "status >= 500"
+        );
+        assert_eq!(
+            LogRequestFilter::new()
+                .status((Bound::Excluded(i64::MAX), Bound::Excluded(i64::MIN)))
+                .build(),
+            format!("status >= {} && status <= {}", i64::MAX, i64::MIN)
+        );
+        assert_eq!(
+            LogRequestFilter::new().url_contains("a\\b\n").build(),
+            "url ~ \"a\\b\n\""
+        );
+        assert_eq!(
+            LogRequestFilter::new()
+                .created(
+                    Utc.with_ymd_and_hms(2022, 6, 1, 0, 0, 0).unwrap()
+                        ..=Utc.with_ymd_and_hms(2022, 6, 2, 0, 0, 0).unwrap(),
+                )
+                .build(),
+            r#"created >= "2022-06-01 00:00:00.000Z" && created <= "2022-06-02 00:00:00.000Z""#
+        );
+        assert_eq!(
+            LogRequestFilter::new()
+                .created((
+                    Bound::Excluded(Utc.with_ymd_and_hms(2022, 6, 1, 0, 0, 0).unwrap()),
+                    Bound::Unbounded,
+                ))
+                .build(),
+            r#"created > "2022-06-01 00:00:00.000Z""#
+        );
+        assert_eq!(
+            filter.build_for(ApiVersion::V0_23),
+            r#"data.type = "request" && data.status >= 400 && data.status <= 499 && (data.method = "GET" || data.method = "POST") && data.url ~ "/api/collections/\"posts\"" && data.auth = "guest" && data.remoteIP = "127.0.0.1" && created >= "2022-06-01 00:00:00.000Z" && created < "2022-06-02 00:00:00.000Z""#
+        );
+    }
+
+    fn log_request(
+        created: &str,
+        method: &str,
+        url: &str,
+        status: i64,
+        exec_time: f64,
+    ) -> LogRequest {
+        LogRequest {
+            base: crate::model::BaseModel {
+                id: format!("{}{}{}", created, url, status),
+                created: created.to_string(),
+                updated: created.to_string(),
+            },
+            url: url.to_string(),
+            method: method.to_string(),
+            status,
+            meta: HashMap::from([("execTime".to_string(), serde_json::json!(exec_time))]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_log_analytics() {
+        let logs = vec![
+            log_request("2022-06-01 19:05:00.000Z", "GET", "/api/health", 200, 1.5),
+            log_request(
+                "2022-06-01 19:45:00.000Z",
+                "GET",
+                "/api/health?x=1",
+                500,
+                10.0,
+            ),
+            log_request(
+                "2022-06-01 20:10:00.000Z",
+                "POST",
+                "/api/records",
+                400,
+                3.25,
+            ),
+            log_request("2022-06-01 20:20:00.000Z", "GET", "/api/health", 200, 0.5),
+            log_request("invalid", "GET", "/api/health", 200, 0.1),
+        ];
+
+        let rates = error_rate_per_hour(&logs);
+        assert!(rates.len() == 2);
+        assert!(rates[0].hour == Utc.with_ymd_and_hms(2022, 6, 1, 19, 0, 0).unwrap());
+        assert!(rates[0].total == 2);
+        assert!(rates[0].errors == 1);
+        assert!(rates[0].rate == 0.5);
+        assert!(rates[1].total == 2);
+
+        let endpoints = top_endpoints(&logs, 1);
+        assert_eq!(
+            endpoints,
+            vec![LogEndpointCount {
+                method: "GET".to_string(),
+                url: "/api/health".to_string(),
+                count: 4,
+            }]
+        );
+
+        let slowest = slowest_requests(&logs, 2);
+        assert!(slowest.len() == 2);
+        assert!(slowest[0].status == 500);
+        assert!(slowest[1].url == "/api/records");
+    }
+
+    #[tokio::test]
+    async fn test_log_get_requests_list() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/logs/requests")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("page".into(), "1".into()),
+                mockito::Matcher::UrlEncoded("perPage".into(), "30".into()),
+                mockito::Matcher::UrlEncoded("filter".into(), "status >= 500".into()),
+            ]))
+            .with_status(200)
+            .with_header("Accept-Language", "en")
+            .with_body(
+                r#"{
+  "page": 1,
+  "perPage": 30,
+  "totalItems": 1,
+  "items": [
+    {
+      "id": "ai5z3aoed6809au",
+      "created": "2022-05-01 10:00:00.123Z",
+      "updated": "2022-05-01 10:00:00.123Z",
+      "url": "/api/collections/posts/records",
+      "method": "GET",
+      "status": 500,
+      "auth": "guest",
+      "remoteIp": "127.0.0.1",
+      "userIp": "127.0.0.1",
+      "referer": "",
+      "userAgent": "curl",
+      "meta": {"execTime": 12.5}
+    }
+  ]
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let log_service = LogService::new(&base);
+
+        let response = log_service
+            .get_requests_list(
+                &LogRequestFilter::new().status(500..),
+                &service::crud::CRUDGetListConfig::default(),
+            )
+            .await;
+
+        mock.assert_async().await;
+        let response = response.unwrap();
+
+        assert!(response.items.len() == 1);
+        assert!(response.items[0].url == "/api/collections/posts/records");
+        assert!(response.items[0].exec_time() == Some(12.5));
+    }
+
+    #[tokio::test]
+    async fn test_log_get_requests_list_v0_23() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/logs")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "filter".into(),
+                r#"data.type = "request" && data.status >= 500"#.into(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{
+  "page": 1,
+  "perPage": 30,
+  "totalItems": 1,
+  "items": [
+    {
+      "id": "ai5z3aoed6809au",
+      "created": "2024-11-01 10:00:00.123Z",
+      "level": 8,
+      "message": "GET /api/collections/posts/records",
+      "data": {
+        "type": "request",
+        "url": "/api/collections/posts/records",
+        "method": "GET",
+        "status": 500,
+        "auth": "",
+        "remoteIP": "127.0.0.1",
+        "userIP": "127.0.0.1",
+        "referer": "",
+        "userAgent": "curl",
+        "execTime": 12.5,
+        "error": "boom"
+      }
+    }
+  ]
+}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = crate::rpocket::PocketBaseBuilder::new()
+            .base_url(url)
+            .api_version(ApiVersion::V0_23)
+            .build()
+            .unwrap();
+        let log_service = LogService::new(&base);
+        assert!(log_service.request_base_path == ["api", "logs"]);
+
+        let response = log_service
+            .get_requests_list(
+                &LogRequestFilter::new().status(500..),
+                &service::crud::CRUDGetListConfig::default(),
+            )
+            .await;
+
+        mock.assert_async().await;
+        let response = response.unwrap();
+        assert_eq!(response.total_items, 1);
+        assert_eq!(response.items[0].url, "/api/collections/posts/records");
+        assert_eq!(response.items[0].status, 500);
+        assert_eq!(response.items[0].remote_ip, "127.0.0.1");
+        assert_eq!(response.items[0].exec_time(), Some(12.5));
+        assert_eq!(response.items[0].meta["error"], "boom");
     }
 }
@@ -1,9 +1,13 @@
 pub mod admin;
 pub mod auth_state;
+pub mod batch;
+pub mod bulk;
 pub mod collection;
 pub mod crud;
 pub mod health;
 pub mod http;
 pub mod log;
+pub mod oauth2;
 pub mod record;
+pub(crate) mod request;
 pub mod setting;
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RPocketError;
+use crate::service::http::RequestOptions;
+use crate::service::record::{AuthProviderInfo, RecordAuthWithOAuth2Config};
+
+/// OAUTH2_REDIRECT_TIMEOUT is the default time to wait for the provider redirect.
+pub const OAUTH2_REDIRECT_TIMEOUT: Duration = Duration::from_secs(300);
+
+// page shown in the browser once the redirect is received.
+const OAUTH2_REDIRECT_PAGE: &str =
+    "<html><body>Authentication complete, you can close this window.</body></html>";
+
+// page shown in the browser when the redirect carries an error.
+const OAUTH2_REDIRECT_FAILED_PAGE: &str =
+    "<html><body>Authentication failed, you can close this window.</body></html>";
+
+/// RecordAuthWithOAuth2FlowConfig is the config for the oauth2 flow with a local redirect listener.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordAuthWithOAuth2FlowConfig<T> {
+    /// name of the provider, eg. `github`.
+    pub provider: String,
+    /// port of the redirect listener on 127.0.0.1, a free port is picked if 0.
+    pub redirect_port: u16,
+    /// path of the redirect url, eg. `/callback`.
+    pub redirect_path: String,
+    /// time to wait for the provider redirect.
+    pub timeout: Duration,
+    /// sent with the code, eg. `createData` for new users.
+    #[serde(flatten)]
+    pub body: T,
+    #[serde(skip)]
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
+    pub without_saving: bool,
+}
+
+impl<T> Default for RecordAuthWithOAuth2FlowConfig<T>
+where
+    T: Default,
+{
+    /// create a default RecordAuthWithOAuth2FlowConfig.
+    fn default() -> Self {
+        RecordAuthWithOAuth2FlowConfig {
+            provider: String::new(),
+            redirect_port: 0,
+            redirect_path: "/".to_string(),
+            timeout: OAUTH2_REDIRECT_TIMEOUT,
+            body: T::default(),
+            query_params: Vec::new(),
+            options: RequestOptions::default(),
+            without_saving: false,
+        }
+    }
+}
+
+impl<T> RecordAuthWithOAuth2FlowConfig<T> {
+    // the config exchanging the code received from the provider.
+    pub(crate) fn auth_config(
+        &self,
+        provider: &AuthProviderInfo,
+        code: String,
+        redirect_url: String,
+    ) -> RecordAuthWithOAuth2Config<&T> {
+        RecordAuthWithOAuth2Config {
+            provider: provider.name.clone(),
+            code,
+            code_verifier: provider.code_verifier.clone(),
+            redirect_url,
+            mfa_id: None,
+            body: &self.body,
+            query_params: self.query_params.clone(),
+            options: self.options.clone(),
+            without_saving: self.without_saving,
+        }
+    }
+}
+
+// RedirectListener receives the provider redirect on localhost.
+pub(crate) struct RedirectListener {
+    listener: TcpListener,
+    path: String,
+    redirect_url: String,
+    sender: Sender<Result<String, RPocketError>>,
+    receiver: Receiver<Result<String, RPocketError>>,
+}
+
+impl RedirectListener {
+    pub(crate) fn bind(port: u16, path: &str) -> Result<Self, RPocketError> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let path = match path.starts_with('/') {
+            true => path.to_string(),
+            false => format!("/{}", path),
+        };
+        let redirect_url = format!("http://127.0.0.1:{}{}", listener.local_addr()?.port(), path);
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        Ok(RedirectListener {
+            listener,
+            path,
+            redirect_url,
+            sender,
+            receiver,
+        })
+    }
+
+    pub(crate) fn redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    // waits on a thread for the redirect with the given state and returns its code,
+    // dropping the future stops the thread and frees the port.
+    pub(crate) async fn wait(self, state: &str, timeout: Duration) -> Result<String, RPocketError> {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let mut guard = CancelGuard {
+            sender: self.sender.clone(),
+            armed: true,
+        };
+
+        let state = state.to_string();
+        std::thread::spawn(move || {
+            let _ = sender.send(self.receive(&state, timeout));
+        });
+
+        let result = receiver.await.map_err(|_| RPocketError::Cancelled)?;
+        guard.armed = false;
+        result
+    }
+
+    // blocks until the redirect with the given state is received.
+    pub(crate) fn receive(self, state: &str, timeout: Duration) -> Result<String, RPocketError> {
+        let RedirectListener {
+            listener,
+            path,
+            sender,
+            receiver,
+            ..
+        } = self;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let acceptor = {
+            let stopped = stopped.clone();
+            let state = state.to_string();
+            std::thread::spawn(move || accept(listener, path, state, sender, &stopped))
+        };
+
+        let result = match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => Err(RPocketError::Error(
+                "timed out waiting for the oauth2 redirect".into(),
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err(RPocketError::Cancelled),
+        };
+
+        // wakes the accept up so the port is freed.
+        stopped.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(addr);
+        let _ = acceptor.join();
+
+        result
+    }
+}
+
+// accepts the connections until stopped, each one is handled on its own thread so a
+// connection that sends nothing, eg. a browser preconnect, does not delay the redirect.
+fn accept(
+    listener: TcpListener,
+    path: String,
+    state: String,
+    sender: Sender<Result<String, RPocketError>>,
+    stopped: &AtomicBool,
+) {
+    let path = Arc::new(path);
+    let state = Arc::new(state);
+
+    loop {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(error) => {
+                let _ = sender.send(Err(error.into()));
+                return;
+            }
+        };
+        if stopped.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let (path, state, sender) = (path.clone(), state.clone(), sender.clone());
+        std::thread::spawn(move || {
+            // a failed connection, eg. a malformed request, does not stop the login.
+            if let Ok(Some(result)) = handle(stream, &path, &state) {
+                let _ = sender.send(result);
+            }
+        });
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    redirect_path: &str,
+    state: &str,
+) -> std::io::Result<Option<Result<String, RPocketError>>> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    // eg. `GET /callback?code=...&state=... HTTP/1.1`.
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if path != redirect_path {
+        respond(&mut stream, "404 Not Found", "")?;
+        return Ok(None);
+    }
+
+    let query: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    // a request without the state of this flow is not the provider redirect,
+    // unless it reports an error that may come without the state.
+    if !query.contains_key("error") && query.get("state").map(String::as_str) != Some(state) {
+        respond(&mut stream, "400 Bad Request", OAUTH2_REDIRECT_FAILED_PAGE)?;
+        return Ok(None);
+    }
+
+    let result = redirect_code(&query);
+    let _ = match result {
+        Ok(_) => respond(&mut stream, "200 OK", OAUTH2_REDIRECT_PAGE),
+        Err(_) => respond(&mut stream, "400 Bad Request", OAUTH2_REDIRECT_FAILED_PAGE),
+    };
+
+    Ok(Some(result))
+}
+
+// CancelGuard stops the listener thread when the waiting future is dropped.
+struct CancelGuard {
+    sender: Sender<Result<String, RPocketError>>,
+    armed: bool,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = self.sender.send(Err(RPocketError::Cancelled));
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+// returns the code of the redirect, the state is checked by the listener.
+pub(crate) fn redirect_code(query: &HashMap<String, String>) -> Result<String, RPocketError> {
+    if let Some(error) = query.get("error") {
+        return Err(RPocketError::Error(
+            format!("oauth2 provider error: {}", error).into(),
+        ));
+    }
+
+    query
+        .get("code")
+        .cloned()
+        .ok_or_else(|| RPocketError::Error("oauth2 redirect without code".into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_redirect_code() {
+        let query = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(redirect_code(&query(&[("code", "abc")])).unwrap(), "abc");
+        assert!(redirect_code(&query(&[("error", "access_denied"), ("code", "abc")])).is_err());
+        assert!(redirect_code(&query(&[])).is_err());
+    }
+
+    // sends a request line to the listener and returns the status line of the response.
+    fn get(addr: &str, target: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\n\r\n", target).unwrap();
+        let mut status = String::new();
+        BufReader::new(&stream).read_line(&mut status).unwrap();
+        status.trim_end().to_string()
+    }
+
+    #[test]
+    fn test_redirect_listener() {
+        let listener = RedirectListener::bind(0, "callback").unwrap();
+        let addr = listener.listener.local_addr().unwrap().to_string();
+        let handle = std::thread::spawn(move || listener.receive("s1", Duration::from_secs(10)));
+
+        // a preconnect without request, an other path and a forged state are skipped.
+        drop(TcpStream::connect(&addr).unwrap());
+        assert_eq!(get(&addr, "/favicon.ico"), "HTTP/1.1 404 Not Found");
+        assert_eq!(
+            get(&addr, "/callback?code=x&state=s2"),
+            "HTTP/1.1 400 Bad Request"
+        );
+        assert_eq!(get(&addr, "/callback?code=abc&state=s1"), "HTTP/1.1 200 OK");
+
+        assert_eq!(handle.join().unwrap().unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_redirect_listener_idle_connection() {
+        let listener = RedirectListener::bind(0, "callback").unwrap();
+        let addr = listener.listener.local_addr().unwrap().to_string();
+        let handle = std::thread::spawn(move || listener.receive("s1", Duration::from_secs(10)));
+
+        // a preconnect that sends nothing and stays open does not delay the redirect.
+        let started = std::time::Instant::now();
+        let _idle = TcpStream::connect(&addr).unwrap();
+        assert_eq!(get(&addr, "/callback?code=abc&state=s1"), "HTTP/1.1 200 OK");
+
+        assert_eq!(handle.join().unwrap().unwrap(), "abc");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_redirect_listener_provider_error() {
+        let listener = RedirectListener::bind(0, "callback").unwrap();
+        let addr = listener.listener.local_addr().unwrap().to_string();
+        let handle = std::thread::spawn(move || listener.receive("s1", Duration::from_secs(10)));
+
+        // the provider may redirect an error without the state.
+        assert_eq!(
+            get(&addr, "/callback?error=access_denied"),
+            "HTTP/1.1 400 Bad Request"
+        );
+        assert!(handle.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_redirect_listener_timeout() {
+        let listener = RedirectListener::bind(0, "/").unwrap();
+        let started = std::time::Instant::now();
+
+        assert!(listener.receive("s1", Duration::from_millis(100)).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_listener_cancel() {
+        let listener = RedirectListener::bind(0, "/").unwrap();
+        let port = listener.listener.local_addr().unwrap().port();
+
+        let wait = listener.wait("s1", Duration::from_secs(300));
+        assert!(tokio::time::timeout(Duration::from_millis(100), wait)
+            .await
+            .is_err());
+
+        // the thread stops and frees the port once the future is dropped.
+        let started = std::time::Instant::now();
+        while TcpListener::bind(("127.0.0.1", port)).is_err() {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
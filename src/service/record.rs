@@ -3,6 +3,9 @@ use std::collections::HashMap;
 use crate::error::RPocketError;
 use crate::model::Record;
 use crate::service;
+use crate::service::auth_state::{AuthPayload, SavedAuthResponse};
+use crate::service::http::RequestOptions;
+use crate::service::request::ApiRequest;
 use serde::{Deserialize, Serialize};
 
 /// RecordAuthResponse is the response for the auth.
@@ -14,31 +17,151 @@ pub struct RecordAuthResponse<T> {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
-/// AuthProvicderInfo is the info for an auth provider.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AuthProvicderInfo {
+/// AuthProviderInfo is the info for an auth provider.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuthProviderInfo {
     pub name: String,
+    /// name to show in the login form, since `v0.23`.
+    pub display_name: String,
     pub state: String,
     pub code_verifier: String,
     pub code_challenge: String,
     pub code_challenge_method: String,
+    /// url of the provider, it ends with `redirect_uri=`.
+    #[serde(alias = "authURL")]
     pub auth_url: String,
 }
 
+impl AuthProviderInfo {
+    /// returns true if the provider uses PKCE, `code_verifier` must be sent with the code.
+    pub fn is_pkce(&self) -> bool {
+        !self.code_challenge_method.is_empty()
+    }
+
+    /// returns the url of the provider with the redirect url appended.
+    pub fn auth_url_with_redirect(&self, redirect_url: &str) -> String {
+        format!(
+            "{}{}",
+            self.auth_url,
+            url::form_urlencoded::byte_serialize(redirect_url.as_bytes()).collect::<String>()
+        )
+    }
+}
+
+/// AuthProvicderInfo is the previous, misspelled, name of AuthProviderInfo.
+#[deprecated(note = "use AuthProviderInfo")]
+pub type AuthProvicderInfo = AuthProviderInfo;
+
+/// PasswordAuthMethod is the password auth of a collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordAuthMethod {
+    pub enabled: bool,
+    /// fields accepted as identity, eg. `email` or `username`.
+    pub identity_fields: Vec<String>,
+}
+
+/// DurationAuthMethod is the MFA or OTP auth of a collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DurationAuthMethod {
+    pub enabled: bool,
+    /// validity in seconds of the MFA session or of the OTP.
+    pub duration: i64,
+}
+
 /// ListAuthMethod is the model for a list auth method.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+/// the `v0.23` response, with `password` and `oauth2` sections, and the older one,
+/// with `usernamePassword`, `emailPassword` and `authProviders`, fill all the fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "ListAuthMethodData")]
 pub struct ListAuthMethod {
     pub username_password: bool,
     pub email_password: bool,
-    pub auth_providers: Vec<AuthProvicderInfo>,
+    pub auth_providers: Vec<AuthProviderInfo>,
+    pub password: PasswordAuthMethod,
+    pub oauth2_enabled: bool,
+    pub mfa: DurationAuthMethod,
+    pub otp: DurationAuthMethod,
+}
+
+impl ListAuthMethod {
+    /// returns the auth provider with the given name.
+    pub fn provider(&self, name: &str) -> Option<&AuthProviderInfo> {
+        self.auth_providers
+            .iter()
+            .find(|provider| provider.name == name)
+    }
+}
+
+// ListAuthMethodData is a list auth method response of any API version.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ListAuthMethodData {
+    username_password: Option<bool>,
+    email_password: Option<bool>,
+    auth_providers: Vec<AuthProviderInfo>,
+    password: Option<PasswordAuthMethod>,
+    // only set by a serialized ListAuthMethod.
+    oauth2_enabled: Option<bool>,
+    oauth2: Option<OAuth2AuthMethodData>,
+    mfa: DurationAuthMethod,
+    otp: DurationAuthMethod,
+}
+
+// OAuth2AuthMethodData is the `oauth2` section since `v0.23`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct OAuth2AuthMethodData {
+    enabled: bool,
+    providers: Vec<AuthProviderInfo>,
+}
+
+impl From<ListAuthMethodData> for ListAuthMethod {
+    fn from(data: ListAuthMethodData) -> Self {
+        let username_password = data.username_password.unwrap_or(false);
+        let email_password = data.email_password.unwrap_or(false);
+        let password = data.password.unwrap_or_else(|| PasswordAuthMethod {
+            enabled: username_password || email_password,
+            identity_fields: [(email_password, "email"), (username_password, "username")]
+                .into_iter()
+                .filter(|(enabled, _)| *enabled)
+                .map(|(_, field)| field.to_string())
+                .collect(),
+        });
+        let has_field = |field: &str| {
+            password.enabled && password.identity_fields.iter().any(|name| name == field)
+        };
+        let (oauth2_enabled, auth_providers) = match data.oauth2 {
+            Some(oauth2) => (oauth2.enabled, oauth2.providers),
+            None => (
+                data.oauth2_enabled
+                    .unwrap_or(!data.auth_providers.is_empty()),
+                data.auth_providers,
+            ),
+        };
+
+        ListAuthMethod {
+            username_password: data
+                .username_password
+                .unwrap_or_else(|| has_field("username")),
+            email_password: data.email_password.unwrap_or_else(|| has_field("email")),
+            auth_providers,
+            password,
+            oauth2_enabled,
+            mfa: data.mfa,
+            otp: data.otp,
+        }
+    }
 }
 
-/// RecordListAuthMethodsResponse is the response for the list auth methods.
+/// RecordListAuthMethodsConfig is the config for the list auth methods.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RecordListAuthMethodsConfig {
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordAuthWithPasswordConfig is the config for the auth with password.
@@ -46,11 +169,16 @@ pub struct RecordListAuthMethodsConfig {
 pub struct RecordAuthWithPasswordConfig<T> {
     pub identity: String,
     pub password: String,
+    /// id of the MFA challenge returned by the first auth.
+    #[serde(rename = "mfaId", skip_serializing_if = "Option::is_none")]
+    pub mfa_id: Option<String>,
     #[serde(flatten)]
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
     #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
     pub without_saving: bool,
 }
 
@@ -63,14 +191,83 @@ pub struct RecordAuthWithOAuth2Config<T> {
     pub code_verifier: String,
     #[serde(rename = "redirectUrl")]
     pub redirect_url: String,
+    /// id of the MFA challenge returned by the first auth.
+    #[serde(rename = "mfaId", skip_serializing_if = "Option::is_none")]
+    pub mfa_id: Option<String>,
+    #[serde(flatten)]
+    pub body: T,
+    #[serde(skip)]
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
+    pub without_saving: bool,
+}
+
+/// RecordRequestOTPConfig is the config for the request otp.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordRequestOTPConfig<T> {
+    pub email: String,
     #[serde(flatten)]
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
     #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+/// RecordRequestOTPResponse is the response for the request otp.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordRequestOTPResponse {
+    pub otp_id: String,
+}
+
+/// RecordAuthWithOTPConfig is the config for the auth with otp.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordAuthWithOTPConfig<T> {
+    #[serde(rename = "otpId")]
+    pub otp_id: String,
+    /// the one-time password sent by email.
+    pub password: String,
+    /// id of the MFA challenge returned by the first auth.
+    #[serde(rename = "mfaId", skip_serializing_if = "Option::is_none")]
+    pub mfa_id: Option<String>,
+    #[serde(flatten)]
+    pub body: T,
+    #[serde(skip)]
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
     pub without_saving: bool,
 }
 
+/// RecordAuthOutcome is the outcome of an auth that may require a second factor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordAuthOutcome<T> {
+    /// the auth is complete, the token is saved unless `without_saving` is set.
+    Authenticated(T),
+    /// the first factor is valid, authenticate again with another method and the `mfa_id`.
+    MfaRequired { mfa_id: String },
+}
+
+impl<T> RecordAuthOutcome<T> {
+    /// converts the result of an auth method, the MFA challenge error becomes `MfaRequired`.
+    pub fn from_result(result: Result<T, RPocketError>) -> Result<Self, RPocketError> {
+        match result {
+            Ok(response) => Ok(RecordAuthOutcome::Authenticated(response)),
+            Err(RPocketError::APIError(error)) => match error.data["mfaId"].as_str() {
+                Some(mfa_id) if error.code == 401 => Ok(RecordAuthOutcome::MfaRequired {
+                    mfa_id: mfa_id.to_string(),
+                }),
+                _ => Err(RPocketError::APIError(error)),
+            },
+            Err(error) => Err(error),
+        }
+    }
+}
+
 /// RecordAuthRefreshConfig is the config for the auth refresh.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RecordAuthRefreshConfig<T> {
@@ -79,6 +276,8 @@ pub struct RecordAuthRefreshConfig<T> {
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
     #[serde(skip)]
+    pub options: RequestOptions,
+    #[serde(skip)]
     pub without_saving: bool,
 }
 
@@ -90,6 +289,8 @@ pub struct RecordRequestPasswordResetConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordConfirmPasswordResetConfig is the config for the confirm password reset.
@@ -103,6 +304,8 @@ pub struct RecordConfirmPasswordResetConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordRequestVerificationConfig is the config for the request verification.
@@ -113,6 +316,8 @@ pub struct RecordRequestVerificationConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordConfirmVerificationConfig is the config for the confirm verification.
@@ -123,6 +328,8 @@ pub struct RecordConfirmVerificationConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordRequestEmailChangeConfig is the config for the request email change.
@@ -134,6 +341,8 @@ pub struct RecordRequestEmailChangeConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordConfirmEmailChangeConfig is the config for the confirm email change.
@@ -145,6 +354,8 @@ pub struct RecordConfirmEmailChangeConfig<T> {
     pub body: T,
     #[serde(skip)]
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordListExternalAuthsConfig is the config for the list external auths.
@@ -152,6 +363,8 @@ pub struct RecordConfirmEmailChangeConfig<T> {
 pub struct RecordListExternalAuthsConfig {
     pub id: String,
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
 }
 
 /// RecordUnlinkExternalAuthConfig is the config for the unlink external auth.
@@ -160,13 +373,271 @@ pub struct RecordUnlinkExternalAuthConfig {
     pub id: String,
     pub provider: String,
     pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+/// RecordUpsertConfig is the config for the upsert by and find or create methods.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordUpsertConfig<T> {
+    /// fields of the body that identify the record, eg. a unique `slug`.
+    #[serde(skip)]
+    pub fields: Vec<String>,
+    /// filter that finds the record, used instead of `fields`.
+    #[serde(skip)]
+    pub filter: Option<String>,
+    #[serde(flatten)]
+    pub body: T,
+    #[serde(skip)]
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip)]
+    pub options: RequestOptions,
+}
+
+/// RecordUpsertResponse is the response for the upsert by and find or create methods.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordUpsertResponse<T> {
+    pub record: T,
+    /// whether the record was created, `false` if an existing record was found.
+    pub created: bool,
+}
+
+impl SavedAuthResponse for RecordAuthResponse<Record> {
+    fn auth_payload(&self) -> (&str, AuthPayload) {
+        (&self.token, AuthPayload::User(self.record.clone()))
+    }
+}
+
+// RecordRequests builds the record requests of the async and the blocking services.
+pub(crate) struct RecordRequests<'a> {
+    pub(crate) collection: &'a str,
+}
+
+impl<'a> RecordRequests<'a> {
+    fn path<'s>(&'s self, segments: &[&'s str]) -> Vec<&'s str> {
+        let mut path = vec!["api", "collections", self.collection];
+        path.extend_from_slice(segments);
+        path
+    }
+
+    fn action<'c, B>(
+        &self,
+        action: &str,
+        body: &B,
+        query_params: &[(String, String)],
+        options: &'c RequestOptions,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        ApiRequest::new(reqwest::Method::POST, &self.path(&[action]), options)
+            .query(query_params)
+            .json(body)
+    }
+
+    pub(crate) fn list_auth_methods<'c>(
+        &self,
+        config: &'c RecordListAuthMethodsConfig,
+    ) -> ApiRequest<'c> {
+        ApiRequest::new(
+            reqwest::Method::GET,
+            &self.path(&["auth-methods"]),
+            &config.options,
+        )
+        .query(&config.query_params)
+    }
+
+    pub(crate) fn auth_with_password<'c, B>(
+        &self,
+        config: &'c RecordAuthWithPasswordConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "auth-with-password",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn request_otp<'c, B>(
+        &self,
+        config: &'c RecordRequestOTPConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action("request-otp", config, &config.query_params, &config.options)
+    }
+
+    pub(crate) fn auth_with_otp<'c, B>(
+        &self,
+        config: &'c RecordAuthWithOTPConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "auth-with-otp",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn auth_with_oauth2<'c, B>(
+        &self,
+        config: &'c RecordAuthWithOAuth2Config<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "auth-with-oauth2",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn auth_refresh<'c, B>(
+        &self,
+        config: &'c RecordAuthRefreshConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "auth-refresh",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn request_password_reset<'c, B>(
+        &self,
+        config: &'c RecordRequestPasswordResetConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "request-password-reset",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn confirm_password_reset<'c, B>(
+        &self,
+        config: &'c RecordConfirmPasswordResetConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "confirm-password-reset",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn request_verification<'c, B>(
+        &self,
+        config: &'c RecordRequestVerificationConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "request-verification",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn confirm_verification<'c, B>(
+        &self,
+        config: &'c RecordConfirmVerificationConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "confirm-verification",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn request_email_change<'c, B>(
+        &self,
+        config: &'c RecordRequestEmailChangeConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "request-email-change",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn confirm_email_change<'c, B>(
+        &self,
+        config: &'c RecordConfirmEmailChangeConfig<B>,
+    ) -> Result<ApiRequest<'c>, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.action(
+            "confirm-email-change",
+            config,
+            &config.query_params,
+            &config.options,
+        )
+    }
+
+    pub(crate) fn list_external_auths<'c>(
+        &self,
+        config: &'c RecordListExternalAuthsConfig,
+    ) -> ApiRequest<'c> {
+        ApiRequest::new(
+            reqwest::Method::GET,
+            &self.path(&["records", &config.id, "external-auths"]),
+            &config.options,
+        )
+        .query(&config.query_params)
+    }
+
+    pub(crate) fn unlink_external_auth<'c>(
+        &self,
+        config: &'c RecordUnlinkExternalAuthConfig,
+    ) -> ApiRequest<'c> {
+        ApiRequest::new(
+            reqwest::Method::DELETE,
+            &self.path(&["records", &config.id, "external-auths", &config.provider]),
+            &config.options,
+        )
+        .query(&config.query_params)
+    }
 }
 
 /// RecordService is the service for the record.
 pub struct RecordService<'a, C> {
-    client: &'a mut C,
+    client: &'a C,
     collection: &'a str,
-    record_base_path: String,
+    record_base_path: [&'a str; 4],
+    requests: RecordRequests<'a>,
 }
 
 impl<'a, C> RecordService<'a, C>
@@ -174,361 +645,550 @@ where
     C: crate::rpocket::PocketBaseClient + Sized,
 {
     /// create a new RecordService.
-    pub fn new(client: &'a mut C, collection: &'a str) -> Self {
+    pub fn new(client: &'a C, collection: &'a str) -> Self {
         RecordService {
             client,
             collection,
-            record_base_path: format!("api/collections/{}/records", collection),
+            record_base_path: ["api", "collections", collection, "records"],
+            requests: RecordRequests { collection },
         }
     }
 
     /// returns crud service.
-    pub fn crud(&'a mut self) -> service::crud::CRUDService<'a, C> {
+    pub fn crud(&self) -> service::crud::CRUDService<'_, C> {
         self.client.crud(&self.record_base_path)
     }
 
-    /// list auth methods
-    pub async fn list_auth_methods<T>(
-        &mut self,
-        config: &RecordListAuthMethodsConfig,
-    ) -> Result<T, RPocketError>
+    /// imports records from NDJSON, one json object per line.
+    /// values are coerced to the schema field types and invalid rows are reported, not fatal.
+    pub async fn import_ndjson<R>(
+        &self,
+        reader: R,
+        config: &service::bulk::RecordImportConfig,
+    ) -> Result<service::bulk::RecordImportReport, RPocketError>
     where
-        T: serde::de::DeserializeOwned,
+        R: std::io::BufRead,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/auth-methods", self.collection).as_str())?;
+        let rows = service::bulk::ndjson_rows(reader);
+        service::bulk::import_rows(self.client, self.collection, rows, config).await
+    }
 
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::GET, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params);
+    /// imports records from a CSV with a header row naming the columns.
+    /// values are coerced to the schema field types and invalid rows are reported, not fatal.
+    #[cfg(feature = "csv")]
+    pub async fn import_csv<R>(
+        &self,
+        reader: R,
+        config: &service::bulk::RecordImportConfig,
+    ) -> Result<service::bulk::RecordImportReport, RPocketError>
+    where
+        R: std::io::Read,
+    {
+        let rows = service::bulk::csv_rows(reader)?;
+        service::bulk::import_rows(self.client, self.collection, rows, config).await
+    }
+
+    /// updates the record matching `config.fields` or `config.filter`, or creates it if none matches.
+    /// a create that fails because a concurrent request created the record retries the lookup.
+    pub async fn upsert_by<T, B>(
+        &self,
+        config: &RecordUpsertConfig<B>,
+    ) -> Result<RecordUpsertResponse<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let filter = self.upsert_filter(config)?;
+        let mutate = |id: Option<String>| service::crud::CRUDMutateConfig {
+            id,
+            body: &config.body,
+            query_params: config.query_params.clone(),
+            options: config.options.clone(),
+        };
 
-        let response = self.client.http().send(request_builder).await?;
+        if let Some(existing) = self.find_first(&filter, config).await? {
+            let record = self.crud().mutate(&mutate(Some(existing.base.id))).await?;
+            return Ok(RecordUpsertResponse {
+                record,
+                created: false,
+            });
+        }
 
-        Ok(response.json::<T>().await?)
+        match self.crud().mutate(&mutate(None)).await {
+            Ok(record) => Ok(RecordUpsertResponse {
+                record,
+                created: true,
+            }),
+            Err(error) => match self.find_after_conflict(&error, &filter, config).await? {
+                Some(existing) => Ok(RecordUpsertResponse {
+                    record: self.crud().mutate(&mutate(Some(existing.base.id))).await?,
+                    created: false,
+                }),
+                None => Err(error),
+            },
+        }
     }
 
-    async fn save_auth_response<T>(
-        &mut self,
-        response: reqwest::Response,
-    ) -> Result<T, RPocketError>
+    /// returns the record matching `config.fields` or `config.filter`, or creates it if none matches.
+    /// a create that fails because a concurrent request created the record retries the lookup.
+    pub async fn find_or_create<T, B>(
+        &self,
+        config: &RecordUpsertConfig<B>,
+    ) -> Result<RecordUpsertResponse<T>, RPocketError>
     where
         T: serde::de::DeserializeOwned,
+        B: Serialize,
     {
-        let auth_state = self.client.auth_state();
-        let auth_response = response.json::<RecordAuthResponse<Record>>().await?;
+        let filter = self.upsert_filter(config)?;
 
-        let token = auth_response.token;
-        let meta = auth_response.meta;
-        let user = service::auth_state::AuthPayload::User(auth_response.record);
+        if let Some(existing) = self.find_first(&filter, config).await? {
+            return Ok(RecordUpsertResponse {
+                record: serde_json::from_value(serde_json::to_value(existing)?)?,
+                created: false,
+            });
+        }
 
-        auth_state.save(token.as_str(), &user).await?;
+        let created = self
+            .crud()
+            .mutate(&service::crud::CRUDMutateConfig {
+                id: None,
+                body: &config.body,
+                query_params: config.query_params.clone(),
+                options: config.options.clone(),
+            })
+            .await;
 
-        let record = match user {
-            service::auth_state::AuthPayload::User(user) => user,
-            _ => unreachable!(),
-        };
+        match created {
+            Ok(record) => Ok(RecordUpsertResponse {
+                record,
+                created: true,
+            }),
+            Err(error) => match self.find_after_conflict(&error, &filter, config).await? {
+                Some(existing) => Ok(RecordUpsertResponse {
+                    record: serde_json::from_value(serde_json::to_value(existing)?)?,
+                    created: false,
+                }),
+                None => Err(error),
+            },
+        }
+    }
+
+    // builds the lookup filter from the config filter or the values of the fields in the body.
+    fn upsert_filter<B>(&self, config: &RecordUpsertConfig<B>) -> Result<String, RPocketError>
+    where
+        B: Serialize,
+    {
+        if let Some(ref filter) = config.filter {
+            return Ok(filter.clone());
+        }
+        if config.fields.is_empty() {
+            return Err(RPocketError::ConfigError(
+                "upsert needs fields or a filter".to_string(),
+            ));
+        }
 
-        let auth_response = RecordAuthResponse {
-            token,
-            record,
-            meta,
+        let body = serde_json::to_value(&config.body)?;
+        let conditions = config
+            .fields
+            .iter()
+            .map(|field| match body.get(field) {
+                Some(value) if value.is_array() || value.is_object() => {
+                    Err(RPocketError::ConfigError(format!(
+                        "upsert field {} must be a string, a number or a bool",
+                        field
+                    )))
+                }
+                Some(serde_json::Value::String(value)) => {
+                    Ok(format!("{} = {}", field, crate::filter::quote(value)))
+                }
+                Some(value) if !value.is_null() => Ok(format!("{} = {}", field, value)),
+                _ => Err(RPocketError::ConfigError(format!(
+                    "upsert field {} is missing from the body",
+                    field
+                ))),
+            })
+            .collect::<Result<Vec<_>, RPocketError>>()?;
+
+        Ok(conditions.join(" && "))
+    }
+
+    async fn find_first<B>(
+        &self,
+        filter: &str,
+        config: &RecordUpsertConfig<B>,
+    ) -> Result<Option<Record>, RPocketError> {
+        let mut query_params = vec![
+            ("filter".to_string(), filter.to_string()),
+            ("skipTotal".to_string(), "1".to_string()),
+        ];
+        query_params.extend(config.query_params.iter().cloned());
+
+        let list: crate::model::ListResult<Record> = self
+            .crud()
+            .get_list(&service::crud::CRUDGetListConfig {
+                per_page: 1,
+                page: 1,
+                query_params,
+                options: config.options.clone(),
+            })
+            .await?;
+
+        Ok(list.items.into_iter().next())
+    }
+
+    // a concurrent create of the same record fails the unique index validation of an upsert
+    // field, any field with a filter, the record it created is looked up again.
+    async fn find_after_conflict<B>(
+        &self,
+        error: &RPocketError,
+        filter: &str,
+        config: &RecordUpsertConfig<B>,
+    ) -> Result<Option<Record>, RPocketError> {
+        let data = match error {
+            RPocketError::APIError(error) if error.code == 400 => error.data.as_object(),
+            _ => None,
         };
+        let conflict = data.into_iter().flatten().any(|(field, error)| {
+            error["code"] == "validation_not_unique"
+                && (config.filter.is_some() || config.fields.contains(field))
+        });
+
+        match conflict {
+            true => self.find_first(filter, config).await,
+            false => Ok(None),
+        }
+    }
+
+    /// runs the create, update and delete operations of the config.
+    /// results are in the order of the operations.
+    pub async fn batch<R, B>(
+        &self,
+        config: &service::batch::RecordBatchConfig<B>,
+    ) -> Result<Vec<service::batch::RecordBatchResult<R>>, RPocketError>
+    where
+        R: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        service::batch::batch(self.client, self.collection, config).await
+    }
 
-        let auth_response = serde_json::to_value(&auth_response)?;
-        let response = serde_json::from_value(auth_response)?;
+    /// exports the records of the collection page by page, see `RecordExportConfig` for the formats.
+    /// returns the number of exported records.
+    pub async fn export_to<W>(
+        &self,
+        writer: W,
+        config: &service::bulk::RecordExportConfig,
+    ) -> Result<usize, RPocketError>
+    where
+        W: std::io::Write,
+    {
+        service::bulk::export_records(self.client, self.collection, writer, config).await
+    }
 
-        Ok(response)
+    /// same as `export_to` for an async writer.
+    pub async fn export_to_async<W>(
+        &self,
+        writer: W,
+        config: &service::bulk::RecordExportConfig,
+    ) -> Result<usize, RPocketError>
+    where
+        W: futures::AsyncWrite + Unpin,
+    {
+        service::bulk::export_records_async(self.client, self.collection, writer, config).await
+    }
+
+    /// list the auth methods of the collection, eg. the OAuth2 providers.
+    pub async fn list_auth_methods(
+        &self,
+        config: &RecordListAuthMethodsConfig,
+    ) -> Result<ListAuthMethod, RPocketError> {
+        let response = self
+            .client
+            .http()
+            .execute(&self.requests.list_auth_methods(config))
+            .await?;
+
+        Ok(response.json::<ListAuthMethod>().await?)
     }
 
     /// authenticate with password
     pub async fn auth_with_password<T, B>(
-        &mut self,
+        &self,
         config: &RecordAuthWithPasswordConfig<B>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/auth-with-password", self.collection).as_str())?;
+        let request = self.requests.auth_with_password(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+            .await
+    }
 
-        let request_builder = self
+    /// authenticate with password, the MFA challenge of the first factor is
+    /// `RecordAuthOutcome::MfaRequired`, see `auth_with_otp_mfa`.
+    pub async fn auth_with_password_mfa<T, B>(
+        &self,
+        config: &RecordAuthWithPasswordConfig<B>,
+    ) -> Result<RecordAuthOutcome<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        RecordAuthOutcome::from_result(self.auth_with_password(config).await)
+    }
+
+    /// sends a one-time password to the email of the auth record.
+    pub async fn request_otp<B>(
+        &self,
+        config: &RecordRequestOTPConfig<B>,
+    ) -> Result<RecordRequestOTPResponse, RPocketError>
+    where
+        B: Serialize,
+    {
+        let response = self
             .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
+            .http()
+            .execute(&self.requests.request_otp(config)?)
+            .await?;
 
-        let response = self.client.http().send(request_builder).await?;
+        Ok(response.json::<RecordRequestOTPResponse>().await?)
+    }
 
-        if !config.without_saving {
-            return self.save_auth_response::<T>(response).await;
-        }
+    /// authenticate with a one-time password.
+    pub async fn auth_with_otp<T, B>(
+        &self,
+        config: &RecordAuthWithOTPConfig<B>,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        let request = self.requests.auth_with_otp(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+            .await
+    }
 
-        Ok(response.json::<T>().await?)
+    /// authenticate with a one-time password, the MFA challenge of the first factor is
+    /// `RecordAuthOutcome::MfaRequired`. the second factor sets `config.mfa_id` to the
+    /// `mfa_id` of the challenge.
+    pub async fn auth_with_otp_mfa<T, B>(
+        &self,
+        config: &RecordAuthWithOTPConfig<B>,
+    ) -> Result<RecordAuthOutcome<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        RecordAuthOutcome::from_result(self.auth_with_otp(config).await)
     }
 
     /// authenticate with oauth2
     pub async fn auth_with_oauth2<T, B>(
-        &mut self,
+        &self,
         config: &RecordAuthWithOAuth2Config<B>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/auth-with-oauth2", self.collection).as_str())?;
+        let request = self.requests.auth_with_oauth2(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+            .await
+    }
 
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
+    /// authenticate with oauth2, the MFA challenge of the first factor is
+    /// `RecordAuthOutcome::MfaRequired`, see `auth_with_otp_mfa`.
+    pub async fn auth_with_oauth2_mfa<T, B>(
+        &self,
+        config: &RecordAuthWithOAuth2Config<B>,
+    ) -> Result<RecordAuthOutcome<T>, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+    {
+        RecordAuthOutcome::from_result(self.auth_with_oauth2(config).await)
+    }
+
+    /// authenticate with oauth2 in a desktop or command-line app, the redirect of the provider
+    /// is received by a listener on localhost. `open_url` is called with the url of the provider
+    /// to open it in a browser or print it.
+    pub async fn auth_with_oauth2_flow<T, B, F>(
+        &self,
+        config: &service::oauth2::RecordAuthWithOAuth2FlowConfig<B>,
+        open_url: F,
+    ) -> Result<T, RPocketError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: Serialize,
+        F: FnOnce(&str) -> Result<(), RPocketError>,
+    {
+        let auth_methods = self
+            .list_auth_methods(&RecordListAuthMethodsConfig {
+                options: config.options.clone(),
+                ..Default::default()
+            })
+            .await?;
+        let provider = auth_methods.provider(&config.provider).ok_or_else(|| {
+            RPocketError::ConfigError(format!("unknown auth provider {}", config.provider))
+        })?;
 
-        let response = self.client.http().send(request_builder).await?;
+        let listener =
+            service::oauth2::RedirectListener::bind(config.redirect_port, &config.redirect_path)?;
+        let redirect_url = listener.redirect_url().to_string();
 
-        if !config.without_saving {
-            return self.save_auth_response::<T>(response).await;
-        }
+        open_url(&provider.auth_url_with_redirect(&redirect_url))?;
+
+        let code = listener.wait(&provider.state, config.timeout).await?;
 
-        Ok(response.json::<T>().await?)
+        self.auth_with_oauth2(&config.auth_config(provider, code, redirect_url))
+            .await
     }
 
     /// refreshes the current authenticated record instance and
     pub async fn auth_refresh<T, B>(
-        &mut self,
+        &self,
         config: &RecordAuthRefreshConfig<B>,
     ) -> Result<T, RPocketError>
     where
         T: serde::de::DeserializeOwned,
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/auth-refresh", self.collection).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        let response = self.client.http().send(request_builder).await?;
-
-        if !config.without_saving {
-            return self.save_auth_response::<T>(response).await;
-        }
-
-        Ok(response.json::<T>().await?)
+        let request = self.requests.auth_refresh(config)?;
+        self.client
+            .http()
+            .execute_auth::<RecordAuthResponse<Record>, T>(&request, config.without_saving)
+            .await
     }
 
     /// sends auth record password reset request.
     pub async fn request_password_reset<B>(
-        &mut self,
+        &self,
         config: &RecordRequestPasswordResetConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/request-password-reset", self.collection).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
+        self.client
+            .http()
+            .execute(&self.requests.request_password_reset(config)?)
+            .await?;
 
-        self.client.http().send(request_builder).await?;
         Ok(())
     }
 
     /// confirms auth record password reset request.
     pub async fn confirm_password_reset<B>(
-        &mut self,
+        &self,
         config: &RecordConfirmPasswordResetConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/confirm-password-reset", self.collection).as_str())?;
+        self.client
+            .http()
+            .execute(&self.requests.confirm_password_reset(config)?)
+            .await?;
 
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
         Ok(())
     }
 
     /// sends auth record verification email request.
     pub async fn request_verification<B>(
-        &mut self,
+        &self,
         config: &RecordRequestVerificationConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/request-verification", self.collection).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        self.client
+            .http()
+            .execute(&self.requests.request_verification(config)?)
+            .await?;
 
         Ok(())
     }
 
     /// confirms auth record verification email request.
     pub async fn confirm_verification<B>(
-        &mut self,
+        &self,
         config: &RecordConfirmVerificationConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/confirm-verification", self.collection).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        self.client
+            .http()
+            .execute(&self.requests.confirm_verification(config)?)
+            .await?;
 
         Ok(())
     }
 
     /// sends an email change request to the authenticated record model.
     pub async fn request_email_change<B>(
-        &mut self,
+        &self,
         config: &RecordRequestEmailChangeConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/request-email-change", self.collection).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        self.client
+            .http()
+            .execute(&self.requests.request_email_change(config)?)
+            .await?;
 
         Ok(())
     }
 
     /// confirms auth record's new email address.
     pub async fn confirm_email_change<B>(
-        &mut self,
+        &self,
         config: &RecordConfirmEmailChangeConfig<B>,
     ) -> Result<(), RPocketError>
     where
         B: Serialize,
     {
-        let url = self
-            .client
-            .base_url()
-            .join(format!("api/collections/{}/confirm-email-change", self.collection).as_str())?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::POST, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params)
-            .json(&config);
-
-        self.client.http().send(request_builder).await?;
+        self.client
+            .http()
+            .execute(&self.requests.confirm_email_change(config)?)
+            .await?;
 
         Ok(())
     }
 
     /// lists all linked external auth providers for the specified auth record.
     pub async fn list_external_auths<T>(
-        &mut self,
+        &self,
         config: &RecordListExternalAuthsConfig,
     ) -> Result<Vec<T>, RPocketError>
     where
         T: serde::de::DeserializeOwned,
     {
-        let url = self.client.base_url().join(
-            format!(
-                "api/collections/{}/records/{}/external-auths",
-                self.collection, config.id
-            )
-            .as_str(),
-        )?;
-
-        let request_builder = self
+        let response = self
             .client
-            .request_builder(reqwest::Method::GET, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params);
-
-        let response = self.client.http().send(request_builder).await?;
+            .http()
+            .execute(&self.requests.list_external_auths(config))
+            .await?;
 
         Ok(response.json::<Vec<T>>().await?)
     }
 
     /// unlink a single external auth provider from the specified auth record.
     pub async fn unlink_external_auth(
-        &mut self,
+        &self,
         config: &RecordUnlinkExternalAuthConfig,
     ) -> Result<(), RPocketError> {
-        let url = self.client.base_url().join(
-            format!(
-                "api/collections/{}/records/{}/external-auths/{}",
-                self.collection, config.id, config.provider
-            )
-            .as_str(),
-        )?;
-
-        let request_builder = self
-            .client
-            .request_builder(reqwest::Method::DELETE, url.as_str())
-            .header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
-            .query(&config.query_params);
-
-        self.client.http().send(request_builder).await?;
+        self.client
+            .http()
+            .execute(&self.requests.unlink_external_auth(config))
+            .await?;
 
         Ok(())
     }
@@ -544,15 +1204,15 @@ mod test {
 
     #[test]
     fn test_record_crud() {
-        let mut base = PocketBase::new("http://test.com", "en");
-        let record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new("http://test.com", "en");
+        let record_service = RecordService::new(&base, "test");
 
-        assert!(record_service.record_base_path == "api/collections/test/records");
+        assert!(record_service.record_base_path == ["api", "collections", "test", "records"]);
     }
 
     #[tokio::test]
     async fn test_record_list_auth_methods() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -594,15 +1254,14 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordListAuthMethodsConfig {
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
-        let response = record_service
-            .list_auth_methods::<ListAuthMethod>(&config)
-            .await;
+        let response = record_service.list_auth_methods(&config).await;
         mock.assert_async().await;
         let response = response.unwrap();
 
@@ -619,12 +1278,77 @@ mod test {
         assert!(response.auth_providers[0].code_challenge_method == "S256");
         assert!(
             response.auth_providers[0].auth_url
-                == "https://github.com/login/oauth/authorize?client_id=demo&code_challenge=NM1oVexB6Q6QH8uPtOUfK7tq4pmu4Jz6lNDIwoxHZNE%3D&code_challenge_method=S256&response_type=code&scope=user&state=3Yd8jNkK_6PJG6hPWwBjLqKwse6Ejd&redirect_uri=")
+                == "https://github.com/login/oauth/authorize?client_id=demo&code_challenge=NM1oVexB6Q6QH8uPtOUfK7tq4pmu4Jz6lNDIwoxHZNE%3D&code_challenge_method=S256&response_type=code&scope=user&state=3Yd8jNkK_6PJG6hPWwBjLqKwse6Ejd&redirect_uri=");
+        assert!(response.password.enabled);
+        assert!(response.password.identity_fields == vec!["email".to_string()]);
+        assert!(response.oauth2_enabled);
+        assert!(response.provider("gitlab").unwrap().is_pkce());
+        assert!(response.provider("twitter").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_record_list_auth_methods_v0_23() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mock = server
+            .mock("GET", "/api/collections/users/auth-methods")
+            .with_status(200)
+            .with_body(
+                r#"{
+  "password": {"enabled": true, "identityFields": ["email", "username"]},
+  "oauth2": {
+    "enabled": true,
+    "providers": [{
+      "name": "github",
+      "displayName": "GitHub",
+      "state": "s1",
+      "authURL": "https://github.com/login/oauth/authorize?client_id=demo&state=s1&redirect_uri=",
+      "codeVerifier": "",
+      "codeChallenge": "",
+      "codeChallengeMethod": ""
+    }]
+  },
+  "mfa": {"enabled": true, "duration": 1800},
+  "otp": {"enabled": false, "duration": 180}
+}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let response = RecordService::new(&base, "users")
+            .list_auth_methods(&RecordListAuthMethodsConfig::default())
+            .await;
+        mock.assert_async().await;
+        let response = response.unwrap();
+
+        assert!(response.username_password);
+        assert!(response.email_password);
+        assert!(response.oauth2_enabled);
+        assert!(response.mfa.enabled && response.mfa.duration == 1800);
+        assert!(!response.otp.enabled);
+
+        // a serialized ListAuthMethod reads back the same.
+        let disabled = ListAuthMethod {
+            oauth2_enabled: false,
+            ..response.clone()
+        };
+        let value = serde_json::to_value(&disabled).unwrap();
+        assert!(serde_json::from_value::<ListAuthMethod>(value).unwrap() == disabled);
+
+        let github = response.provider("github").unwrap();
+        assert!(github.display_name == "GitHub");
+        assert!(!github.is_pkce());
+        assert!(
+            github.auth_url_with_redirect("http://127.0.0.1:8400/")
+                == "https://github.com/login/oauth/authorize?client_id=demo&state=s1&redirect_uri=http%3A%2F%2F127.0.0.1%3A8400%2F"
+        );
     }
 
     #[tokio::test]
     async fn test_record_auth_with_password() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -653,8 +1377,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordAuthWithPasswordConfig::<HashMap<String, String>> {
             identity: String::from_str("test").unwrap(),
             password: String::from_str("12345678").unwrap(),
@@ -709,7 +1433,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_auth_with_oauth2() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -744,8 +1468,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordAuthWithOAuth2Config::<HashMap<String, String>> {
             provider: String::from_str("google").unwrap(),
             code: String::from_str("12345678").unwrap(),
@@ -782,9 +1506,121 @@ mod test {
         assert!(meta["avatarUrl"] == "https://example.com/avatar.png");
     }
 
+    #[tokio::test]
+    async fn test_record_auth_with_oauth2_flow() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        server
+            .mock("GET", "/api/collections/users/auth-methods")
+            .with_status(200)
+            .with_body(
+                r#"{"usernamePassword": false, "emailPassword": true, "authProviders": [{
+  "name": "github",
+  "state": "s1",
+  "codeVerifier": "v1",
+  "codeChallenge": "c1",
+  "codeChallengeMethod": "S256",
+  "authUrl": "https://github.com/login/oauth/authorize?client_id=demo&state=s1&redirect_uri="
+}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let auth_mock = server
+            .mock("POST", "/api/collections/users/auth-with-oauth2")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "provider": "github",
+                "code": "abc",
+                "codeVerifier": "v1",
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"token": "oauth2-token", "record": {"id": "8171022dc95a4ed", "collectionId": "_pb_users_auth_", "collectionName": "users", "created": "", "updated": ""}}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "users");
+        let config = service::oauth2::RecordAuthWithOAuth2FlowConfig::<HashMap<String, String>> {
+            provider: "github".to_string(),
+            redirect_path: "/callback".to_string(),
+            ..Default::default()
+        };
+
+        // the provider redirects the browser to the listener.
+        let redirect = |state: &'static str, status: &'static str| {
+            move |auth_url: &str| {
+                let redirect_url = auth_url.split_once("redirect_uri=").unwrap().1;
+                let redirect_url = url::Url::parse(
+                    &url::form_urlencoded::parse(redirect_url.as_bytes())
+                        .map(|(key, _)| key)
+                        .collect::<String>(),
+                )
+                .unwrap();
+                std::thread::spawn(move || {
+                    use std::io::{Read, Write};
+                    let address = (
+                        redirect_url.host_str().unwrap(),
+                        redirect_url.port().unwrap(),
+                    );
+                    let mut favicon = std::net::TcpStream::connect(address).unwrap();
+                    favicon
+                        .write_all(b"GET /favicon.ico HTTP/1.1\r\n\r\n")
+                        .unwrap();
+                    let mut response = String::new();
+                    favicon.read_to_string(&mut response).unwrap();
+                    assert!(response.starts_with("HTTP/1.1 404"));
+
+                    let mut stream = std::net::TcpStream::connect(address).unwrap();
+                    write!(
+                        stream,
+                        "GET {}?code=abc&state={} HTTP/1.1\r\n\r\n",
+                        redirect_url.path(),
+                        state
+                    )
+                    .unwrap();
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response).unwrap();
+                    assert!(response.starts_with(status));
+                });
+                Ok(())
+            }
+        };
+
+        // a forged state is refused and the flow keeps waiting until the timeout.
+        let forged_config = service::oauth2::RecordAuthWithOAuth2FlowConfig {
+            timeout: std::time::Duration::from_millis(500),
+            ..config.clone()
+        };
+        let response = record_service
+            .auth_with_oauth2_flow::<RecordAuthResponse<Record>, _, _>(
+                &forged_config,
+                redirect("forged", "HTTP/1.1 400"),
+            )
+            .await;
+        assert!(response.is_err());
+
+        let response = record_service
+            .auth_with_oauth2_flow::<RecordAuthResponse<Record>, _, _>(
+                &config,
+                redirect("s1", "HTTP/1.1 200"),
+            )
+            .await
+            .unwrap();
+
+        auth_mock.assert_async().await;
+        assert_eq!(response.token, "oauth2-token");
+        assert_eq!(
+            base.auth_state().get_token().await.unwrap().unwrap(),
+            "oauth2-token"
+        );
+    }
+
     #[tokio::test]
     async fn test_record_auth_refresh() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -813,8 +1649,8 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordAuthRefreshConfig::<HashMap<String, String>> {
             body: HashMap::new(),
             query_params: Vec::new(),
@@ -843,7 +1679,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_request_password_reset() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -855,12 +1691,13 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordRequestPasswordResetConfig::<HashMap<String, String>> {
             email: String::from_str("example@example.com").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -873,7 +1710,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_confirm_password_reset() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -885,14 +1722,15 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordConfirmPasswordResetConfig::<HashMap<String, String>> {
             token: String::from_str("test").unwrap(),
             password: String::from_str("test").unwrap(),
             password_confirm: String::from_str("test").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -905,7 +1743,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_request_verification() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -917,12 +1755,13 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordRequestVerificationConfig::<HashMap<String, String>> {
             email: String::from_str("example@example.com").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -935,7 +1774,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_confirm_verification() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -947,12 +1786,13 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordConfirmVerificationConfig::<HashMap<String, String>> {
             token: String::from_str("test").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -965,7 +1805,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_request_email_change() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -977,12 +1817,13 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordRequestEmailChangeConfig::<HashMap<String, String>> {
             new_email: String::from_str("example@example.com").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -995,7 +1836,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_confirm_email_change() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -1007,13 +1848,14 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordConfirmEmailChangeConfig::<HashMap<String, String>> {
             token: String::from_str("test").unwrap(),
             password: String::from_str("test").unwrap(),
             body: HashMap::new(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -1026,7 +1868,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_list_external_auths() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -1058,11 +1900,12 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordListExternalAuthsConfig {
             id: String::from_str("test").unwrap(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service
@@ -1083,7 +1926,7 @@ mod test {
 
     #[tokio::test]
     async fn test_record_unlink_external_auth() {
-        let mut server = mockito::Server::new();
+        let mut server = mockito::Server::new_async().await;
         let url = server.url();
 
         let mock = server
@@ -1097,12 +1940,13 @@ mod test {
             .create_async()
             .await;
 
-        let mut base = PocketBase::new(url.as_str(), "en");
-        let mut record_service = RecordService::new(&mut base, "test");
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "test");
         let config = RecordUnlinkExternalAuthConfig {
             id: String::from_str("test").unwrap(),
             provider: String::from_str("test").unwrap(),
             query_params: Vec::new(),
+            options: Default::default(),
         };
 
         let response = record_service.unlink_external_auth(&config).await;
@@ -1110,4 +1954,256 @@ mod test {
         mock.assert_async().await;
         response.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_record_upsert_by() {
+        let fake = crate::testing::FakePocketBase::start().await.unwrap();
+        fake.create_collection(serde_json::json!({
+            "name": "tags",
+            "schema": [{"name": "slug", "type": "text"}, {"name": "label", "type": "text"}],
+            "listRule": "",
+            "createRule": "",
+            "updateRule": "",
+        }))
+        .unwrap();
+
+        let base = PocketBase::new(fake.url(), "en");
+        let record_service = RecordService::new(&base, "tags");
+        let config = |label: &str| RecordUpsertConfig {
+            fields: vec!["slug".to_string()],
+            body: serde_json::json!({"slug": "rust", "label": label}),
+            ..Default::default()
+        };
+
+        let response: RecordUpsertResponse<Record> =
+            record_service.upsert_by(&config("Rust")).await.unwrap();
+        assert!(response.created);
+        let id = response.record.base.id;
+
+        let response: RecordUpsertResponse<Record> = record_service
+            .upsert_by(&config("Rust lang"))
+            .await
+            .unwrap();
+        assert!(!response.created);
+        assert_eq!(response.record.base.id, id);
+        assert_eq!(response.record.data["label"], "Rust lang");
+
+        let response: RecordUpsertResponse<Record> = record_service
+            .find_or_create(&config("Other"))
+            .await
+            .unwrap();
+        assert!(!response.created);
+        assert_eq!(response.record.data["label"], "Rust lang");
+
+        // backslashes and newlines are matched as written.
+        let config = |label: &str| RecordUpsertConfig {
+            fields: vec!["slug".to_string()],
+            body: serde_json::json!({"slug": "c:\\rust\n\"x\"", "label": label}),
+            ..Default::default()
+        };
+        let response: RecordUpsertResponse<Record> =
+            record_service.upsert_by(&config("A")).await.unwrap();
+        assert!(response.created);
+        let response: RecordUpsertResponse<Record> =
+            record_service.upsert_by(&config("B")).await.unwrap();
+        assert!(!response.created);
+        assert_eq!(response.record.data["label"], "B");
+
+        let response: RecordUpsertResponse<Record> = record_service
+            .find_or_create(&RecordUpsertConfig {
+                filter: Some("slug = 'go'".to_string()),
+                body: serde_json::json!({"slug": "go"}),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(response.created);
+
+        let error = record_service
+            .upsert_by::<Record, _>(&RecordUpsertConfig {
+                fields: vec!["slug".to_string()],
+                body: serde_json::json!({"label": "x"}),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RPocketError::ConfigError(_)));
+
+        let error = record_service
+            .upsert_by::<Record, _>(&RecordUpsertConfig {
+                fields: vec!["slug".to_string()],
+                body: serde_json::json!({"slug": ["rust"]}),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RPocketError::ConfigError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_record_find_or_create_conflict() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let mut list = |body: &str| {
+            server
+                .mock("GET", "/api/collections/tags/records")
+                .match_query(mockito::Matcher::UrlEncoded(
+                    "filter".into(),
+                    "slug = \"rust\"".into(),
+                ))
+                .with_status(200)
+                .with_body(format!(
+                    r#"{{"page":1,"perPage":1,"totalItems":-1,"totalPages":-1,"items":[{}]}}"#,
+                    body
+                ))
+                .expect(1)
+        };
+
+        let empty_mock = list("").create_async().await;
+        let found_mock = list(
+            r#"{"id":"a","created":"","updated":"","collectionId":"b","collectionName":"tags","slug":"rust"}"#,
+        ).create_async().await;
+        let create_mock = server
+            .mock("POST", "/api/collections/tags/records")
+            .with_status(400)
+            .with_body(
+                r#"{"code":400,"message":"Failed to create record.","data":{"slug":{"code":"validation_not_unique","message":"Value must be unique."}}}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "tags");
+        let response: RecordUpsertResponse<Record> = record_service
+            .find_or_create(&RecordUpsertConfig {
+                fields: vec!["slug".to_string()],
+                body: serde_json::json!({"slug": "rust"}),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        empty_mock.assert_async().await;
+        found_mock.assert_async().await;
+        create_mock.assert_async().await;
+        assert!(!response.created);
+        assert_eq!(response.record.base.id, "a");
+    }
+
+    #[tokio::test]
+    async fn test_record_find_or_create_invalid() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let list_mock = server
+            .mock("GET", "/api/collections/tags/records")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"page":1,"perPage":1,"totalItems":-1,"totalPages":-1,"items":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let create_mock = server
+            .mock("POST", "/api/collections/tags/records")
+            .with_status(400)
+            .with_body(
+                r#"{"code":400,"message":"Failed to create record.","data":{"label":{"code":"validation_required","message":"Cannot be blank."}}}"#,
+            )
+            .create_async()
+            .await;
+
+        // a validation error that is not a unique conflict is not retried.
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "tags");
+        let error = record_service
+            .find_or_create::<Record, _>(&RecordUpsertConfig {
+                fields: vec!["slug".to_string()],
+                body: serde_json::json!({"slug": "rust"}),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+
+        list_mock.assert_async().await;
+        create_mock.assert_async().await;
+        assert!(matches!(error, RPocketError::APIError(error) if error.code == 400));
+    }
+
+    #[tokio::test]
+    async fn test_record_auth_with_otp_mfa() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let password_mock = server
+            .mock("POST", "/api/collections/users/auth-with-password")
+            .match_body(r#"{"identity":"test@example.com","password":"12345678"}"#)
+            .with_status(401)
+            .with_body(
+                r#"{"code":401,"message":"Please complete the MFA authentication.","data":{"mfaId":"mfa000000000001"}}"#,
+            )
+            .create_async()
+            .await;
+        let request_otp_mock = server
+            .mock("POST", "/api/collections/users/request-otp")
+            .match_body(r#"{"email":"test@example.com"}"#)
+            .with_status(200)
+            .with_body(r#"{"otpId":"otp000000000001"}"#)
+            .create_async()
+            .await;
+        let otp_mock = server
+            .mock("POST", "/api/collections/users/auth-with-otp")
+            .match_body(
+                r#"{"otpId":"otp000000000001","password":"123456","mfaId":"mfa000000000001"}"#,
+            )
+            .with_status(200)
+            .with_body(
+                r#"{"token":"token","record":{"id":"user00000000001","created":"","updated":"","collectionId":"_pb_users_auth_","collectionName":"users","email":"test@example.com"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let base = PocketBase::new(url.as_str(), "en");
+        let record_service = RecordService::new(&base, "users");
+
+        let outcome: RecordAuthOutcome<RecordAuthResponse<Record>> = record_service
+            .auth_with_password_mfa(&RecordAuthWithPasswordConfig::<HashMap<String, String>> {
+                identity: "test@example.com".to_string(),
+                password: "12345678".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let mfa_id = match outcome {
+            RecordAuthOutcome::MfaRequired { mfa_id } => mfa_id,
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        };
+        assert!(base.auth_state().get_token().await.unwrap().is_none());
+
+        let otp = record_service
+            .request_otp(&RecordRequestOTPConfig::<HashMap<String, String>> {
+                email: "test@example.com".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let response: RecordAuthResponse<Record> = record_service
+            .auth_with_otp(&RecordAuthWithOTPConfig::<HashMap<String, String>> {
+                otp_id: otp.otp_id,
+                password: "123456".to_string(),
+                mfa_id: Some(mfa_id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        password_mock.assert_async().await;
+        request_otp_mock.assert_async().await;
+        otp_mock.assert_async().await;
+        assert!(response.record.base.id == "user00000000001");
+        assert_eq!(
+            base.auth_state().get_token().await.unwrap().unwrap(),
+            "token"
+        );
+    }
 }
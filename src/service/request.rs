@@ -0,0 +1,73 @@
+use serde::Serialize;
+
+use crate::error::RPocketError;
+use crate::service::http::RequestOptions;
+
+// ApiRequest is a json request to the API, the services build it once and the async and
+// the blocking http services only send it.
+pub(crate) struct ApiRequest<'a> {
+    pub(crate) method: reqwest::Method,
+    pub(crate) path: Vec<String>,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) body: Option<Vec<u8>>,
+    pub(crate) options: &'a RequestOptions,
+}
+
+impl<'a> ApiRequest<'a> {
+    pub(crate) fn new<S>(method: reqwest::Method, path: &[S], options: &'a RequestOptions) -> Self
+    where
+        S: AsRef<str>,
+    {
+        ApiRequest {
+            method,
+            path: path
+                .iter()
+                .map(|segment| segment.as_ref().to_string())
+                .collect(),
+            query: Vec::new(),
+            body: None,
+            options,
+        }
+    }
+
+    // appends query params, the params keep their order.
+    pub(crate) fn query(mut self, query: &[(String, String)]) -> Self {
+        self.query.extend_from_slice(query);
+        self
+    }
+
+    pub(crate) fn json<B>(mut self, body: &B) -> Result<Self, RPocketError>
+    where
+        B: Serialize,
+    {
+        self.body = Some(serde_json::to_vec(body)?);
+        Ok(self)
+    }
+
+    pub(crate) fn url(&self, base_url: &url::Url) -> Result<url::Url, RPocketError> {
+        let path = self.path.iter().map(String::as_str).collect::<Vec<_>>();
+        crate::rpocket::build_url(base_url, &path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_api_request() {
+        let options = RequestOptions::default();
+        let request = ApiRequest::new(reqwest::Method::POST, &["api", "a b"], &options)
+            .query(&[("expand".to_string(), "user".to_string())])
+            .json(&serde_json::json!({"title": "x"}))
+            .unwrap();
+
+        let base_url = url::Url::parse("http://localhost:8090/pb/").unwrap();
+        assert_eq!(
+            request.url(&base_url).unwrap().as_str(),
+            "http://localhost:8090/pb/api/a%20b"
+        );
+        assert_eq!(request.query, [("expand".to_string(), "user".to_string())]);
+        assert_eq!(request.body.unwrap(), br#"{"title":"x"}"#);
+    }
+}
@@ -69,7 +69,7 @@ impl Storage for MemoryStorage {
         let data = self.data.read().map_err(|_| RPocketError::MutexError)?;
         let inner_entry = data.get(key);
 
-        if let Some(..) = inner_entry {
+        if inner_entry.is_some() {
             drop(data);
             let mut data = self.data.write().map_err(|_| RPocketError::MutexError)?;
             data.remove(key);
@@ -79,6 +79,112 @@ impl Storage for MemoryStorage {
     }
 }
 
+// serializes the reads and writes of all the FileStorages of the process, so two
+// storages of the same file, eg. one passed to `with_storage`, don't lose updates.
+static FILE_STORAGE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+// numbers the temporary files of the process so each write has its own.
+static FILE_STORAGE_WRITES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// FileStorage is a Storage persisted as a json object in a file,
+/// it keeps the auth state between runs of a program.
+/// the file is read and written with blocking calls on the calling thread, it only holds
+/// a few tokens so an async runtime is blocked briefly.
+pub struct FileStorage {
+    path: std::path::PathBuf,
+}
+
+impl FileStorage {
+    /// create a new FileStorage, the file and its directory are created on the first write.
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        FileStorage { path: path.into() }
+    }
+
+    /// returns the path of the file.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<std::collections::HashMap<String, String>, RPocketError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(data) if data.trim().is_empty() => Ok(std::collections::HashMap::new()),
+            Ok(data) => Ok(serde_json::from_str(&data)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                Ok(std::collections::HashMap::new())
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn write(&self, data: &std::collections::HashMap<String, String>) -> Result<(), RPocketError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // the file holds auth tokens, it is created private to the owner and
+        // renamed into place so it is never readable by others, even briefly.
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            FILE_STORAGE_WRITES.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let result = options.open(&tmp_path).and_then(|mut file| {
+            use std::io::Write;
+            file.write_all(serde_json::to_string_pretty(data)?.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(error) = result.and_then(|_| std::fs::rename(&tmp_path, &self.path)) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for FileStorage {
+    /// get the value of a key.
+    async fn get(&self, key: &str) -> Result<Option<String>, RPocketError> {
+        let _lock = FILE_STORAGE_LOCK
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        Ok(self.read()?.remove(key))
+    }
+
+    /// set the value of a key.
+    async fn set(&self, key: &str, value: &str) -> Result<(), RPocketError> {
+        let _lock = FILE_STORAGE_LOCK
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        let mut data = self.read()?;
+        data.insert(key.to_string(), value.to_string());
+        self.write(&data)
+    }
+
+    /// delete a key.
+    async fn delete(&self, key: &str) -> Result<(), RPocketError> {
+        let _lock = FILE_STORAGE_LOCK
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        let mut data = self.read()?;
+        if data.remove(key).is_some() {
+            self.write(&data)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -95,4 +201,63 @@ mod test {
         storage.delete("key").await.unwrap();
         assert_eq!(storage.get("key").await.unwrap(), None);
     }
+
+    #[tokio::test]
+    async fn test_file_storage() {
+        let path = std::env::temp_dir()
+            .join(format!("rpocket-file-storage-{}", std::process::id()))
+            .join("auth.json");
+        let storage = FileStorage::new(&path);
+        assert_eq!(storage.get("key").await.unwrap(), None);
+
+        storage.set("key", "value").await.unwrap();
+        storage.set("other", "value2").await.unwrap();
+        assert_eq!(
+            FileStorage::new(&path).get("key").await.unwrap().unwrap(),
+            "value"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        storage.delete("key").await.unwrap();
+        assert_eq!(storage.get("key").await.unwrap(), None);
+        assert_eq!(storage.get("other").await.unwrap().unwrap(), "value2");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_file_storage_shared_file() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "rpocket-file-storage-shared-{}",
+                std::process::id()
+            ))
+            .join("auth.json");
+
+        // two storages of the same file written from several threads keep every key.
+        let handles = (0..8)
+            .map(|index| {
+                let storage = FileStorage::new(&path);
+                std::thread::spawn(move || {
+                    futures::executor::block_on(storage.set(&index.to_string(), "value"))
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        let storage = FileStorage::new(&path);
+        for index in 0..8 {
+            let value = futures::executor::block_on(storage.get(&index.to_string()));
+            assert_eq!(value.unwrap().unwrap(), "value");
+        }
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
 }
@@ -0,0 +1,583 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::error::RPocketError;
+use crate::rpocket::{
+    PocketBaseHTTPRequest, PocketBaseHTTPResponse, PocketBaseRequest, PocketBaseResponse,
+};
+
+/// CASSETTE_REDACTED_HEADERS are the headers never written to a cassette.
+pub const CASSETTE_REDACTED_HEADERS: [&str; 4] = [
+    "authorization",
+    "cookie",
+    "proxy-authorization",
+    "set-cookie",
+];
+
+/// CASSETTE_REDACTED_BODY_KEYS are the json keys whose values are replaced in the bodies
+/// written to a cassette, at any depth.
+pub const CASSETTE_REDACTED_BODY_KEYS: [&str; 7] = [
+    "password",
+    "passwordConfirm",
+    "oldPassword",
+    "token",
+    "secret",
+    "clientSecret",
+    "privateKey",
+];
+
+/// CASSETTE_REDACTED_VALUE replaces the redacted values of the bodies.
+pub const CASSETTE_REDACTED_VALUE: &str = "[redacted]";
+
+/// Cassette is the content of a cassette file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// Interaction is a recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// RecordedRequest is a request saved in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// the body is base64 encoded, it is not valid utf-8.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub body_base64: bool,
+}
+
+/// RecordedResponse is a response saved in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// the body is base64 encoded, it is not valid utf-8.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub body_base64: bool,
+}
+
+/// UnmatchedRequest is the error of a request that is not in the replayed cassette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmatchedRequest {
+    pub method: String,
+    pub url: String,
+}
+
+impl std::fmt::Display for UnmatchedRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no recorded response in the cassette for {} {}",
+            self.method, self.url
+        )
+    }
+}
+
+impl std::error::Error for UnmatchedRequest {}
+
+/// CassetteMode is the mode of a cassette layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// send the requests and save them with their responses.
+    Record,
+    /// answer the requests from the cassette without network.
+    Replay,
+}
+
+struct CassetteState {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+    // replayed interactions, an interaction is replayed once.
+    used: Mutex<Vec<bool>>,
+    // carries the rebuilt request builders, requests are executed by the inner service.
+    http_client: reqwest::Client,
+}
+
+/// CassetteLayer is a middleware recording requests to a cassette file or replaying them.
+///
+/// ```no_run
+/// use rpocket::{rpocket::PocketBaseBuilder, testing::cassette::CassetteLayer};
+///
+/// let pocket_base = PocketBaseBuilder::new()
+///     .base_url("http://127.0.0.1:8090")
+///     .layer(CassetteLayer::replay("tests/cassettes/posts.json").unwrap())
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct CassetteLayer {
+    state: Arc<CassetteState>,
+}
+
+impl CassetteLayer {
+    fn new(path: PathBuf, mode: CassetteMode, interactions: Vec<Interaction>) -> Self {
+        CassetteLayer {
+            state: Arc::new(CassetteState {
+                path,
+                mode,
+                used: Mutex::new(vec![false; interactions.len()]),
+                interactions: Mutex::new(interactions),
+                http_client: reqwest::Client::new(),
+            }),
+        }
+    }
+
+    /// create a layer recording to the cassette at path.
+    /// the file is overwritten after every request.
+    pub fn record<P: Into<PathBuf>>(path: P) -> Self {
+        Self::new(path.into(), CassetteMode::Record, Vec::new())
+    }
+
+    /// create a layer replaying the cassette at path.
+    pub fn replay<P: Into<PathBuf>>(path: P) -> Result<Self, RPocketError> {
+        let path = path.into();
+        let cassette: Cassette = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+
+        Ok(Self::new(path, CassetteMode::Replay, cassette.interactions))
+    }
+
+    /// returns the mode of the layer.
+    pub fn mode(&self) -> CassetteMode {
+        self.state.mode
+    }
+
+    /// returns the interactions of the cassette.
+    pub fn interactions(&self) -> Result<Vec<Interaction>, RPocketError> {
+        Ok(self
+            .state
+            .interactions
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?
+            .clone())
+    }
+}
+
+impl<S> tower::Layer<S> for CassetteLayer {
+    type Service = CassetteService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CassetteService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// CassetteService is the service created by `CassetteLayer`.
+#[derive(Clone)]
+pub struct CassetteService<S> {
+    inner: S,
+    state: Arc<CassetteState>,
+}
+
+impl<S> tower_service::Service<PocketBaseRequest> for CassetteService<S>
+where
+    S: tower_service::Service<
+            PocketBaseRequest,
+            Response = PocketBaseResponse,
+            Error = RPocketError,
+            Future = BoxFuture<'static, Result<PocketBaseResponse, RPocketError>>,
+        > + Clone
+        + Send
+        + 'static,
+{
+    type Response = PocketBaseResponse;
+    type Error = RPocketError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        match self.state.mode {
+            CassetteMode::Record => self.inner.poll_ready(cx),
+            CassetteMode::Replay => std::task::Poll::Ready(Ok(())),
+        }
+    }
+
+    fn call(&mut self, request: PocketBaseRequest) -> Self::Future {
+        let state = self.state.clone();
+        let PocketBaseRequest::HTTP(PocketBaseHTTPRequest { request_builder }) = request;
+
+        let request = match request_builder.build() {
+            Ok(request) => request,
+            Err(error) => return Box::pin(async move { Err(error.into()) }),
+        };
+        let recorded_request = record_request(&request);
+
+        if state.mode == CassetteMode::Replay {
+            return Box::pin(async move {
+                let response = state.replay(&recorded_request)?;
+                Ok(PocketBaseResponse::HTTP(PocketBaseHTTPResponse {
+                    response: build_response(&response)?,
+                }))
+            });
+        }
+
+        let request_builder =
+            reqwest::RequestBuilder::from_parts(state.http_client.clone(), request);
+        let future = self
+            .inner
+            .call(PocketBaseRequest::HTTP(PocketBaseHTTPRequest {
+                request_builder,
+            }));
+
+        Box::pin(async move {
+            let PocketBaseResponse::HTTP(PocketBaseHTTPResponse { response }) = future.await?;
+
+            let status = response.status().as_u16();
+            let headers = redact_headers(response.headers());
+            let (body, body_base64) = encode_body(&response.bytes().await?);
+
+            let recorded_response = RecordedResponse {
+                status,
+                headers,
+                body,
+                body_base64,
+            };
+            state.save(Interaction {
+                request: recorded_request,
+                response: recorded_response.clone(),
+            })?;
+
+            Ok(PocketBaseResponse::HTTP(PocketBaseHTTPResponse {
+                response: build_response(&recorded_response)?,
+            }))
+        })
+    }
+}
+
+impl CassetteState {
+    // appends the interaction and writes the cassette file.
+    fn save(&self, interaction: Interaction) -> Result<(), RPocketError> {
+        let mut interactions = self
+            .interactions
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        interactions.push(interaction);
+
+        let cassette = Cassette {
+            interactions: interactions.clone(),
+        };
+        std::fs::write(
+            &self.path,
+            format!("{}\n", serde_json::to_string_pretty(&cassette)?),
+        )?;
+
+        Ok(())
+    }
+
+    // returns the first response recorded for the request and not replayed yet.
+    fn replay(&self, request: &RecordedRequest) -> Result<RecordedResponse, RPocketError> {
+        let interactions = self
+            .interactions
+            .lock()
+            .map_err(|_| RPocketError::MutexError)?;
+        let mut used = self.used.lock().map_err(|_| RPocketError::MutexError)?;
+
+        let index = interactions
+            .iter()
+            .enumerate()
+            .position(|(index, interaction)| {
+                !used[index]
+                    && interaction.request.method == request.method
+                    && request_target(&interaction.request.url) == request_target(&request.url)
+                    && interaction.request.body.as_deref().unwrap_or_default()
+                        == request.body.as_deref().unwrap_or_default()
+            })
+            .ok_or_else(|| {
+                RPocketError::Error(Box::new(UnmatchedRequest {
+                    method: request.method.clone(),
+                    url: request.url.clone(),
+                }))
+            })?;
+
+        used[index] = true;
+
+        Ok(interactions[index].response.clone())
+    }
+}
+
+fn record_request(request: &reqwest::Request) -> RecordedRequest {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(encode_body);
+
+    RecordedRequest {
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        headers: redact_headers(request.headers()),
+        body_base64: body.as_ref().is_some_and(|(_, base64)| *base64),
+        body: body.map(|(body, _)| body),
+    }
+}
+
+// returns the path and the query of the url, the host of a test server changes between runs.
+fn request_target(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(url) => match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+// returns the body as text, with its secrets redacted, or as base64 if it isn't utf-8.
+fn encode_body(body: &[u8]) -> (String, bool) {
+    use base64::Engine;
+
+    let Ok(text) = std::str::from_utf8(body) else {
+        return (base64::engine::general_purpose::STANDARD.encode(body), true);
+    };
+
+    if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(text) {
+        if redact_body(&mut value) {
+            return (value.to_string(), false);
+        }
+    }
+
+    (text.to_string(), false)
+}
+
+// replaces the values of the secret keys, returns true if one was found.
+fn redact_body(value: &mut serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut redacted = false;
+            for (key, value) in map.iter_mut() {
+                if CASSETTE_REDACTED_BODY_KEYS.contains(&key.as_str()) {
+                    *value = serde_json::Value::String(CASSETTE_REDACTED_VALUE.to_string());
+                    redacted = true;
+                } else {
+                    redacted |= redact_body(value);
+                }
+            }
+            redacted
+        }
+        serde_json::Value::Array(values) => {
+            let mut redacted = false;
+            for value in values.iter_mut() {
+                redacted |= redact_body(value);
+            }
+            redacted
+        }
+        _ => false,
+    }
+}
+
+fn redact_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| !CASSETTE_REDACTED_HEADERS.contains(&name.as_str()))
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+fn build_response(recorded: &RecordedResponse) -> Result<reqwest::Response, RPocketError> {
+    let mut builder = http::Response::builder().status(recorded.status);
+
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name, value);
+    }
+
+    let body = match recorded.body_base64 {
+        true => {
+            use base64::Engine;
+            base64::engine::general_purpose::STANDARD
+                .decode(&recorded.body)
+                .map_err(|error| RPocketError::Error(Box::new(error)))?
+        }
+        false => recorded.body.clone().into_bytes(),
+    };
+
+    let response = builder
+        .body(body)
+        .map_err(|error| RPocketError::Error(Box::new(error)))?;
+
+    Ok(reqwest::Response::from(response))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::Record;
+    use crate::rpocket::{PocketBaseBuilder, PocketBaseClient, TOKEN_KEY};
+    use crate::service::crud::CRUDGetOneConfig;
+
+    #[tokio::test]
+    async fn test_cassette_record_and_replay() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let path = std::env::temp_dir().join(format!(
+            "rpocket-cassette-{}.json",
+            server.host_with_port().replace(':', "-")
+        ));
+
+        let mock = server
+            .mock("GET", "/api/collections/posts/records/1")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("set-cookie", "session=secret")
+            .with_body(r#"{"id": "1", "collectionId": "a", "collectionName": "posts"}"#)
+            .create_async()
+            .await;
+
+        let config = CRUDGetOneConfig {
+            id: "1".to_string(),
+            ..Default::default()
+        };
+
+        let layer = CassetteLayer::record(&path);
+        let base = PocketBaseBuilder::new()
+            .base_url(url.as_str())
+            .layer(layer.clone())
+            .build()
+            .unwrap();
+        base.storage().set(TOKEN_KEY, "secret").await.unwrap();
+
+        let recorded = base
+            .record("posts")
+            .crud()
+            .get_one::<Record>(&config)
+            .await
+            .unwrap();
+        mock.assert_async().await;
+
+        let interactions = layer.interactions().unwrap();
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].request.method, "GET");
+        assert!(interactions[0]
+            .request
+            .headers
+            .iter()
+            .all(|(name, _)| name != "authorization"));
+        assert!(interactions[0]
+            .response
+            .headers
+            .iter()
+            .all(|(name, _)| name != "set-cookie"));
+
+        // the server is gone, responses come from the cassette.
+        drop(server);
+
+        let base = PocketBaseBuilder::new()
+            .base_url(url.as_str())
+            .layer(CassetteLayer::replay(&path).unwrap())
+            .build()
+            .unwrap();
+
+        let replayed = base
+            .record("posts")
+            .crud()
+            .get_one::<Record>(&config)
+            .await
+            .unwrap();
+        assert_eq!(replayed, recorded);
+
+        let unmatched = base.record("posts").crud().get_one::<Record>(&config).await;
+        match unmatched {
+            Err(RPocketError::Error(error)) => {
+                assert!(error.downcast_ref::<UnmatchedRequest>().is_some())
+            }
+            _ => panic!("expected an unmatched request error"),
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cassette_redaction_and_binary_body() {
+        use crate::service::record::{RecordAuthResponse, RecordAuthWithPasswordConfig};
+        use std::collections::HashMap;
+
+        let mut server = mockito::Server::new_async().await;
+        let path = std::env::temp_dir().join(format!(
+            "rpocket-cassette-{}.json",
+            server.host_with_port().replace(':', "-")
+        ));
+        let file = vec![0u8, 159, 146, 150, 255];
+
+        server
+            .mock("POST", "/api/collections/users/auth-with-password")
+            .with_status(200)
+            .with_body(
+                r#"{"token": "real-token", "record": {"id": "1", "collectionId": "a", "collectionName": "users"}}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/files/a/1/avatar.png?thumb=100x100")
+            .with_status(200)
+            .with_body(&file)
+            .create_async()
+            .await;
+
+        let config = RecordAuthWithPasswordConfig::<HashMap<String, String>> {
+            identity: "test@example.com".to_string(),
+            password: "12345678".to_string(),
+            ..Default::default()
+        };
+        let download =
+            |base_url: &str| format!("{}/api/files/a/1/avatar.png?thumb=100x100", base_url);
+
+        let layer = CassetteLayer::record(&path);
+        let base = PocketBaseBuilder::new()
+            .base_url(server.url())
+            .layer(layer.clone())
+            .build()
+            .unwrap();
+        base.record("users")
+            .auth_with_password::<RecordAuthResponse<Record>, _>(&config)
+            .await
+            .unwrap();
+        let request_builder = base.request_builder(reqwest::Method::GET, &download(&server.url()));
+        base.http().send(request_builder).await.unwrap();
+
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(!cassette.contains("12345678"));
+        assert!(!cassette.contains("real-token"));
+        assert!(layer.interactions().unwrap()[1].response.body_base64);
+
+        // replayed on another host, eg. a new test server.
+        drop(server);
+        let base_url = "http://127.0.0.1:9";
+        let base = PocketBaseBuilder::new()
+            .base_url(base_url)
+            .layer(CassetteLayer::replay(&path).unwrap())
+            .build()
+            .unwrap();
+
+        let response = base
+            .record("users")
+            .auth_with_password::<RecordAuthResponse<Record>, _>(&config)
+            .await
+            .unwrap();
+        assert_eq!(response.token, CASSETTE_REDACTED_VALUE);
+
+        let request_builder = base.request_builder(reqwest::Method::GET, &download(base_url));
+        let response = base.http().send(request_builder).await.unwrap();
+        assert_eq!(response.bytes().await.unwrap().to_vec(), file);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Router,
+};
+use serde_json::{json, Value};
+
+use super::state::{Auth, FakeError, FakeState};
+
+pub(crate) type SharedState = Arc<Mutex<FakeState>>;
+
+type Answer = Result<Response, FakeError>;
+
+impl IntoResponse for FakeError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::BAD_REQUEST);
+        json_response(status, &self.to_json())
+    }
+}
+
+// returns the routes of the fake server.
+pub(crate) fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/api/health", get(health))
+        .route(
+            "/api/admins/auth-with-password",
+            post(admin_auth_with_password),
+        )
+        .route("/api/admins/auth-refresh", post(admin_auth_refresh))
+        .route(
+            "/api/collections",
+            get(list_collections).post(create_collection),
+        )
+        .route("/api/collections/import", put(import_collections))
+        .route(
+            "/api/collections/:collection",
+            get(get_collection)
+                .patch(update_collection)
+                .delete(delete_collection),
+        )
+        .route(
+            "/api/collections/:collection/records",
+            get(list_records).post(create_record),
+        )
+        .route(
+            "/api/collections/:collection/records/:id",
+            get(get_record).patch(update_record).delete(delete_record),
+        )
+        .route(
+            "/api/collections/:collection/auth-with-password",
+            post(record_auth_with_password),
+        )
+        .route(
+            "/api/collections/:collection/auth-refresh",
+            post(record_auth_refresh),
+        )
+        .route(
+            "/api/collections/:collection/auth-methods",
+            get(auth_methods),
+        )
+        .route("/api/settings", get(get_settings).patch(update_settings))
+        .fallback(|| async { FakeError::not_found() })
+        .with_state(state)
+}
+
+fn lock(state: &SharedState) -> MutexGuard<'_, FakeState> {
+    state.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+fn auth(state: &FakeState, headers: &HeaderMap) -> Auth {
+    state.auth(
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok()),
+    )
+}
+
+fn parse_body(body: &Bytes) -> Result<Value, FakeError> {
+    if body.is_empty() {
+        return Ok(json!({}));
+    }
+
+    serde_json::from_slice(body).map_err(|_| {
+        FakeError::bad_request("Failed to load the submitted data due to invalid formatting.")
+    })
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+fn ok(body: Value) -> Answer {
+    Ok(json_response(StatusCode::OK, &body))
+}
+
+fn no_content() -> Answer {
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn health() -> Answer {
+    ok(json!({"code": 200, "message": "API is healthy.", "data": {}}))
+}
+
+async fn admin_auth_with_password(State(state): State<SharedState>, body: Bytes) -> Answer {
+    ok(lock(&state).admin_auth_with_password(&parse_body(&body)?)?)
+}
+
+async fn admin_auth_refresh(State(state): State<SharedState>, headers: HeaderMap) -> Answer {
+    let state = lock(&state);
+    ok(state.admin_auth_refresh(&auth(&state, &headers))?)
+}
+
+async fn list_collections(
+    State(state): State<SharedState>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Answer {
+    let state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    ok(state.list_collections(&query)?)
+}
+
+async fn create_collection(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Answer {
+    let mut state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    ok(state.insert_collection(&parse_body(&body)?)?)
+}
+
+async fn import_collections(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Answer {
+    let mut state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    state.import_collections(&parse_body(&body)?)?;
+    no_content()
+}
+
+async fn get_collection(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    headers: HeaderMap,
+) -> Answer {
+    let state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    ok(state.get_collection(&collection)?)
+}
+
+async fn update_collection(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Answer {
+    let mut state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    ok(state.update_collection(&collection, &parse_body(&body)?)?)
+}
+
+async fn delete_collection(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    headers: HeaderMap,
+) -> Answer {
+    let mut state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    state.delete_collection(&collection)?;
+    no_content()
+}
+
+async fn list_records(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Answer {
+    let state = lock(&state);
+    let auth = auth(&state, &headers);
+    ok(state.list_records(&collection, &query, &auth)?)
+}
+
+async fn create_record(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Answer {
+    let mut state = lock(&state);
+    let auth = auth(&state, &headers);
+    ok(state.create_record(&collection, &parse_body(&body)?, &auth)?)
+}
+
+async fn get_record(
+    State(state): State<SharedState>,
+    Path((collection, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Answer {
+    let state = lock(&state);
+    let auth = auth(&state, &headers);
+    ok(state.get_record(&collection, &id, &auth)?)
+}
+
+async fn update_record(
+    State(state): State<SharedState>,
+    Path((collection, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Answer {
+    let mut state = lock(&state);
+    let auth = auth(&state, &headers);
+    ok(state.update_record(&collection, &id, &parse_body(&body)?, &auth)?)
+}
+
+async fn delete_record(
+    State(state): State<SharedState>,
+    Path((collection, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Answer {
+    let mut state = lock(&state);
+    let auth = auth(&state, &headers);
+    state.delete_record(&collection, &id, &auth)?;
+    no_content()
+}
+
+async fn record_auth_with_password(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> Answer {
+    ok(lock(&state).record_auth_with_password(&collection, &parse_body(&body)?)?)
+}
+
+async fn record_auth_refresh(
+    State(state): State<SharedState>,
+    Path(collection): Path<String>,
+    headers: HeaderMap,
+) -> Answer {
+    let state = lock(&state);
+    ok(state.record_auth_refresh(&collection, &auth(&state, &headers))?)
+}
+
+async fn auth_methods(State(state): State<SharedState>, Path(collection): Path<String>) -> Answer {
+    ok(lock(&state).auth_methods(&collection)?)
+}
+
+async fn get_settings(State(state): State<SharedState>, headers: HeaderMap) -> Answer {
+    let state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    ok(state.get_settings())
+}
+
+async fn update_settings(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Answer {
+    let mut state = lock(&state);
+    state.require_admin(&auth(&state, &headers))?;
+    ok(state.update_settings(&parse_body(&body)?))
+}
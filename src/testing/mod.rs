@@ -0,0 +1,332 @@
+pub mod cassette;
+mod handlers;
+mod sort;
+mod state;
+mod token;
+
+use std::sync::{Arc, Mutex};
+
+use crate::{error::RPocketError, model};
+
+pub use state::TOKEN_DURATION;
+
+/// FakePocketBase is an in-memory PocketBase server listening on a random local port.
+/// it implements the collections, records CRUD with pagination, filters and sort,
+/// admin and record auth with signed tokens, and the settings endpoints.
+/// collection rules are evaluated with [`crate::filter`]: `null` is admin only, an empty
+/// rule allows everyone and `@collection` references are not supported.
+/// the server is stopped when the FakePocketBase is dropped.
+pub struct FakePocketBase {
+    url: String,
+    state: handlers::SharedState,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl FakePocketBase {
+    /// starts a new server, it must be called inside a tokio runtime.
+    pub async fn start() -> Result<Self, RPocketError> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let state = Arc::new(Mutex::new(state::FakeState::new()));
+        let (shutdown, receiver) = tokio::sync::oneshot::channel::<()>();
+
+        let server = axum::Server::from_tcp(listener)
+            .map_err(|error| RPocketError::Error(Box::new(error)))?
+            .serve(handlers::router(state.clone()).into_make_service())
+            .with_graceful_shutdown(async {
+                receiver.await.ok();
+            });
+        tokio::spawn(server);
+
+        Ok(FakePocketBase {
+            url,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// returns the base url of the server.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// creates an admin.
+    pub fn create_admin(&self, email: &str, password: &str) -> Result<model::Admin, RPocketError> {
+        let admin = self.lock().insert_admin(email, password);
+        Ok(serde_json::from_value(admin)?)
+    }
+
+    /// creates a collection, missing fields get the PocketBase defaults.
+    pub fn create_collection(
+        &self,
+        collection: serde_json::Value,
+    ) -> Result<model::Collection, RPocketError> {
+        let collection = self.lock().insert_collection(&collection)?;
+        Ok(serde_json::from_value(collection)?)
+    }
+
+    /// creates a record, ignoring the collection create rule.
+    /// records of auth collections need `password` and `passwordConfirm`.
+    pub fn create_record(
+        &self,
+        collection: &str,
+        data: serde_json::Value,
+    ) -> Result<model::Record, RPocketError> {
+        let admin = state::Auth::Admin(String::new());
+        let record = self.lock().create_record(collection, &data, &admin)?;
+        Ok(serde_json::from_value(record)?)
+    }
+
+    /// returns the settings as answered by the settings endpoint.
+    pub fn settings(&self) -> Result<model::Settings, RPocketError> {
+        Ok(serde_json::from_value(self.lock().get_settings())?)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, state::FakeState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl Drop for FakePocketBase {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{Collection, ListResult, Record};
+    use crate::rpocket::{PocketBase, PocketBaseClient};
+    use crate::service::{
+        admin::{AdminAuthResponse, AdminAuthWithPasswordConfig},
+        crud::{CRUDDeleteConfig, CRUDGetListConfig, CRUDGetOneConfig, CRUDMutateConfig},
+        record::{RecordAuthResponse, RecordAuthWithPasswordConfig},
+        setting::{SettingGetAllConfig, SettingUpdateConfig},
+    };
+    use std::collections::HashMap;
+
+    async fn admin_client(fake: &FakePocketBase) -> PocketBase<crate::rpocket::PocketBaseService> {
+        fake.create_admin("admin@example.com", "password123")
+            .unwrap();
+
+        let base = PocketBase::new(fake.url(), "en");
+        base.admin()
+            .auth_with_password::<AdminAuthResponse, HashMap<String, String>>(
+                &AdminAuthWithPasswordConfig {
+                    identity: "admin@example.com".to_string(),
+                    password: "password123".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        base
+    }
+
+    #[tokio::test]
+    async fn test_fake_pocket_base_records() {
+        let fake = FakePocketBase::start().await.unwrap();
+        let base = admin_client(&fake).await;
+
+        let collection = base
+            .collection()
+            .crud()
+            .mutate::<Collection, _>(&CRUDMutateConfig {
+                body: serde_json::json!({"name": "posts", "listRule": ""}),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(collection.name, "posts");
+
+        for (title, views) in [("a", 3), ("b", 1), ("c", 2)] {
+            base.record("posts")
+                .crud()
+                .mutate::<Record, _>(&CRUDMutateConfig {
+                    body: serde_json::json!({"title": title, "views": views}),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let page: ListResult<Record> = base
+            .record("posts")
+            .crud()
+            .get_list(&CRUDGetListConfig {
+                per_page: 1,
+                page: 2,
+                query_params: vec![
+                    ("sort".to_string(), "-views".to_string()),
+                    ("filter".to_string(), "views >= 2".to_string()),
+                ],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total_items, 2);
+        assert_eq!(page.items[0].data["title"], "c");
+
+        let record = &page.items[0];
+        let updated = base
+            .record("posts")
+            .crud()
+            .mutate::<Record, _>(&CRUDMutateConfig {
+                id: Some(record.base.id.clone()),
+                body: serde_json::json!({"title": "d"}),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.data["title"], "d");
+        assert_eq!(updated.data["views"], 2);
+
+        base.record("posts")
+            .crud()
+            .delete(&CRUDDeleteConfig {
+                id: record.base.id.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let missing = base
+            .record("posts")
+            .crud()
+            .get_one::<Record>(&CRUDGetOneConfig {
+                id: record.base.id.clone(),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(missing, Err(RPocketError::APIError(error)) if error.code == 404));
+
+        // guests can list but only admins can create with a null rule.
+        let guest = PocketBase::new(fake.url(), "en");
+        let created = guest
+            .record("posts")
+            .crud()
+            .mutate::<Record, _>(&CRUDMutateConfig {
+                body: serde_json::json!({"title": "e"}),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(created, Err(RPocketError::APIError(error)) if error.code == 403));
+    }
+
+    #[tokio::test]
+    async fn test_fake_pocket_base_record_auth() {
+        let fake = FakePocketBase::start().await.unwrap();
+        fake.create_collection(serde_json::json!({
+            "name": "users",
+            "type": "auth",
+            "listRule": "id = @request.auth.id || verified = true",
+            "viewRule": "id = @request.auth.id",
+        }))
+        .unwrap();
+        let user = fake
+            .create_record(
+                "users",
+                serde_json::json!({
+                    "email": "user@example.com",
+                    "password": "password123",
+                    "passwordConfirm": "password123",
+                }),
+            )
+            .unwrap();
+
+        let base = PocketBase::new(fake.url(), "en");
+        let view = base
+            .record("users")
+            .crud()
+            .get_one::<Record>(&CRUDGetOneConfig {
+                id: user.base.id.clone(),
+                ..Default::default()
+            })
+            .await;
+        assert!(view.is_err());
+
+        let auth = base
+            .record("users")
+            .auth_with_password::<RecordAuthResponse<Record>, HashMap<String, String>>(
+                &RecordAuthWithPasswordConfig {
+                    identity: "user@example.com".to_string(),
+                    password: "password123".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(auth.record.base.id, user.base.id);
+
+        let view = base
+            .record("users")
+            .crud()
+            .get_one::<Record>(&CRUDGetOneConfig {
+                id: user.base.id.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(view.data["email"], "user@example.com");
+        assert!(!view.data.contains_key("password"));
+
+        let other = fake
+            .create_record(
+                "users",
+                serde_json::json!({"password": "password123", "passwordConfirm": "password123"}),
+            )
+            .unwrap();
+        let view = base
+            .record("users")
+            .crud()
+            .get_one::<Record>(&CRUDGetOneConfig {
+                id: other.base.id.clone(),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(view, Err(RPocketError::APIError(error)) if error.code == 404));
+
+        let list = base
+            .record("users")
+            .crud()
+            .get_list::<Record>(&CRUDGetListConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(list.total_items, 1);
+        assert_eq!(list.items[0].base.id, user.base.id);
+    }
+
+    #[tokio::test]
+    async fn test_fake_pocket_base_settings() {
+        let fake = FakePocketBase::start().await.unwrap();
+
+        let guest = PocketBase::new(fake.url(), "en");
+        let settings = guest
+            .setting()
+            .get_all::<model::Settings>(&SettingGetAllConfig::default())
+            .await;
+        assert!(matches!(settings, Err(RPocketError::APIError(error)) if error.code == 401));
+
+        let base = admin_client(&fake).await;
+        let settings = base
+            .setting()
+            .update::<model::Settings, _>(&SettingUpdateConfig {
+                body: serde_json::json!({"meta": {"appName": "Test"}, "smtp": {"password": "secret"}}),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(settings.meta.app_name, "Test");
+        assert_eq!(settings.meta.app_url, "http://localhost:8090");
+        assert_eq!(
+            settings.smtp.password,
+            crate::service::setting::SETTING_MASKED_VALUE
+        );
+        assert_eq!(fake.settings().unwrap(), settings);
+    }
+}
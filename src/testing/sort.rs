@@ -0,0 +1,57 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+// sorts the items by a comma separated list of fields, `-` sorts descending.
+pub(crate) fn sort(items: &mut [Map<String, Value>], sort: &str) {
+    let fields: Vec<(&str, bool)> = sort
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| match field.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (field.strip_prefix('+').unwrap_or(field), false),
+        })
+        .collect();
+
+    items.sort_by(|a, b| {
+        for (field, descending) in &fields {
+            let ordering = compare(
+                a.get(*field).unwrap_or(&Value::Null),
+                b.get(*field).unwrap_or(&Value::Null),
+            );
+            if ordering != Ordering::Equal {
+                return if *descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+            }
+        }
+        Ordering::Equal
+    });
+}
+
+// orders json values, null first, then booleans, numbers and strings.
+pub(crate) fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
@@ -0,0 +1,886 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use serde_json::{json, Map, Value};
+
+use super::{sort, token};
+use crate::{
+    error::APIError,
+    error::RPocketError,
+    filter::{self, Expr, FilterContext, FilterError},
+    model,
+    service::setting::SETTING_MASKED_VALUE,
+};
+
+/// TOKEN_DURATION is the lifetime in seconds of the tokens issued by the fake server.
+pub const TOKEN_DURATION: i64 = 1209600;
+
+const MAX_PER_PAGE: usize = 500;
+const ID_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+const ID_LENGTH: usize = 15;
+
+// FakeError is an error answered by the fake server.
+#[derive(Debug)]
+pub(crate) struct FakeError {
+    pub(crate) status: u16,
+    pub(crate) message: String,
+}
+
+impl FakeError {
+    pub(crate) fn bad_request<T: Into<String>>(message: T) -> Self {
+        FakeError {
+            status: 400,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn unauthorized() -> Self {
+        FakeError {
+            status: 401,
+            message: "The request requires valid authorization token to be set.".to_string(),
+        }
+    }
+
+    pub(crate) fn forbidden() -> Self {
+        FakeError {
+            status: 403,
+            message: "You are not allowed to perform this request.".to_string(),
+        }
+    }
+
+    pub(crate) fn not_found() -> Self {
+        FakeError {
+            status: 404,
+            message: "The requested resource wasn't found.".to_string(),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        json!({"code": self.status, "message": self.message, "data": {}})
+    }
+}
+
+impl From<FilterError> for FakeError {
+    fn from(error: FilterError) -> Self {
+        FakeError::bad_request(format!("Invalid filter: {}", error))
+    }
+}
+
+impl From<FakeError> for RPocketError {
+    fn from(error: FakeError) -> Self {
+        RPocketError::APIError(APIError {
+            code: error.status as i64,
+            message: error.message,
+            data: json!({}),
+        })
+    }
+}
+
+// Auth is the authenticated caller of a request.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Auth {
+    Guest,
+    Admin(String),
+    Record { collection_id: String, id: String },
+}
+
+struct StoredAdmin {
+    data: Map<String, Value>,
+    password: String,
+}
+
+struct StoredRecord {
+    data: Map<String, Value>,
+    password: Option<String>,
+}
+
+// FakeState is the in-memory database of the fake server.
+pub(crate) struct FakeState {
+    secret: Vec<u8>,
+    random: RandomState,
+    next_id: u64,
+    admins: Vec<StoredAdmin>,
+    collections: Vec<Map<String, Value>>,
+    // records by collection id.
+    records: HashMap<String, Vec<StoredRecord>>,
+    settings: Value,
+}
+
+impl FakeState {
+    pub(crate) fn new() -> Self {
+        let random = RandomState::new();
+        let secret = (0..4u64)
+            .flat_map(|i| hash(&random, i).to_le_bytes())
+            .collect();
+
+        let mut settings = model::Settings::default();
+        settings.meta.app_name = "Acme".to_string();
+        settings.meta.app_url = "http://localhost:8090".to_string();
+        settings.logs.max_days = 5;
+
+        FakeState {
+            secret,
+            random,
+            next_id: 0,
+            admins: Vec::new(),
+            collections: Vec::new(),
+            records: HashMap::new(),
+            settings: serde_json::to_value(settings).expect("settings are serializable"),
+        }
+    }
+
+    fn new_id(&mut self) -> String {
+        self.next_id += 1;
+
+        let mut seed = hash(&self.random, self.next_id);
+        (0..ID_LENGTH)
+            .map(|i| {
+                if i % 12 == 0 {
+                    seed = hash(&self.random, (self.next_id, seed));
+                }
+                let c = ID_ALPHABET[(seed % ID_ALPHABET.len() as u64) as usize];
+                seed /= ID_ALPHABET.len() as u64;
+                c as char
+            })
+            .collect()
+    }
+
+    fn new_base(&mut self, id: Option<&str>) -> Map<String, Value> {
+        let now = model::format_date(&chrono::Utc::now());
+        let id = match id {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => self.new_id(),
+        };
+
+        let mut data = Map::new();
+        data.insert("id".to_string(), json!(id));
+        data.insert("created".to_string(), json!(now));
+        data.insert("updated".to_string(), json!(now));
+        data
+    }
+
+    fn issue_token(&self, mut claims: Value) -> String {
+        claims["exp"] = json!(chrono::Utc::now().timestamp() + TOKEN_DURATION);
+        token::sign(&self.secret, &claims)
+    }
+
+    // returns the caller authenticated by the Authorization header value.
+    pub(crate) fn auth(&self, authorization: Option<&str>) -> Auth {
+        let token = match authorization {
+            Some(token) => token.strip_prefix("Bearer ").unwrap_or(token),
+            None => return Auth::Guest,
+        };
+
+        let claims = match token::verify(&self.secret, token) {
+            Some(claims) => claims,
+            None => return Auth::Guest,
+        };
+        let id = claims["id"].as_str().unwrap_or_default().to_string();
+
+        match claims["type"].as_str() {
+            Some("admin") if self.admin_index(&id).is_some() => Auth::Admin(id),
+            Some("authRecord") => {
+                let collection_id = claims["collectionId"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let exists = self
+                    .records
+                    .get(&collection_id)
+                    .map(|records| records.iter().any(|record| record.data["id"] == id))
+                    .unwrap_or(false);
+
+                if exists {
+                    Auth::Record { collection_id, id }
+                } else {
+                    Auth::Guest
+                }
+            }
+            _ => Auth::Guest,
+        }
+    }
+
+    pub(crate) fn require_admin(&self, auth: &Auth) -> Result<(), FakeError> {
+        match auth {
+            Auth::Admin(_) => Ok(()),
+            Auth::Guest => Err(FakeError::unauthorized()),
+            Auth::Record { .. } => Err(FakeError::forbidden()),
+        }
+    }
+
+    // admins
+
+    fn admin_index(&self, id: &str) -> Option<usize> {
+        self.admins.iter().position(|admin| admin.data["id"] == id)
+    }
+
+    pub(crate) fn insert_admin(&mut self, email: &str, password: &str) -> Value {
+        let mut data = self.new_base(None);
+        data.insert("avatar".to_string(), json!(0));
+        data.insert("email".to_string(), json!(email));
+
+        self.admins.push(StoredAdmin {
+            data: data.clone(),
+            password: password.to_string(),
+        });
+
+        Value::Object(data)
+    }
+
+    pub(crate) fn admin_auth_with_password(&self, body: &Value) -> Result<Value, FakeError> {
+        let admin = self
+            .admins
+            .iter()
+            .find(|admin| {
+                admin.data["email"] == body["identity"]
+                    && Some(admin.password.as_str()) == body["password"].as_str()
+            })
+            .ok_or_else(|| FakeError::bad_request("Failed to authenticate."))?;
+
+        Ok(self.admin_auth_response(&admin.data))
+    }
+
+    pub(crate) fn admin_auth_refresh(&self, auth: &Auth) -> Result<Value, FakeError> {
+        self.require_admin(auth)?;
+
+        let id = match auth {
+            Auth::Admin(id) => id,
+            _ => unreachable!(),
+        };
+        let index = self.admin_index(id).ok_or_else(FakeError::not_found)?;
+
+        Ok(self.admin_auth_response(&self.admins[index].data))
+    }
+
+    fn admin_auth_response(&self, admin: &Map<String, Value>) -> Value {
+        json!({
+            "token": self.issue_token(json!({"id": admin["id"], "type": "admin"})),
+            "admin": admin,
+        })
+    }
+
+    // collections
+
+    fn collection_index(&self, id_or_name: &str) -> Result<usize, FakeError> {
+        self.collections
+            .iter()
+            .position(|collection| {
+                collection["id"] == id_or_name || collection["name"] == id_or_name
+            })
+            .ok_or_else(FakeError::not_found)
+    }
+
+    pub(crate) fn list_collections(
+        &self,
+        query: &HashMap<String, String>,
+    ) -> Result<Value, FakeError> {
+        list_result(self.collections.clone(), query, &FilterContext::default())
+    }
+
+    pub(crate) fn get_collection(&self, id_or_name: &str) -> Result<Value, FakeError> {
+        Ok(Value::Object(
+            self.collections[self.collection_index(id_or_name)?].clone(),
+        ))
+    }
+
+    pub(crate) fn insert_collection(&mut self, body: &Value) -> Result<Value, FakeError> {
+        let name = body["name"].as_str().unwrap_or_default();
+        if name.is_empty() {
+            return Err(FakeError::bad_request("Failed to create record."));
+        }
+        if self.collection_index(name).is_ok() {
+            return Err(FakeError::bad_request(format!(
+                "The collection name {} is already in use.",
+                name
+            )));
+        }
+
+        let mut collection = self.new_base(body["id"].as_str());
+        for (key, default) in [
+            ("name", json!(name)),
+            ("type", json!(model::DEFAULT_COLLECTION_TYPE)),
+            ("system", json!(false)),
+            ("schema", json!([])),
+            ("indexes", json!([])),
+            ("listRule", Value::Null),
+            ("viewRule", Value::Null),
+            ("createRule", Value::Null),
+            ("updateRule", Value::Null),
+            ("deleteRule", Value::Null),
+            ("options", json!({})),
+        ] {
+            collection.insert(key.to_string(), body.get(key).cloned().unwrap_or(default));
+        }
+        let schema = self.fill_schema(&collection["schema"]);
+        collection.insert("schema".to_string(), schema);
+
+        self.records.insert(
+            collection["id"].as_str().unwrap_or_default().to_string(),
+            Vec::new(),
+        );
+        self.collections.push(collection.clone());
+
+        Ok(Value::Object(collection))
+    }
+
+    // fills the missing properties of the schema fields.
+    fn fill_schema(&mut self, schema: &Value) -> Value {
+        let fields = schema.as_array().cloned().unwrap_or_default();
+
+        Value::Array(
+            fields
+                .into_iter()
+                .map(|mut field| {
+                    if let Value::Object(ref mut field) = field {
+                        if !matches!(field.get("id"), Some(Value::String(id)) if !id.is_empty()) {
+                            field.insert("id".to_string(), json!(self.new_id()));
+                        }
+                        for (key, default) in [
+                            ("name", json!("")),
+                            ("type", json!("text")),
+                            ("system", json!(false)),
+                            ("required", json!(false)),
+                            ("options", json!({})),
+                        ] {
+                            field.entry(key).or_insert(default);
+                        }
+                    }
+                    field
+                })
+                .collect(),
+        )
+    }
+
+    pub(crate) fn update_collection(
+        &mut self,
+        id_or_name: &str,
+        body: &Value,
+    ) -> Result<Value, FakeError> {
+        let index = self.collection_index(id_or_name)?;
+        let schema = body.get("schema").map(|schema| self.fill_schema(schema));
+        let collection = &mut self.collections[index];
+
+        if let Value::Object(body) = body {
+            for (key, value) in body {
+                if !matches!(key.as_str(), "id" | "created" | "updated") {
+                    collection.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        if let Some(schema) = schema {
+            collection.insert("schema".to_string(), schema);
+        }
+        collection.insert(
+            "updated".to_string(),
+            json!(model::format_date(&chrono::Utc::now())),
+        );
+
+        Ok(Value::Object(collection.clone()))
+    }
+
+    pub(crate) fn delete_collection(&mut self, id_or_name: &str) -> Result<(), FakeError> {
+        let index = self.collection_index(id_or_name)?;
+        let collection = self.collections.remove(index);
+
+        if let Some(id) = collection["id"].as_str() {
+            self.records.remove(id);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn import_collections(&mut self, body: &Value) -> Result<(), FakeError> {
+        let collections = body["collections"]
+            .as_array()
+            .ok_or_else(|| FakeError::bad_request("Failed to import the collections."))?;
+
+        if body["deleteMissing"].as_bool().unwrap_or(false) {
+            let keep: Vec<&Value> = collections
+                .iter()
+                .flat_map(|collection| [&collection["id"], &collection["name"]])
+                .collect();
+            let missing: Vec<String> = self
+                .collections
+                .iter()
+                .filter(|collection| !keep.contains(&&collection["id"]))
+                .filter_map(|collection| collection["id"].as_str().map(str::to_string))
+                .collect();
+
+            for id in missing {
+                self.delete_collection(&id)?;
+            }
+        }
+
+        for collection in collections {
+            let existing = [&collection["id"], &collection["name"]]
+                .iter()
+                .filter_map(|key| key.as_str())
+                .find_map(|key| self.collection_index(key).ok());
+
+            match existing {
+                Some(index) => {
+                    let id = self.collections[index]["id"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string();
+                    self.update_collection(&id, collection)?;
+                }
+                None => {
+                    self.insert_collection(collection)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // records
+
+    // returns the parsed rule, `None` if the request is allowed for every record.
+    fn rule(&self, collection: usize, rule: &str, auth: &Auth) -> Result<Option<Expr>, FakeError> {
+        if let Auth::Admin(_) = auth {
+            return Ok(None);
+        }
+
+        match &self.collections[collection][rule] {
+            Value::String(rule) if rule.trim().is_empty() => Ok(None),
+            Value::String(rule) => Ok(Some(filter::parse(rule)?)),
+            _ => Err(FakeError::forbidden()),
+        }
+    }
+
+    // builds the `@request` context of a rule.
+    fn rule_context(
+        &self,
+        auth: &Auth,
+        method: &str,
+        body: Option<&Map<String, Value>>,
+    ) -> FilterContext {
+        let mut context = FilterContext::default().method(method);
+
+        if let Some(body) = body {
+            context = context.data(body.clone().into_iter().collect());
+        }
+
+        if let Auth::Record { collection_id, id } = auth {
+            let record = self
+                .records
+                .get(collection_id)
+                .and_then(|records| records.iter().find(|record| record.data["id"] == *id))
+                .and_then(|record| serde_json::from_value(Value::Object(record.data.clone())).ok());
+
+            if let Some(record) = record {
+                context = context.auth(record);
+            }
+        }
+
+        context
+    }
+
+    fn collection_records(&self, collection: usize) -> &Vec<StoredRecord> {
+        let id = self.collections[collection]["id"]
+            .as_str()
+            .unwrap_or_default();
+        self.records
+            .get(id)
+            .expect("records exist for every collection")
+    }
+
+    fn collection_records_mut(&mut self, collection: usize) -> &mut Vec<StoredRecord> {
+        let id = self.collections[collection]["id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        self.records
+            .get_mut(&id)
+            .expect("records exist for every collection")
+    }
+
+    fn record_index(&self, collection: usize, id: &str) -> Result<usize, FakeError> {
+        self.collection_records(collection)
+            .iter()
+            .position(|record| record.data["id"] == id)
+            .ok_or_else(FakeError::not_found)
+    }
+
+    fn is_auth_collection(&self, collection: usize) -> bool {
+        self.collections[collection]["type"] == "auth"
+    }
+
+    pub(crate) fn list_records(
+        &self,
+        collection: &str,
+        query: &HashMap<String, String>,
+        auth: &Auth,
+    ) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+        let rule = self.rule(collection, "listRule", auth)?;
+        let context = self.rule_context(auth, "GET", None);
+
+        let mut items = Vec::new();
+        for record in self.collection_records(collection) {
+            if allows(&rule, &record.data, &context)? {
+                items.push(record.data.clone());
+            }
+        }
+
+        list_result(items, query, &context)
+    }
+
+    pub(crate) fn get_record(
+        &self,
+        collection: &str,
+        id: &str,
+        auth: &Auth,
+    ) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+        let rule = self.rule(collection, "viewRule", auth)?;
+        let index = self.record_index(collection, id)?;
+
+        let data = &self.collection_records(collection)[index].data;
+        if !allows(&rule, data, &self.rule_context(auth, "GET", None))? {
+            return Err(FakeError::not_found());
+        }
+
+        Ok(Value::Object(data.clone()))
+    }
+
+    pub(crate) fn create_record(
+        &mut self,
+        collection: &str,
+        body: &Value,
+        auth: &Auth,
+    ) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+        let rule = self.rule(collection, "createRule", auth)?;
+
+        let mut body = match body {
+            Value::Object(body) => body.clone(),
+            _ => return Err(FakeError::bad_request("Failed to create record.")),
+        };
+        let context = self.rule_context(auth, "POST", Some(&body));
+        let mut data = self.new_base(body.get("id").and_then(Value::as_str));
+        if self
+            .record_index(collection, data["id"].as_str().unwrap_or_default())
+            .is_ok()
+        {
+            return Err(FakeError::bad_request("Failed to create record."));
+        }
+        data.insert(
+            "collectionId".to_string(),
+            self.collections[collection]["id"].clone(),
+        );
+        data.insert(
+            "collectionName".to_string(),
+            self.collections[collection]["name"].clone(),
+        );
+
+        let mut password = None;
+        if self.is_auth_collection(collection) {
+            password = Some(
+                take_password(&mut body)?
+                    .ok_or_else(|| FakeError::bad_request("Failed to create record."))?,
+            );
+
+            let username = format!("user{}", self.next_id);
+            for (key, default) in [
+                ("username", json!(username)),
+                ("email", json!("")),
+                ("emailVisibility", json!(false)),
+                ("verified", json!(false)),
+            ] {
+                data.insert(key.to_string(), default);
+            }
+        }
+
+        for (key, value) in body {
+            if !matches!(
+                key.as_str(),
+                "id" | "created" | "updated" | "collectionId" | "collectionName"
+            ) {
+                data.insert(key, value);
+            }
+        }
+
+        if !allows(&rule, &data, &context)? {
+            return Err(FakeError::bad_request("Failed to create record."));
+        }
+
+        self.collection_records_mut(collection).push(StoredRecord {
+            data: data.clone(),
+            password,
+        });
+
+        Ok(Value::Object(data))
+    }
+
+    pub(crate) fn update_record(
+        &mut self,
+        collection: &str,
+        id: &str,
+        body: &Value,
+        auth: &Auth,
+    ) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+        let rule = self.rule(collection, "updateRule", auth)?;
+        let index = self.record_index(collection, id)?;
+
+        let mut body = match body {
+            Value::Object(body) => body.clone(),
+            _ => return Err(FakeError::bad_request("Failed to update record.")),
+        };
+        let context = self.rule_context(auth, "PATCH", Some(&body));
+        if !allows(
+            &rule,
+            &self.collection_records(collection)[index].data,
+            &context,
+        )? {
+            return Err(FakeError::not_found());
+        }
+        let password = match self.is_auth_collection(collection) {
+            true => take_password(&mut body)?,
+            false => None,
+        };
+
+        let record = &mut self.collection_records_mut(collection)[index];
+        for (key, value) in body {
+            if !matches!(
+                key.as_str(),
+                "id" | "created" | "updated" | "collectionId" | "collectionName"
+            ) {
+                record.data.insert(key, value);
+            }
+        }
+        record.data.insert(
+            "updated".to_string(),
+            json!(model::format_date(&chrono::Utc::now())),
+        );
+        if password.is_some() {
+            record.password = password;
+        }
+
+        Ok(Value::Object(record.data.clone()))
+    }
+
+    pub(crate) fn delete_record(
+        &mut self,
+        collection: &str,
+        id: &str,
+        auth: &Auth,
+    ) -> Result<(), FakeError> {
+        let collection = self.collection_index(collection)?;
+        let rule = self.rule(collection, "deleteRule", auth)?;
+        let index = self.record_index(collection, id)?;
+
+        let context = self.rule_context(auth, "DELETE", None);
+        if !allows(
+            &rule,
+            &self.collection_records(collection)[index].data,
+            &context,
+        )? {
+            return Err(FakeError::not_found());
+        }
+
+        self.collection_records_mut(collection).remove(index);
+
+        Ok(())
+    }
+
+    pub(crate) fn record_auth_with_password(
+        &self,
+        collection: &str,
+        body: &Value,
+    ) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+        if !self.is_auth_collection(collection) {
+            return Err(FakeError::bad_request("Failed to authenticate."));
+        }
+
+        let record = self
+            .collection_records(collection)
+            .iter()
+            .find(|record| {
+                let identity = &body["identity"];
+                (record.data["email"] == *identity || record.data["username"] == *identity)
+                    && record.password.as_deref() == body["password"].as_str()
+            })
+            .ok_or_else(|| FakeError::bad_request("Failed to authenticate."))?;
+
+        Ok(self.record_auth_response(&record.data))
+    }
+
+    pub(crate) fn record_auth_refresh(
+        &self,
+        collection: &str,
+        auth: &Auth,
+    ) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+
+        match auth {
+            Auth::Record { collection_id, id }
+                if self.collections[collection]["id"] == *collection_id =>
+            {
+                let index = self.record_index(collection, id)?;
+                Ok(self.record_auth_response(&self.collection_records(collection)[index].data))
+            }
+            Auth::Guest => Err(FakeError::unauthorized()),
+            _ => Err(FakeError::forbidden()),
+        }
+    }
+
+    fn record_auth_response(&self, record: &Map<String, Value>) -> Value {
+        json!({
+            "token": self.issue_token(json!({
+                "id": record["id"],
+                "type": "authRecord",
+                "collectionId": record["collectionId"],
+            })),
+            "record": record,
+        })
+    }
+
+    pub(crate) fn auth_methods(&self, collection: &str) -> Result<Value, FakeError> {
+        let collection = self.collection_index(collection)?;
+        let is_auth = self.is_auth_collection(collection);
+
+        Ok(json!({
+            "usernamePassword": is_auth,
+            "emailPassword": is_auth,
+            "authProviders": [],
+        }))
+    }
+
+    // settings
+
+    pub(crate) fn get_settings(&self) -> Value {
+        let mut settings = self.settings.clone();
+        mask_secrets(&mut settings);
+        settings
+    }
+
+    pub(crate) fn update_settings(&mut self, body: &Value) -> Value {
+        merge_settings(&mut self.settings, body);
+        self.get_settings()
+    }
+}
+
+fn hash<T: Hash>(random: &RandomState, value: T) -> u64 {
+    random.hash_one(value)
+}
+
+// removes password and passwordConfirm from the body, they must match.
+fn take_password(body: &mut Map<String, Value>) -> Result<Option<String>, FakeError> {
+    let password = body.remove("password");
+    let password_confirm = body.remove("passwordConfirm");
+
+    match password {
+        Some(Value::String(password)) if !password.is_empty() => {
+            if password_confirm.as_ref().and_then(Value::as_str) != Some(password.as_str()) {
+                return Err(FakeError::bad_request("Failed to load the submitted data."));
+            }
+            Ok(Some(password))
+        }
+        _ => Ok(None),
+    }
+}
+
+// returns true if the rule allows the item, `None` allows everything.
+fn allows(
+    rule: &Option<Expr>,
+    item: &Map<String, Value>,
+    context: &FilterContext,
+) -> Result<bool, FakeError> {
+    match rule {
+        Some(rule) => Ok(rule.evaluate_value(&Value::Object(item.clone()), context)?),
+        None => Ok(true),
+    }
+}
+
+// filters, sorts and paginates the items like the list endpoints.
+fn list_result(
+    items: Vec<Map<String, Value>>,
+    query: &HashMap<String, String>,
+    context: &FilterContext,
+) -> Result<Value, FakeError> {
+    let page = query
+        .get("page")
+        .and_then(|page| page.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+    let per_page = query
+        .get("perPage")
+        .and_then(|per_page| per_page.parse::<usize>().ok())
+        .unwrap_or(30)
+        .clamp(1, MAX_PER_PAGE);
+
+    let filter = match query.get("filter") {
+        Some(filter) if !filter.trim().is_empty() => Some(filter::parse(filter)?),
+        _ => None,
+    };
+
+    let mut filtered = Vec::with_capacity(items.len());
+    for item in items {
+        if allows(&filter, &item, context)? {
+            filtered.push(item);
+        }
+    }
+
+    if let Some(sort) = query.get("sort") {
+        sort::sort(&mut filtered, sort);
+    }
+
+    let total_items = filtered.len();
+    let items: Vec<Map<String, Value>> = filtered
+        .into_iter()
+        .skip((page - 1) * per_page)
+        .take(per_page)
+        .collect();
+
+    Ok(json!({
+        "page": page,
+        "perPage": per_page,
+        "totalItems": total_items,
+        "totalPages": total_items.div_ceil(per_page),
+        "items": items,
+    }))
+}
+
+// replaces the secrets with the masked value, like the settings endpoint does.
+fn mask_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let is_secret = matches!(key.as_str(), "secret" | "password" | "clientSecret");
+                match value {
+                    Value::String(secret) if is_secret && !secret.is_empty() => {
+                        *secret = SETTING_MASKED_VALUE.to_string();
+                    }
+                    _ => mask_secrets(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+// deep merges the patch into the settings, masked values are ignored.
+fn merge_settings(settings: &mut Value, patch: &Value) {
+    match (settings, patch) {
+        (Value::Object(settings), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match settings.get_mut(key) {
+                    Some(current) if current.is_object() && value.is_object() => {
+                        merge_settings(current, value)
+                    }
+                    _ if value == SETTING_MASKED_VALUE => {}
+                    _ => {
+                        settings.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (settings, patch) => *settings = patch.clone(),
+    }
+}
@@ -0,0 +1,42 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+// signs the claims as an HS256 JWT.
+pub(crate) fn sign(secret: &[u8], claims: &serde_json::Value) -> String {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let signing_input = format!("{}.{}", header, payload);
+
+    format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature(secret, &signing_input))
+    )
+}
+
+// returns the claims of the token if it is signed with the secret and not expired.
+pub(crate) fn verify(secret: &[u8], token: &str) -> Option<serde_json::Value> {
+    let (signing_input, signature) = token.rsplit_once('.')?;
+    let (_, payload) = signing_input.split_once('.')?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).ok()?;
+    mac.update(signing_input.as_bytes());
+    mac.verify_slice(&URL_SAFE_NO_PAD.decode(signature).ok()?)
+        .ok()?;
+
+    let claims: serde_json::Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+
+    if claims["exp"].as_i64()? <= chrono::Utc::now().timestamp() {
+        return None;
+    }
+
+    Some(claims)
+}
+
+fn signature(secret: &[u8], signing_input: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac accepts any key size");
+    mac.update(signing_input.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}